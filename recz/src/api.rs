@@ -1,4 +1,4 @@
-//! There is public traits for `Regex` and `Match` structs generated by `regn`.
+//! There is public traits for `Regex` and `Match` structs generated by `remc`.
 //! It allows to interact with multiple instances of those structs outside of
 //! place their creation.
 
@@ -6,6 +6,79 @@ pub trait RegexBytes {
     fn match_at<'h>(&self, haystack: &'h [u8], start: usize) -> Option<impl MatchBytes<'h>>;
 
     fn match_iter<'h>(&self, haystack: &'h [u8]) -> impl Iterator<Item = impl MatchBytes<'h>>;
+
+    /// Returns the longest match starting at the global `start` position of
+    /// the concatenation of the `segments`, e.g. the pieces of a rope.
+    ///
+    /// The default implementation copies the segments into one buffer and
+    /// calls [`RegexBytes::match_at`]; the regexes generated by `remc` run
+    /// their state machine across the segments without copying them.
+    fn match_segments<'h>(&self, segments: &[&'h [u8]], start: usize) -> Option<SegmentsMatch<'h>> {
+        let haystack = segments.concat();
+        let (start, end) = self
+            .match_at(&haystack, start)
+            .map(|m| (m.start(), m.end()))?;
+        Some(SegmentsMatch::new(segments, start, end))
+    }
+}
+
+/// A match spanning one or more segments of a haystack, returned by
+/// [`RegexBytes::match_segments`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentsMatch<'h> {
+    pieces: Vec<&'h [u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl<'h> SegmentsMatch<'h> {
+    /// Creates the match of the `start..end` global range of the `segments`.
+    pub fn new(segments: &[&'h [u8]], start: usize, end: usize) -> Self {
+        let mut pieces = Vec::new();
+        let mut offset = 0;
+        for segment in segments {
+            let next_offset = offset + segment.len();
+            if start < next_offset && offset < end {
+                let piece_start = start.saturating_sub(offset);
+                let piece_end = end.min(next_offset) - offset;
+                pieces.push(&segment[piece_start..piece_end]);
+            }
+            offset = next_offset;
+        }
+        Self { pieces, start, end }
+    }
+
+    /// Returns the matched parts of the segments in order. An empty match
+    /// has no pieces.
+    #[inline]
+    pub fn pieces(&self) -> &[&'h [u8]] {
+        &self.pieces
+    }
+
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    #[inline]
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
 }
 
 pub trait MatchBytes<'h> {
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+/// An error of compiling a [`Regex`](crate::Regex) at runtime.
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    /// The pattern is malformed.
+    #[error(transparent)]
+    Syntax(#[from] resy::Error),
+
+    /// The repetitions of the pattern expand to too many states.
+    #[error(transparent)]
+    Translation(#[from] regr::Error),
+}
@@ -1,4 +1,10 @@
 mod api;
-pub use api::{MatchBytes, MatchStr, RegexBytes, RegexStr};
+pub use api::{MatchBytes, MatchStr, RegexBytes, RegexStr, SegmentsMatch};
+
+mod error;
+pub use error::Error;
+
+mod runtime;
+pub use runtime::{Match, Matches, Regex, RegexBuilder};
 
 pub use remc::re;
@@ -0,0 +1,315 @@
+//! Regular expressions compiled at runtime, for patterns that aren't known at
+//! compile time and can't be passed to the [`re!`](crate::re) macro.
+//!
+//! The pattern goes through the same pipeline as in the macro, i.e. it's
+//! parsed into a hir, translated into an NFA and determinized, but instead of
+//! generating code, the DFA is flattened into a transition table that is
+//! walked by a small interpreter.
+
+use redt::SetU8;
+use regr::{Arena, Graph, Inst, Translator};
+use resy::Parser;
+use resy::enc::Utf8Encoder;
+use std::collections::HashMap;
+
+use crate::api::{MatchBytes, MatchStr, RegexStr};
+use crate::error::Error;
+
+/// Builder of a [`Regex`] compiled at runtime.
+#[derive(Debug, Clone)]
+pub struct RegexBuilder {
+    pattern: String,
+    case_insensitive: bool,
+    alphabet: SetU8,
+}
+
+impl RegexBuilder {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            case_insensitive: false,
+            alphabet: !SetU8::empty(),
+        }
+    }
+
+    /// Makes the regex case insensitive, like the `i` flag of the `re!` macro
+    /// does.
+    pub fn with_case_insensitive(mut self, value: bool) -> Self {
+        self.case_insensitive = value;
+        self
+    }
+
+    /// Restricts the bytes the regex can match to the `alphabet`, like
+    /// [`Parser::with_alphabet`] does. A pattern matching only bytes out of
+    /// the alphabet fails to build.
+    pub fn with_alphabet(mut self, alphabet: SetU8) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Compiles the pattern into a [`Regex`].
+    ///
+    /// The arenas of the NFA and DFA graphs live only during the compilation,
+    /// so the regex owns nothing but the flattened transition table.
+    pub fn build(&self) -> Result<Regex, Error> {
+        let parser = Parser::new(Utf8Encoder)
+            .with_case_insensitive(self.case_insensitive)
+            .with_alphabet(self.alphabet.clone());
+        let hir = parser.parse(&self.pattern).map_err(|err| *err)?;
+
+        let mut nfa_arena = Arena::new();
+        let nfa = Graph::new_in(&mut nfa_arena);
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa).translate(&hir, start_node, end_node)?;
+
+        let mut dfa_arena = Arena::new();
+        let dfa = nfa.determinize_in(&mut dfa_arena);
+        Ok(Regex::from_dfa(&dfa))
+    }
+}
+
+/// State ID of the transition table meaning there is no transition.
+const INVALID: u32 = u32::MAX;
+
+/// Regular expression compiled at runtime with [`RegexBuilder`].
+///
+/// It always returns the leftmost-longest matches; the capture groups aren't
+/// supported.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    table: Vec<[u32; 1 << u8::BITS]>,
+    finals: Vec<bool>,
+    finals_at_end: Vec<bool>,
+    start_id: u32,
+    text_start_id: u32,
+}
+
+impl Regex {
+    /// Compiles the pattern with the default options. It's a shortcut for
+    /// `RegexBuilder::new(pattern).build()`.
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        RegexBuilder::new(pattern).build()
+    }
+
+    fn from_dfa(dfa: &Graph<'_>) -> Self {
+        let mut id_map = HashMap::new();
+        dfa.for_each_node(|node| {
+            let id = id_map.len() as u32;
+            id_map.insert(node.uid(), id);
+        });
+        let start_id = id_map[&dfa.start_node().uid()];
+        let mut text_start_id = start_id;
+        let mut table = vec![[INVALID; 1 << u8::BITS]; id_map.len()];
+        let mut finals = vec![false; id_map.len()];
+        let mut finals_at_end = vec![false; id_map.len()];
+        dfa.for_each_node(|node| {
+            let id = id_map[&node.uid()];
+            finals[id as usize] = node.is_final();
+            for (target, tr) in node.targets().iter() {
+                let target_id = id_map[&target.uid()];
+                if !tr.is_epsilon() {
+                    for byte in tr.symbols() {
+                        table[id as usize][byte as usize] = target_id;
+                    }
+                    continue;
+                }
+                for inst in tr.instructs() {
+                    match inst {
+                        Inst::AssertStartText if id == start_id => text_start_id = target_id,
+                        Inst::AssertEndText if target.is_final() => {
+                            finals_at_end[id as usize] = true;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        });
+        Self {
+            table,
+            finals,
+            finals_at_end,
+            start_id,
+            text_start_id,
+        }
+    }
+
+    /// Returns the longest match starting exactly at the `start` position of
+    /// the haystack.
+    ///
+    /// The `^` anchor matches only if `start` is `0`, and the `$` anchor
+    /// matches only at the end of the haystack. Returns `None` if `start` is
+    /// out of the haystack or isn't at a char boundary.
+    pub fn match_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        let mut state = if start == 0 {
+            self.text_start_id
+        } else {
+            self.start_id
+        };
+        let rest = haystack.get(start..)?;
+        let mut final_index = self.finals[state as usize].then_some(0);
+        let bytes = rest.as_bytes();
+        for (i, byte) in bytes.iter().enumerate() {
+            state = self.table[state as usize][*byte as usize];
+            if state == INVALID {
+                break;
+            }
+            if self.finals[state as usize] {
+                final_index = Some(i + 1);
+            }
+        }
+        if state != INVALID && self.finals_at_end[state as usize] {
+            final_index = Some(bytes.len());
+        }
+        final_index.map(|index| Match {
+            capture: &rest[..index],
+            start,
+        })
+    }
+
+    /// Returns `true` if there is a match anywhere in the haystack.
+    #[inline]
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.find(haystack).is_some()
+    }
+
+    /// Returns the leftmost match in the haystack, trying every char boundary
+    /// in order as the start of the match.
+    #[inline]
+    pub fn find<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+        self.find_iter(haystack).next()
+    }
+
+    /// Returns an iterator over all non-overlapping leftmost-longest matches
+    /// in the haystack.
+    #[inline]
+    pub fn find_iter<'r, 'h>(&'r self, haystack: &'h str) -> Matches<'r, 'h> {
+        Matches {
+            regex: self,
+            haystack,
+            start: 0,
+            last_end: None,
+        }
+    }
+}
+
+impl RegexStr for Regex {
+    #[inline]
+    fn match_at<'h>(&self, haystack: &'h str, start: usize) -> Option<impl MatchStr<'h>> {
+        Regex::match_at(self, haystack, start)
+    }
+
+    #[inline]
+    fn match_iter<'h>(&self, haystack: &'h str) -> impl Iterator<Item = impl MatchStr<'h>> {
+        self.find_iter(haystack)
+    }
+}
+
+/// A match of a [`Regex`] in a haystack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'h> {
+    capture: &'h str,
+    start: usize,
+}
+
+impl<'h> Match<'h> {
+    #[inline]
+    pub fn as_str(&self) -> &'h str {
+        self.capture
+    }
+
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.start + self.capture.len()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.capture.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.capture.is_empty()
+    }
+
+    #[inline]
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.end()
+    }
+}
+
+impl<'h> MatchBytes<'h> for Match<'h> {
+    #[inline]
+    fn as_bytes(&self) -> &'h [u8] {
+        self.capture.as_bytes()
+    }
+
+    #[inline]
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    #[inline]
+    fn end(&self) -> usize {
+        Match::end(self)
+    }
+}
+
+impl<'h> MatchStr<'h> for Match<'h> {
+    #[inline]
+    fn as_str(&self) -> &'h str {
+        self.capture
+    }
+}
+
+/// An iterator over all non-overlapping leftmost-longest matches in a
+/// haystack.
+///
+/// After an empty match, the search goes on from the next char. An empty match
+/// ending where the previous match ends is skipped, like in the `regex` crate,
+/// e.g. `a*` over `"bab"` yields `0..0`, `1..2` and `3..3`, but not `2..2`.
+///
+/// It is created by the [`Regex::find_iter`] method.
+#[derive(Debug)]
+pub struct Matches<'r, 'h> {
+    regex: &'r Regex,
+    haystack: &'h str,
+    start: usize,
+    last_end: Option<usize>,
+}
+
+impl<'h> Iterator for Matches<'_, 'h> {
+    type Item = Match<'h>;
+
+    fn next(&mut self) -> Option<Match<'h>> {
+        while self.start <= self.haystack.len() {
+            let start = self.start;
+            let next_char_start = start
+                + self.haystack[start..]
+                    .chars()
+                    .next()
+                    .map_or(1, char::len_utf8);
+            if let Some(m) = self.regex.match_at(self.haystack, start)
+                && !(m.is_empty() && self.last_end == Some(m.end()))
+            {
+                self.start = if m.is_empty() {
+                    next_char_start
+                } else {
+                    m.end()
+                };
+                self.last_end = Some(m.end());
+                return Some(m);
+            }
+            self.start = next_char_start;
+        }
+        None
+    }
+}
+
+impl std::iter::FusedIterator for Matches<'_, '_> {}
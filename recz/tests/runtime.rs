@@ -0,0 +1,134 @@
+use pretty_assertions::assert_eq;
+use recz::{Error, MatchBytes, MatchStr, Regex, RegexBuilder, RegexBytes, RegexStr};
+use redt::SetU8;
+
+#[test]
+fn runtime_digits() {
+    let re = RegexBuilder::new("[0-9]+").build().unwrap();
+    let m = re.find("abc123").unwrap();
+    assert_eq!(m.as_str(), "123");
+    assert_eq!(m.range(), 3..6);
+    assert!(re.match_at("abc123", 0).is_none());
+    assert_eq!(re.match_at("abc123", 4).unwrap().as_str(), "23");
+    assert!(!re.is_match("abc"));
+
+    let found: Vec<_> = re.find_iter("1 22 333").map(|m| m.as_str()).collect();
+    assert_eq!(found, ["1", "22", "333"]);
+}
+
+#[test]
+fn runtime_traits() {
+    fn collect<'h>(re: &impl RegexStr, haystack: &'h str) -> Vec<(&'h str, usize)> {
+        re.match_iter(haystack)
+            .map(|m| (m.as_str(), m.start()))
+            .collect()
+    }
+
+    let re = Regex::new("ab|b").unwrap();
+    assert_eq!(collect(&re, "abb"), [("ab", 0), ("b", 2)]);
+    let m = RegexStr::match_at(&re, "xab", 1).unwrap();
+    assert_eq!(m.as_bytes(), b"ab");
+    assert_eq!(m.end(), 3);
+}
+
+#[test]
+fn runtime_bytes_traits() {
+    struct Literal(&'static [u8]);
+    struct LiteralMatch<'h>(&'h [u8], usize);
+
+    impl<'h> MatchBytes<'h> for LiteralMatch<'h> {
+        fn as_bytes(&self) -> &'h [u8] {
+            self.0
+        }
+        fn start(&self) -> usize {
+            self.1
+        }
+        fn end(&self) -> usize {
+            self.1 + self.0.len()
+        }
+    }
+
+    impl RegexBytes for Literal {
+        fn match_at<'h>(&self, haystack: &'h [u8], start: usize) -> Option<impl MatchBytes<'h>> {
+            let bytes = haystack.get(start..start + self.0.len())?;
+            (bytes == self.0).then_some(LiteralMatch(bytes, start))
+        }
+        fn match_iter<'h>(&self, haystack: &'h [u8]) -> impl Iterator<Item = impl MatchBytes<'h>> {
+            (0..haystack.len()).filter_map(move |start| self.match_at(haystack, start))
+        }
+    }
+
+    let re = Literal(b"lo wo");
+    let segments: [&[u8]; 3] = [b"hel", b"lo", b" world"];
+    let m = re.match_segments(&segments, 3).unwrap();
+    assert_eq!(m.range(), 3..8);
+    assert_eq!(m.pieces(), [&b"lo"[..], b" wo"]);
+    assert_eq!(re.match_segments(&segments, 2), None);
+    assert_eq!(re.match_segments(&[], 0), None);
+}
+
+#[test]
+fn runtime_options() {
+    let re = RegexBuilder::new("hello")
+        .with_case_insensitive(true)
+        .build()
+        .unwrap();
+    assert_eq!(re.find("say HeLLo").unwrap().start(), 4);
+
+    let re = Regex::new("^a+$").unwrap();
+    assert_eq!(re.find("aaa").unwrap().as_str(), "aaa");
+    assert!(re.find("aab").is_none());
+    assert!(re.match_at("baa", 1).is_none());
+
+    // the start out of the haystack or inside a char doesn't match
+    let re = Regex::new("[^a]*").unwrap();
+    assert_eq!(re.match_at("aя", 1).unwrap().as_str(), "я");
+    assert!(re.match_at("aя", 2).is_none());
+    assert_eq!(re.match_at("aя", 3).unwrap().range(), 3..3);
+    assert!(re.match_at("aя", 4).is_none());
+
+    let re = Regex::new("x*").unwrap();
+    let found: Vec<_> = re.find_iter("aяx").map(|m| m.range()).collect();
+    assert_eq!(found, [0..0, 1..1, 3..4]);
+
+    // an empty match right after the previous one is skipped
+    let re = Regex::new("a*").unwrap();
+    let found: Vec<_> = re.find_iter("bab").map(|m| m.range()).collect();
+    assert_eq!(found, [0..0, 1..2, 3..3]);
+
+    // a dot matches only the printable ASCII chars
+    let re = RegexBuilder::new("a.+")
+        .with_alphabet(SetU8::from(b' '..=b'~'))
+        .build()
+        .unwrap();
+    assert_eq!(re.find("xa bя").unwrap().as_str(), "a b");
+    assert!(
+        RegexBuilder::new("я")
+            .with_alphabet(SetU8::from(b' '..=b'~'))
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn runtime_errors() {
+    let Error::Syntax(err) = Regex::new("a{3,1}").unwrap_err() else {
+        panic!("expected a syntax error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "repetition expression `{n,m}` expects that `n <= m`"
+    );
+    assert_eq!(err.error_span(), 1..6);
+    assert!(RegexBuilder::new("(a").build().is_err());
+
+    let err = Regex::new("a{100000000}").unwrap_err();
+    assert_eq!(
+        err,
+        Error::Translation(regr::Error::ExpansionLimit { max: 1 << 20 })
+    );
+    assert_eq!(
+        err.to_string(),
+        "repetitions expand the graph to more than 1048576 nodes"
+    );
+}
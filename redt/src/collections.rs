@@ -30,3 +30,44 @@ cfg_if::cfg_if! {
         pub type SetIter<'a, T> = ::std::collections::btree_set::Iter<'a, T>;
     }
 }
+
+/// Iteration in the key order, regardless of the hasher the [`Map`] or
+/// [`Set`] is built with.
+///
+/// It's useful where the output must be deterministic, e.g. for printed
+/// graphs and snapshots. Every call collects the entries into a vector and
+/// sorts it, so it costs `O(n log n)` and shouldn't be used in hot paths.
+pub trait IterSorted {
+    type Item<'a>
+    where
+        Self: 'a;
+
+    /// Returns an iterator over the entries sorted by their keys.
+    fn iter_sorted(&self) -> ::std::vec::IntoIter<Self::Item<'_>>;
+}
+
+impl<K: Ord + ::std::hash::Hash, V> IterSorted for Map<K, V> {
+    type Item<'a>
+        = (&'a K, &'a V)
+    where
+        Self: 'a;
+
+    fn iter_sorted(&self) -> ::std::vec::IntoIter<(&K, &V)> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        entries.into_iter()
+    }
+}
+
+impl<T: Ord + ::std::hash::Hash> IterSorted for Set<T> {
+    type Item<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    fn iter_sorted(&self) -> ::std::vec::IntoIter<&T> {
+        let mut items: Vec<_> = self.iter().collect();
+        items.sort_unstable();
+        items.into_iter()
+    }
+}
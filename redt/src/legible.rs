@@ -8,6 +8,20 @@ pub trait Legible {
     fn display(&self) -> impl std::fmt::Display;
 }
 
+/// Formats every byte of the slice the way a single byte is formatted by
+/// [`Legible::display`], i.e. `'a'` for printable ASCII chars and `D1h`
+/// otherwise, separated by spaces.
+pub fn format_bytes(bytes: &[u8]) -> String {
+    let mut output = String::new();
+    for (index, byte) in bytes.iter().enumerate() {
+        if index != 0 {
+            output.push(' ');
+        }
+        write!(output, "{}", byte.display()).unwrap();
+    }
+    output
+}
+
 /// A wrapper around an integer that can be used within formatting strings for
 /// better legibility.
 ///
@@ -1,7 +1,7 @@
 mod collections;
-pub use collections::{Map, MapIter, MapKeyIter, Set, SetIter};
+pub use collections::{IterSorted, Map, MapIter, MapKeyIter, Set, SetIter};
 
-mod legible;
+pub mod legible;
 pub use legible::Legible;
 
 mod macros;
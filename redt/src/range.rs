@@ -87,10 +87,10 @@ impl<T: Copy> Range<T> {
 }
 
 impl<T: Step> Range<T> {
-    /// Returns the width of the range. If width is greater than `T::MAX`, it
-    /// returns `None`.
+    /// Returns the width of the range. If width is greater than the maximum
+    /// of `T::Distance`, e.g. `T::MAX` for integers, it returns `None`.
     #[inline]
-    pub fn width(&self) -> Option<T> {
+    pub fn width(&self) -> Option<T::Distance> {
         self.last.steps_between(self.start).forward(1)
     }
 }
@@ -168,6 +168,46 @@ impl<T: Step + Ord> Range<T> {
             None
         }
     }
+
+    /// Returns the common elements of the two ranges, or `None` if they don't
+    /// intersect.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if self.intersects(other) {
+            Some(Self {
+                start: self.start().max(other.start()),
+                last: self.last().min(other.last()),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the elements of `self` that are not in `other` as up to two
+    /// ranges: the one at left of `other`, and the one at right of it.
+    ///
+    /// If the ranges don't intersect, `self` is returned as the left piece if
+    /// it's at left of `other`, and as the right piece otherwise.
+    pub fn subtract(&self, other: &Self) -> (Option<Self>, Option<Self>) {
+        if self.is_at_left(other) {
+            return (Some(*self), None);
+        }
+        if self.is_at_right(other) {
+            return (None, Some(*self));
+        }
+        let left = if self.start() < other.start() {
+            let last = other.start().backward(1).unwrap();
+            Some(Self::new_unchecked(self.start(), last))
+        } else {
+            None
+        };
+        let right = if other.last() < self.last() {
+            let start = other.last().forward(1).unwrap();
+            Some(Self::new_unchecked(start, self.last()))
+        } else {
+            None
+        };
+        (left, right)
+    }
 }
 
 impl<T: Step + Ord + std::fmt::Debug> Range<T> {
@@ -239,6 +279,9 @@ impl std::fmt::Display for ByteRangeLegible {
     }
 }
 
+/// Formats the range the same way as transitions of a graph do: printable
+/// characters are quoted, other bytes are written as `NNh`, and a range of a
+/// single element is written without the dash.
 impl std::fmt::Display for Range<u8> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.display().fmt(f)
@@ -2,7 +2,7 @@ use crate::range::Range;
 use crate::step::Step;
 
 /// A set of non-overlapping inclusive ranges, stored in increasing order.
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct RangeList<T> {
     ranges: Vec<Range<T>>,
 }
@@ -37,6 +37,33 @@ impl<T> RangeList<T> {
     pub fn ranges(&self) -> &[Range<T>] {
         &self.ranges
     }
+
+    /// Returns an iterator over all ranges in this range list in decreasing
+    /// order.
+    #[inline]
+    pub fn iter_ranges_rev(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter().rev()
+    }
+}
+
+impl<T: Copy + Ord> RangeList<T> {
+    /// Checks if any range of this range list contains the `value`.
+    ///
+    /// It takes `O(log n)` time, where `n` is the number of ranges.
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|r| r.start() <= value);
+        index > 0 && value <= self.ranges[index - 1].last()
+    }
+
+    /// Checks if all values of the `range` are contained in this range list.
+    /// Adjacent ranges of the list are merged, so the `range` has to lie
+    /// within a single one of them.
+    ///
+    /// It takes `O(log n)` time, where `n` is the number of ranges.
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        let index = self.ranges.partition_point(|r| r.start() <= range.start());
+        index > 0 && range.last() <= self.ranges[index - 1].last()
+    }
 }
 
 impl<T: Step + Ord> RangeList<T> {
@@ -129,6 +156,7 @@ impl<T: Step + Ord> RangeList<T> {
             .ranges
             .binary_search_by(|r| r.last().cmp(&other.start())) // start <= last
         {
+            Ok(index) if self.ranges[index].start() == other.start() => index,
             Ok(index) => {
                 self.ranges[index] = Range::new_unchecked(
                     self.ranges[index].start(),
@@ -160,6 +188,7 @@ impl<T: Step + Ord> RangeList<T> {
             .ranges
             .binary_search_by(|r| r.start().cmp(&other.last()))
         {
+            Ok(index) if self.ranges[index].last() == other.last() => index + 1,
             Ok(index) => {
                 self.ranges[index] = Range::new_unchecked(
                     other.last().forward(1).unwrap(),
@@ -188,6 +217,39 @@ impl<T: Step + Ord> RangeList<T> {
             drop(self.ranges.drain(start..end));
         }
     }
+
+    /// Keeps only the portions of ranges that are covered by both this and
+    /// the `other` range lists.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The range list to intersect this range list with
+    pub fn intersect(&mut self, other: &RangeList<T>) {
+        *self = self.intersection(other);
+    }
+
+    /// Returns a new range list containing only the portions of ranges that
+    /// are covered by both this and the `other` range lists.
+    pub fn intersection(&self, other: &RangeList<T>) -> RangeList<T> {
+        let mut ranges = Vec::with_capacity(self.ranges.len().max(other.ranges.len()));
+        let mut i = 0;
+        let mut j = 0;
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let lhs = self.ranges[i];
+            let rhs = other.ranges[j];
+            let start = lhs.start().max(rhs.start());
+            let last = lhs.last().min(rhs.last());
+            if start <= last {
+                ranges.push(Range::new_unchecked(start, last));
+            }
+            if lhs.last() < rhs.last() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeList { ranges }
+    }
 }
 
 impl<T> std::default::Default for RangeList<T> {
@@ -1,4 +1,4 @@
-use crate::{Legible, RangeU8, Step};
+use crate::{Legible, RangeList, RangeU8, Step};
 use std::fmt::Write;
 use std::ops::Deref;
 use std::ops::RangeInclusive;
@@ -446,6 +446,34 @@ impl SetU8 {
         self.chunks.iter().all(|&chunk| chunk == 0)
     }
 
+    /// Checks if the set contains all the 256 bytes.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.chunks.iter().all(|&chunk| chunk == Chunk::MAX)
+    }
+
+    /// Checks if the set contains all the 256 bytes. The same as
+    /// [`SetU8::is_full`].
+    #[inline]
+    pub fn contains_all(&self) -> bool {
+        self.is_full()
+    }
+
+    /// Returns the number of bytes in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns a new set containing all the bytes that are not in this one.
+    #[inline]
+    pub fn complement(&self) -> SetU8 {
+        !self.clone()
+    }
+
     /// Returns an iterator over the bytes in the set.
     pub fn bytes(&self) -> impl Iterator<Item = u8> {
         ByteIter::new(self)
@@ -455,27 +483,79 @@ impl SetU8 {
     pub fn ranges(&self) -> impl Iterator<Item = RangeU8> {
         RangeIter::new(self)
     }
+
+    /// Returns the minimal list of the inclusive byte ranges in the set, i.e.
+    /// adjacent ranges are coalesced into maximal ones. Equal sets always give
+    /// equal lists.
+    pub fn normalized_ranges(&self) -> Vec<RangeU8> {
+        let mut ranges: Vec<RangeU8> = Vec::new();
+        for range in self.ranges() {
+            match ranges.last_mut() {
+                Some(last) if last.last().checked_steps_between(range.start()) == Some(1) => {
+                    *last = RangeU8::new(last.start(), range.last());
+                }
+                _ => ranges.push(range),
+            }
+        }
+        ranges
+    }
+
+    /// Creates a set of all the bytes of the range list.
+    pub fn from_range_list(list: &RangeList<u8>) -> Self {
+        let mut set = SetU8::empty();
+        for range in list.ranges() {
+            crate::ops::Includable::include(&mut set, *range);
+        }
+        set
+    }
+
+    /// Returns the bytes of the set as a range list. It's the inverse of
+    /// [`SetU8::from_range_list`], because both of them keep the ranges
+    /// coalesced.
+    pub fn to_range_list(&self) -> RangeList<u8> {
+        RangeList::from(self.ranges())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SetU8 {
+    /// Serializes the set compactly as the list of its normalized ranges, each
+    /// of them is a `(start, last)` pair.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.normalized_ranges()
+                .iter()
+                .map(|range| (range.start(), range.last())),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SetU8 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use crate::ops::Includable;
+
+        let mut set = SetU8::empty();
+        for (start, last) in Vec::<(u8, u8)>::deserialize(deserializer)? {
+            if start > last {
+                return Err(serde::de::Error::custom(format!(
+                    "invalid byte range {start}..={last}"
+                )));
+            }
+            set.include(start..=last);
+        }
+        Ok(set)
+    }
 }
 
 impl std::fmt::Display for SetU8 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_char('[')?;
-        let mut iter = self.ranges();
-        let mut range = iter.next();
-        while let Some(cur_range) = range {
-            if let Some(next_range) = iter.next() {
-                if cur_range.last().steps_between(next_range.start()) == 1 {
-                    range = Some(RangeU8::new(cur_range.start(), next_range.last()));
-                    continue;
-                } else {
-                    std::fmt::Display::fmt(&cur_range.display(), f)?;
-                    f.write_str(" | ")?;
-                    range = Some(next_range);
-                }
-            } else {
-                std::fmt::Display::fmt(&cur_range.display(), f)?;
-                break;
+        for (i, range) in self.normalized_ranges().iter().enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
             }
+            std::fmt::Display::fmt(&range.display(), f)?;
         }
         f.write_char(']')
     }
@@ -485,6 +565,7 @@ pub struct ByteIter<T> {
     set: T,
     chunk: Chunk,
     shift: u32,
+    mask: Chunk,
 }
 
 impl<T> ByteIter<T>
@@ -492,11 +573,21 @@ where
     T: Deref<Target = SetU8>,
 {
     pub fn new(set: T) -> Self {
-        let chunk = set.chunks[0];
+        Self::with_mask(set, 0)
+    }
+
+    /// Creates an iterator over the bytes that are NOT in the `set`.
+    pub fn complement(set: T) -> Self {
+        Self::with_mask(set, Chunk::MAX)
+    }
+
+    fn with_mask(set: T, mask: Chunk) -> Self {
+        let chunk = set.chunks[0] ^ mask;
         Self {
             set,
             chunk,
             shift: 0,
+            mask,
         }
     }
 }
@@ -519,7 +610,7 @@ where
             }
             if self.shift < SHIFT_OVERFLOW - 64 {
                 self.shift += 64;
-                self.chunk = self.set.chunks[self.shift as usize >> 6];
+                self.chunk = self.set.chunks[self.shift as usize >> 6] ^ self.mask;
                 continue;
             }
             break;
@@ -532,6 +623,7 @@ pub struct RangeIter<T> {
     set: T,
     chunk: Chunk,
     shift: u32,
+    mask: Chunk,
 }
 
 impl<T> RangeIter<T>
@@ -539,11 +631,21 @@ where
     T: Deref<Target = SetU8>,
 {
     pub fn new(set: T) -> Self {
-        let chunk = set.chunks[0];
+        Self::with_mask(set, 0)
+    }
+
+    /// Creates an iterator over the ranges that are NOT in the `set`.
+    pub fn complement(set: T) -> Self {
+        Self::with_mask(set, Chunk::MAX)
+    }
+
+    fn with_mask(set: T, mask: Chunk) -> Self {
+        let chunk = set.chunks[0] ^ mask;
         Self {
             set,
             chunk,
             shift: 0,
+            mask,
         }
     }
 }
@@ -573,7 +675,7 @@ where
 
             if self.shift < SHIFT_OVERFLOW - 64 {
                 self.shift += 64;
-                self.chunk = self.set.chunks[self.shift as usize >> 6];
+                self.chunk = self.set.chunks[self.shift as usize >> 6] ^ self.mask;
                 continue;
             }
             break;
@@ -1,12 +1,33 @@
-use num_traits::Num;
+use num_traits::{Num, One, Zero};
 
 /// This trait adds some functionality needed by [`crate::Range`] type.
 ///
 /// It could be based on [`std::iter::Step`] trait, but it is unstable yet.
-pub trait Step: Num + Copy {
+pub trait Step: Copy {
+    /// Type of the number of steps between two values. For integers, it's the
+    /// integer type itself.
+    type Distance: Step<Distance = Self::Distance> + Num + TryInto<isize>;
+
     /// Returns the number of steps required to get from `self` to `other` or
-    /// vice versa.
-    fn steps_between(&self, other: Self) -> Self;
+    /// vice versa. It is never negative, so the order of the values is lost,
+    /// e.g. `255u8.steps_between(0)` and `0u8.steps_between(255)` are both
+    /// `255`.
+    fn steps_between(&self, other: Self) -> Self::Distance;
+
+    /// Returns the signed number of steps required to get from `self` to
+    /// `other`, i.e. it is negative if `other` precedes `self`.
+    ///
+    /// Returns `None` if the number doesn't fit into `isize`.
+    ///
+    /// The default implementation takes the sign from whether `other` is
+    /// reached by stepping backward from `self`.
+    fn checked_steps_between(&self, other: Self) -> Option<isize> {
+        let steps: isize = self.steps_between(other).try_into().ok()?;
+        let is_behind = self
+            .backward(steps as usize)
+            .is_some_and(|prev| prev.steps_between(other).is_zero());
+        Some(if is_behind { -steps } else { steps })
+    }
 
     /// Returns the value that would be obtained by taking the _successor_ of
     /// `self` count times.
@@ -22,17 +43,24 @@ pub trait Step: Num + Copy {
 
     /// Checks if there is one step between the two values.
     fn adjoins(&self, other: Self) -> bool {
-        self.steps_between(other) == Self::one()
+        self.steps_between(other) == Self::Distance::one()
     }
 }
 
 macro_rules! impl_step_for {
     ($type:ty) => {
         impl Step for $type {
+            type Distance = Self;
+
             fn steps_between(&self, other: Self) -> Self {
                 self.abs_diff(other)
             }
 
+            fn checked_steps_between(&self, other: Self) -> Option<isize> {
+                let steps = isize::try_from(self.abs_diff(other)).ok()?;
+                Some(if other < *self { -steps } else { steps })
+            }
+
             fn forward(&self, count: usize) -> Option<Self> {
                 if let Ok(count) = Self::try_from(count) {
                     self.checked_add(count)
@@ -56,3 +84,54 @@ impl_step_for!(u8);
 impl_step_for!(u16);
 impl_step_for!(u32);
 impl_step_for!(u64);
+
+/// Number of the surrogate code points `U+D800..=U+DFFF`, which are not valid
+/// chars.
+const SURROGATES_LEN: u32 = 0xE000 - 0xD800;
+
+/// Index of the char among all valid chars, i.e. its code point without the
+/// surrogate gap.
+#[inline]
+fn char_index(c: char) -> u32 {
+    let code = c as u32;
+    if code < 0xD800 {
+        code
+    } else {
+        code - SURROGATES_LEN
+    }
+}
+
+/// Converts an index among all valid chars back to the char.
+#[inline]
+fn char_from_index(index: u32) -> Option<char> {
+    let code = if index < 0xD800 {
+        index
+    } else {
+        index.checked_add(SURROGATES_LEN)?
+    };
+    char::from_u32(code)
+}
+
+/// Chars step over the surrogate gap `U+D800..=U+DFFF`, so `'\u{D7FF}'` and
+/// `'\u{E000}'` are adjacent.
+impl Step for char {
+    type Distance = u32;
+
+    fn steps_between(&self, other: Self) -> u32 {
+        char_index(*self).abs_diff(char_index(other))
+    }
+
+    fn checked_steps_between(&self, other: Self) -> Option<isize> {
+        char_index(*self).checked_steps_between(char_index(other))
+    }
+
+    fn forward(&self, count: usize) -> Option<Self> {
+        let count = u32::try_from(count).ok()?;
+        char_from_index(char_index(*self).checked_add(count)?)
+    }
+
+    fn backward(&self, count: usize) -> Option<Self> {
+        let count = u32::try_from(count).ok()?;
+        char_from_index(char_index(*self).checked_sub(count)?)
+    }
+}
@@ -0,0 +1,25 @@
+use pretty_assertions::assert_eq;
+use redt::{IterSorted, Map, Set};
+
+#[test]
+fn map_iter_sorted() {
+    let mut map = Map::new();
+    for key in [5, 1, 4, 2, 3] {
+        map.insert(key, key * 10);
+    }
+    assert_eq!(
+        map.iter_sorted().collect::<Vec<_>>(),
+        vec![(&1, &10), (&2, &20), (&3, &30), (&4, &40), (&5, &50)]
+    );
+    assert_eq!(Map::<u8, u8>::new().iter_sorted().count(), 0);
+}
+
+#[test]
+fn set_iter_sorted() {
+    let set: Set<_> = ["b", "d", "a", "c"].into_iter().collect();
+    assert_eq!(
+        set.iter_sorted().collect::<Vec<_>>(),
+        vec![&"a", &"b", &"c", &"d"]
+    );
+    assert_eq!(Set::<u8>::new().iter_sorted().count(), 0);
+}
@@ -1,4 +1,5 @@
 use redt::Legible;
+use redt::legible::format_bytes;
 
 #[test]
 fn u8_display() {
@@ -25,3 +26,17 @@ fn u8_arr_display() {
         r#""\x00\x01\x02'\"\\""#
     );
 }
+
+#[test]
+fn bytes_format() {
+    assert_eq!(format_bytes(b""), "");
+    assert_eq!(format_bytes(b"a"), "'a'");
+    assert_eq!(format_bytes(b"ab"), "'a' 'b'");
+    assert_eq!(
+        format_bytes(&[b'a', 0xD1, b' ', b'\n', b'\'', 0x7F]),
+        r"'a' D1h ' ' 0Ah '\'' 7Fh"
+    );
+    for byte in u8::MIN..=u8::MAX {
+        assert_eq!(format_bytes(&[byte]), byte.display().to_string());
+    }
+}
@@ -1,3 +1,4 @@
+mod collections;
 mod legible;
 mod macros;
 mod range;
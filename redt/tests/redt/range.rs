@@ -196,6 +196,65 @@ fn range_merge_panic() {
     r_0_1.merge(&r_5_7);
 }
 
+#[test]
+fn range_intersection() {
+    let r_0_1 = Range::from(b'0'..=b'1');
+    let r_2_4 = Range::from(b'2'..=b'4');
+    let r_3_5 = Range::from(b'3'..=b'5');
+    let r_0_9 = Range::from(b'0'..=b'9');
+    let r_4 = Range::from(b'4');
+
+    // disjoint
+    assert_eq!(r_0_1.intersection(&r_2_4), None);
+    assert_eq!(r_2_4.intersection(&r_0_1), None);
+    assert_eq!(r_4.intersection(&r_0_1), None);
+
+    // partially overlapping
+    assert_eq!(r_2_4.intersection(&r_3_5), Some((b'3'..=b'4').into()));
+    assert_eq!(r_3_5.intersection(&r_2_4), Some((b'3'..=b'4').into()));
+
+    // nested
+    assert_eq!(r_0_9.intersection(&r_2_4), Some(r_2_4));
+    assert_eq!(r_2_4.intersection(&r_0_9), Some(r_2_4));
+    assert_eq!(r_2_4.intersection(&r_4), Some(r_4));
+    assert_eq!(r_4.intersection(&r_4), Some(r_4));
+}
+
+#[test]
+fn range_subtract() {
+    let r_0_1 = Range::from(b'0'..=b'1');
+    let r_2_4 = Range::from(b'2'..=b'4');
+    let r_3_5 = Range::from(b'3'..=b'5');
+    let r_0_9 = Range::from(b'0'..=b'9');
+    let r_4 = Range::from(b'4');
+
+    // disjoint
+    assert_eq!(r_0_1.subtract(&r_2_4), (Some(r_0_1), None));
+    assert_eq!(r_2_4.subtract(&r_0_1), (None, Some(r_2_4)));
+    assert_eq!(r_4.subtract(&r_0_1), (None, Some(r_4)));
+
+    // partially overlapping
+    assert_eq!(r_2_4.subtract(&r_3_5), (Some(Range::from(b'2')), None));
+    assert_eq!(r_3_5.subtract(&r_2_4), (None, Some(Range::from(b'5'))));
+
+    // nested
+    assert_eq!(
+        r_0_9.subtract(&r_2_4),
+        (Some(r_0_1), Some((b'5'..=b'9').into()))
+    );
+    assert_eq!(r_2_4.subtract(&r_0_9), (None, None));
+    assert_eq!(r_2_4.subtract(&r_4), (Some((b'2'..=b'3').into()), None));
+    assert_eq!(r_4.subtract(&r_4), (None, None));
+
+    // at the bounds of the type
+    let full = Range::new(u8::MIN, u8::MAX);
+    assert_eq!(full.subtract(&full), (None, None));
+    assert_eq!(
+        full.subtract(&Range::from(1..=254)),
+        (Some(Range::from(0)), Some(Range::from(255)))
+    );
+}
+
 #[test]
 fn symbol_range_debug_fmt() {
     assert_eq!(format!("{:?}", Range::from(b'a'..=b'a')), r"97");
@@ -241,4 +300,10 @@ fn symbol_range_legible_display() {
     assert_eq!(format!("{}", Range::from(b'a'..=b'a')), r"'a'");
     assert_eq!(format!("{}", Range::from(b'\0'..=b'Z')), r"00h-'Z'");
     assert_eq!(format!("{}", Range::from(b'\x7E'..=b'~')), r"'~'");
+
+    assert_eq!(format!("{}", range(b'a', b'c')), r"'a'-'c'");
+    assert_eq!(format!("{}", range(2u8, 2)), r"02h");
+    assert_eq!(format!("{}", range(2u8, 6)), r"02h-06h");
+    assert_eq!(format!("{}", range(b'\'', b'\\')), r"'\''-'\\'");
+    assert_eq!(format!("{}", range(b'~', 0x80)), r"'~'-80h");
 }
@@ -104,6 +104,12 @@ fn range_list_exclude() {
     list.exclude(Range::new(12, 13));
     assert_eq!(list.ranges(), &[0.into(), Range::new(11, 11)]);
 
+    list.exclude(Range::from(11));
+    assert_eq!(list.ranges(), &[0.into()]);
+
+    list.exclude(Range::from(0));
+    assert_eq!(list.ranges(), &[]);
+
     let mut list = RangeList::<u32>::default();
     list.merge(Range::new(1, 20));
 
@@ -123,6 +129,119 @@ fn range_list_exclude() {
     assert_eq!(list.ranges(), &[]);
 }
 
+#[test]
+fn range_list_intersect() {
+    // empty lists
+    let mut list = RangeList::<u32>::default();
+    list.intersect(&RangeList::new(3, 5));
+    assert_eq!(list.ranges(), &[]);
+
+    let mut list = RangeList::<u32>::new(3, 5);
+    list.intersect(&RangeList::default());
+    assert_eq!(list.ranges(), &[]);
+
+    // disjoint lists
+    let mut list = RangeList::<u32>::from([Range::new(0, 4), Range::new(10, 14)]);
+    list.intersect(&RangeList::from([Range::new(5, 9), Range::new(15, 20)]));
+    assert_eq!(list.ranges(), &[]);
+
+    // identical lists
+    let mut list = RangeList::<u32>::from([Range::new(0, 4), Range::new(10, 14)]);
+    list.intersect(&RangeList::from([Range::new(0, 4), Range::new(10, 14)]));
+    assert_eq!(list.ranges(), &[Range::new(0, 4), Range::new(10, 14)]);
+
+    // partial overlaps
+    let mut list = RangeList::<u32>::new(0, 14);
+    list.intersect(&RangeList::new(5, 8));
+    assert_eq!(list.ranges(), &[Range::new(5, 8)]);
+
+    let mut list = RangeList::<u32>::new(5, 8);
+    list.intersect(&RangeList::new(0, 14));
+    assert_eq!(list.ranges(), &[Range::new(5, 8)]);
+
+    let mut list = RangeList::<u32>::new(0, 14);
+    list.intersect(&RangeList::from([
+        Range::from(0),
+        Range::new(4, 9),
+        Range::new(14, 19),
+    ]));
+    assert_eq!(list.ranges(), &[0.into(), Range::new(4, 9), 14.into()]);
+
+    let mut list = RangeList::<u32>::from([Range::from(0), Range::new(4, 9), Range::new(14, 19)]);
+    list.intersect(&RangeList::new(0, 14));
+    assert_eq!(list.ranges(), &[0.into(), Range::new(4, 9), 14.into()]);
+
+    let lhs = RangeList::<u32>::from([Range::new(1, 5), Range::new(8, 12), Range::new(15, 20)]);
+    let rhs = RangeList::<u32>::from([Range::new(3, 9), Range::new(11, 16)]);
+    let expected = [
+        Range::new(3, 5),
+        Range::new(8, 9),
+        Range::new(11, 12),
+        Range::new(15, 16),
+    ];
+    assert_eq!(lhs.intersection(&rhs).ranges(), &expected);
+    assert_eq!(rhs.intersection(&lhs).ranges(), &expected);
+}
+
+#[test]
+fn range_list_contains() {
+    assert!(!RangeList::<u32>::default().contains(0));
+
+    let list = RangeList::<u32>::from((0..1000).map(|i| Range::new(i * 10 + 2, i * 10 + 5)));
+    assert_eq!(list.len(), 1000);
+    for i in 0..1000 {
+        let base = i * 10;
+        assert!(!list.contains(base));
+        assert!(!list.contains(base + 1));
+        assert!(list.contains(base + 2));
+        assert!(list.contains(base + 3));
+        assert!(list.contains(base + 5));
+        assert!(!list.contains(base + 6));
+        assert!(!list.contains(base + 9));
+    }
+    assert!(!list.contains(u32::MAX));
+
+    let list = RangeList::<u8>::new(0, u8::MAX);
+    assert!(list.contains(0));
+    assert!(list.contains(u8::MAX));
+}
+
+#[test]
+fn range_list_contains_range() {
+    assert!(!RangeList::<u32>::default().contains_range(&Range::new(0, 0)));
+
+    let list = RangeList::<u32>::from([Range::new(2, 5), Range::new(10, 20), Range::new(30, 30)]);
+    assert!(list.contains_range(&Range::new(2, 5)));
+    assert!(list.contains_range(&Range::new(2, 2)));
+    assert!(list.contains_range(&Range::new(5, 5)));
+    assert!(list.contains_range(&Range::new(12, 18)));
+    assert!(list.contains_range(&Range::new(30, 30)));
+    assert!(!list.contains_range(&Range::new(1, 2)));
+    assert!(!list.contains_range(&Range::new(5, 6)));
+    assert!(!list.contains_range(&Range::new(6, 9)));
+    assert!(!list.contains_range(&Range::new(4, 12)));
+    assert!(!list.contains_range(&Range::new(20, 21)));
+    assert!(!list.contains_range(&Range::new(29, 31)));
+    assert!(!list.contains_range(&Range::new(31, u32::MAX)));
+
+    // adjacent ranges are merged, so a range spanning them is contained
+    let list = RangeList::<u8>::from([Range::new(0, 9), Range::new(10, 19)]);
+    assert!(list.contains_range(&Range::new(5, 15)));
+    assert!(RangeList::<u8>::new(0, u8::MAX).contains_range(&Range::new(0, u8::MAX)));
+}
+
+#[test]
+fn range_list_iter_ranges_rev() {
+    assert_eq!(RangeList::<u32>::default().iter_ranges_rev().next(), None);
+
+    let list = RangeList::<u32>::from((0..1000).map(|i| Range::new(i * 10 + 2, i * 10 + 5)));
+    let ranges = list.iter_ranges_rev().copied().collect::<Vec<_>>();
+    assert_eq!(ranges.len(), 1000);
+    assert_eq!(ranges[0], Range::new(9992, 9995));
+    assert_eq!(ranges[999], Range::new(2, 5));
+    assert!(ranges.windows(2).all(|w| w[0].start() > w[1].start()));
+}
+
 #[test]
 fn range_list_fmt() {
     let mut list = RangeList::<u8>::default();
@@ -1,6 +1,6 @@
 use pretty_assertions::assert_eq;
 use redt::ops::*;
-use redt::{RangeU8, SetU8};
+use redt::{RangeList, RangeU8, SetU8};
 
 #[test]
 fn setu8_new() {
@@ -113,6 +113,74 @@ fn setu8_merge_set() {
     assert_eq!(a, b);
 }
 
+#[test]
+fn setu8_len_and_complement() {
+    let empty = SetU8::empty();
+    assert_eq!(empty.len(), 0);
+    assert!(empty.is_empty());
+    assert!(!empty.is_full());
+    assert!(!empty.contains_all());
+
+    let full = empty.complement();
+    assert_eq!(full.len(), 256);
+    assert!(!full.is_empty());
+    assert!(full.is_full());
+    assert!(full.contains_all());
+    assert_eq!(full.complement(), empty);
+
+    let single = SetU8::from(b'a');
+    assert_eq!(single.len(), 1);
+    assert!(!single.is_full());
+    let others = single.complement();
+    assert_eq!(others.len(), 255);
+    assert!(!others.contains(b'a'));
+    assert!(others.contains(0) && others.contains(u8::MAX));
+    assert!(!others.is_full());
+    assert_eq!(others.complement(), single);
+
+    let set = SetU8::from(&[0, 63, 64, 127, 128, 191, 192, 255]);
+    assert_eq!(set.len(), 8);
+    assert_eq!(set.complement().len(), 248);
+}
+
+#[test]
+fn setu8_normalized_ranges() {
+    assert_eq!(SetU8::empty().normalized_ranges(), []);
+    assert_eq!(
+        SetU8::empty().complement().normalized_ranges(),
+        [RangeU8::new(0, u8::MAX)]
+    );
+
+    let mut one_by_one = SetU8::empty();
+    one_by_one.include(b'a');
+    one_by_one.include(b'b');
+    one_by_one.include(b'c');
+    let mut from_range = SetU8::empty();
+    from_range.include(RangeU8::new(b'a', b'c'));
+    assert_eq!(one_by_one, from_range);
+    assert_eq!(one_by_one.normalized_ranges(), [RangeU8::new(b'a', b'c')]);
+    assert_eq!(from_range.normalized_ranges(), [RangeU8::new(b'a', b'c')]);
+
+    // ranges crossing the chunk boundaries
+    let mut set = SetU8::empty();
+    set.include(RangeU8::new(60, 200));
+    set.exclude(RangeU8::new(100, 110));
+    set.include(b'0');
+    let mut reversed = SetU8::empty();
+    reversed.include(b'0');
+    reversed.include(RangeU8::new(111, 200));
+    reversed.include(RangeU8::new(60, 99));
+    assert_eq!(
+        set.normalized_ranges(),
+        [
+            RangeU8::from(b'0'),
+            RangeU8::new(60, 99),
+            RangeU8::new(111, 200)
+        ]
+    );
+    assert_eq!(set.normalized_ranges(), reversed.normalized_ranges());
+}
+
 #[test]
 fn setu8_display_fmt() {
     let mut a = SetU8::new();
@@ -120,6 +188,16 @@ fn setu8_display_fmt() {
     a.include(210..=240);
 
     assert_eq!(format!("{}", a), "[00h-C8h | D2h-F0h]");
+
+    // ranges at the boundaries of bytes aren't coalesced with each other
+    let mut a = SetU8::new();
+    a.include(0..=0);
+    a.include(2..=0xFE);
+    assert_eq!(format!("{}", a), "[00h | 02h-FEh]");
+    a.include(0xFF..=0xFF);
+    assert_eq!(format!("{}", a), "[00h | 02h-FFh]");
+    a.include(1..=1);
+    assert_eq!(format!("{}", a), "[00h-FFh]");
 }
 
 #[test]
@@ -141,3 +219,80 @@ fn setu8_ranges() {
             .collect::<Vec<_>>()
     );
 }
+
+#[test]
+fn setu8_complement_iters() {
+    use redt::{ByteIter, RangeIter};
+
+    let a = SetU8::from(10..=240);
+    assert_eq!(
+        ByteIter::complement(&a).collect::<Vec<_>>(),
+        (0..=9).chain(241..=255).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        RangeIter::complement(&a).collect::<Vec<_>>(),
+        [RangeU8::new(0, 9), RangeU8::new(241, 255)]
+    );
+
+    let full = SetU8::from(0..=255);
+    assert_eq!(ByteIter::complement(&full).next(), None);
+    assert_eq!(RangeIter::complement(&full).next(), None);
+    assert_eq!(
+        RangeIter::complement(&SetU8::new()).collect::<Vec<_>>(),
+        [
+            RangeU8::new(0, 63),
+            RangeU8::new(64, 127),
+            RangeU8::new(128, 191),
+            RangeU8::new(192, 255)
+        ]
+    );
+}
+
+#[test]
+fn setu8_range_list_interop() {
+    let list = RangeList::<u8>::from([RangeU8::new(b'0', b'9'), RangeU8::new(b'a', b'z')]);
+    let set = SetU8::from_range_list(&list);
+    assert_eq!(set.len(), 36);
+    assert!(set.contains(b'0'));
+    assert!(set.contains(b'z'));
+    assert!(!set.contains(b'A'));
+    assert_eq!(set.to_range_list(), list);
+
+    assert!(SetU8::from_range_list(&RangeList::default()).is_empty());
+    assert!(SetU8::empty().to_range_list().is_empty());
+    assert_eq!(
+        SetU8::new().complement().to_range_list(),
+        RangeList::new(0, u8::MAX)
+    );
+}
+
+mod prop {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn setu8_range_list_round_trip(bounds in proptest::collection::vec((any::<u8>(), any::<u8>()), 0..16)) {
+            let list = RangeList::<u8>::from(
+                bounds
+                    .into_iter()
+                    .map(|(start, last)| RangeU8::new(start.min(last), start.max(last))),
+            );
+            assert_eq!(SetU8::from_range_list(&list).to_range_list(), list);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn setu8_serde() {
+    let set = SetU8::from(&[1, 2, 3, 10, 255]);
+    let json = serde_json::to_string(&set).unwrap();
+    assert_eq!(json, "[[1,3],[10,10],[255,255]]");
+    assert_eq!(serde_json::from_str::<SetU8>(&json).unwrap(), set);
+    assert_eq!(serde_json::from_str::<SetU8>("[]").unwrap(), SetU8::empty());
+
+    let err = serde_json::from_str::<SetU8>("[[3,1]]").unwrap_err();
+    assert!(err.to_string().starts_with("invalid byte range 3..=1"));
+}
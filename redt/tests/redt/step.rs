@@ -1,5 +1,5 @@
 use pretty_assertions::assert_eq;
-use redt::Step;
+use redt::{Range, RangeList, Step};
 
 #[test]
 fn u8_steps_between() {
@@ -8,6 +8,25 @@ fn u8_steps_between() {
     assert_eq!(8u8.steps_between(2), 6);
 }
 
+#[test]
+fn u8_checked_steps_between() {
+    assert_eq!(1u8.checked_steps_between(2), Some(1));
+    assert_eq!(2u8.checked_steps_between(1), Some(-1));
+    assert_eq!(1u8.checked_steps_between(1), Some(0));
+    assert_eq!(0u8.checked_steps_between(255), Some(255));
+    assert_eq!(255u8.checked_steps_between(0), Some(-255));
+    assert_eq!(255u8.checked_steps_between(255), Some(0));
+    assert_eq!(254u8.checked_steps_between(255), Some(1));
+    assert_eq!(255u8.checked_steps_between(254), Some(-1));
+
+    assert_eq!(
+        0u64.checked_steps_between(isize::MAX as u64),
+        Some(isize::MAX)
+    );
+    assert_eq!(0u64.checked_steps_between(u64::MAX), None);
+    assert_eq!(u64::MAX.checked_steps_between(0), None);
+}
+
 #[test]
 fn u8_forward() {
     assert_eq!(1u8.forward(3), Some(4));
@@ -29,3 +48,102 @@ fn u8_adjoins() {
     assert!(!1u8.adjoins(1));
     assert!(!1u8.adjoins(3));
 }
+
+#[test]
+fn char_steps_between() {
+    assert_eq!('a'.steps_between('c'), 2);
+    assert_eq!('c'.steps_between('a'), 2);
+    assert_eq!('\u{D7FF}'.steps_between('\u{E000}'), 1);
+    assert_eq!('\u{D7FE}'.steps_between('\u{E001}'), 3);
+    assert_eq!('\0'.steps_between(char::MAX), 0x10FFFF - 0x800);
+}
+
+#[test]
+fn char_checked_steps_between() {
+    assert_eq!('a'.checked_steps_between('c'), Some(2));
+    assert_eq!('c'.checked_steps_between('a'), Some(-2));
+    assert_eq!('\u{D7FF}'.checked_steps_between('\u{E000}'), Some(1));
+    assert_eq!('\u{E000}'.checked_steps_between('\u{D7FF}'), Some(-1));
+    assert_eq!(
+        char::MAX.checked_steps_between('\0'),
+        Some(-(0x10FFFF - 0x800))
+    );
+}
+
+#[test]
+fn default_checked_steps_between() {
+    /// A decimal digit, which relies on the default `checked_steps_between`.
+    #[derive(Debug, Clone, Copy)]
+    struct Digit(u8);
+
+    impl Step for Digit {
+        type Distance = u8;
+
+        fn steps_between(&self, other: Self) -> u8 {
+            self.0.abs_diff(other.0)
+        }
+
+        fn forward(&self, count: usize) -> Option<Self> {
+            self.0.forward(count).filter(|d| *d <= 9).map(Digit)
+        }
+
+        fn backward(&self, count: usize) -> Option<Self> {
+            self.0.backward(count).map(Digit)
+        }
+    }
+
+    assert_eq!(Digit(2).checked_steps_between(Digit(7)), Some(5));
+    assert_eq!(Digit(7).checked_steps_between(Digit(2)), Some(-5));
+    assert_eq!(Digit(0).checked_steps_between(Digit(9)), Some(9));
+    assert_eq!(Digit(9).checked_steps_between(Digit(0)), Some(-9));
+    assert_eq!(Digit(4).checked_steps_between(Digit(4)), Some(0));
+}
+
+#[test]
+fn char_forward() {
+    assert_eq!('a'.forward(2), Some('c'));
+    assert_eq!('\u{D7FF}'.forward(1), Some('\u{E000}'));
+    assert_eq!('\u{D7FE}'.forward(3), Some('\u{E001}'));
+    assert_eq!(char::MAX.forward(0), Some(char::MAX));
+    assert_eq!(char::MAX.forward(1), None);
+    assert_eq!('\u{10FFFE}'.forward(2), None);
+    assert_eq!('a'.forward(usize::MAX), None);
+}
+
+#[test]
+fn char_backward() {
+    assert_eq!('c'.backward(2), Some('a'));
+    assert_eq!('\u{E000}'.backward(1), Some('\u{D7FF}'));
+    assert_eq!('\u{E001}'.backward(3), Some('\u{D7FE}'));
+    assert_eq!(char::MAX.backward(1), Some('\u{10FFFE}'));
+    assert_eq!('\0'.backward(1), None);
+}
+
+#[test]
+fn char_adjoins() {
+    assert!('\u{D7FF}'.adjoins('\u{E000}'));
+    assert!('\u{E000}'.adjoins('\u{D7FF}'));
+    assert!(!'\u{D7FF}'.adjoins('\u{E001}'));
+    assert!('\u{10FFFE}'.adjoins(char::MAX));
+}
+
+#[test]
+fn char_range_list() {
+    let list = RangeList::from([
+        Range::new('a', '\u{D7FF}'),
+        Range::new('\u{E000}', char::MAX),
+    ]);
+    assert_eq!(list.ranges(), [Range::new('a', char::MAX)]);
+    assert_eq!(Range::new('\u{D7FF}', '\u{E000}').width(), Some(2));
+    assert_eq!(
+        Range::new('\0', char::MAX).width(),
+        Some(0x10FFFF - 0x800 + 1)
+    );
+
+    let mut list = RangeList::from(Range::new('a', char::MAX));
+    list.exclude(Range::new('b', '\u{D7FF}'));
+    assert_eq!(
+        list.ranges(),
+        [Range::new('a', 'a'), Range::new('\u{E000}', char::MAX)]
+    );
+}
@@ -1,8 +1,11 @@
 mod determ;
 pub use determ::determinize;
 
+mod trie;
+pub(crate) use trie::build_trie;
+
 mod verify;
-pub use verify::verify_dfa;
+pub use verify::{DfaViolation, is_dfa, verify_dfa};
 
 mod visit;
 pub use visit::{VisitResult, visit_nodes, visit_transitions};
@@ -0,0 +1,24 @@
+use std::collections::BTreeMap;
+
+/// A trie of byte strings. Each trie node is a map from a byte to the index of
+/// the next trie node, and a flag if any string ends in it. The root node has
+/// index `0`.
+pub(crate) type Trie = Vec<(BTreeMap<u8, usize>, bool)>;
+
+/// Builds a trie of the `literals`, i.e. literals with common prefixes share
+/// the nodes of these prefixes.
+pub(crate) fn build_trie<'l>(literals: impl IntoIterator<Item = &'l [u8]>) -> Trie {
+    let mut trie: Trie = vec![(BTreeMap::new(), false)];
+    for literal in literals {
+        let mut index = 0;
+        for byte in literal {
+            let next_index = trie.len();
+            index = *trie[index].0.entry(*byte).or_insert(next_index);
+            if index == next_index {
+                trie.push((BTreeMap::new(), false));
+            }
+        }
+        trie[index].1 = true;
+    }
+    trie
+}
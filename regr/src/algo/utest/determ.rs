@@ -16,12 +16,12 @@ fn e_closure() {
     let e = nfa.node();
     let f = nfa.node();
 
-    a.connect(b);
-    a.connect(c);
+    a.connect_epsilon(b);
+    a.connect_epsilon(c);
     b.connect(d).merge(1);
-    c.connect(e);
-    d.connect(f);
-    e.connect(f);
+    c.connect_epsilon(e);
+    d.connect_epsilon(f);
+    e.connect_epsilon(f);
 
     let mut dfa_arena = Arena::new();
     let dfa = Graph::new_in(&mut dfa_arena);
@@ -34,10 +34,10 @@ fn e_closure() {
     assert_eq!(det.e_closure(e), tset![e, f]);
     assert_eq!(det.e_closure(f), tset![f]);
 
-    f.connect(b);
+    f.connect_epsilon(b);
     assert_eq!(det.e_closure(f), tset![b, f]);
 
-    f.connect(c);
+    f.connect_epsilon(c);
     assert_eq!(det.e_closure(f), tset![b, c, e, f]);
 
     assert!(det.inst_map.is_empty());
@@ -1,40 +1,113 @@
 use crate::Epsilon;
 use crate::algo::{self, VisitResult::*};
 use crate::graph::Graph;
+use crate::isa::Inst;
 use crate::node::Node;
 use redt::{SetU8, ops::*};
 
-/// Checks if the given graph represents a valid DFA.
+/// Describes why a graph isn't a valid DFA: the node with several transitions
+/// for the same symbol, and the targets of these transitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DfaViolation {
+    /// UID of the node having the conflicting transitions.
+    pub node: u64,
+    /// The symbol with several targets, or `None` for Epsilon transitions of
+    /// the same kind.
+    pub symbol: Option<u8>,
+    /// UIDs of the conflicting targets.
+    pub targets: Vec<u64>,
+}
+
+impl std::fmt::Display for DfaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "node {} has several targets for ", self.node)?;
+        match self.symbol {
+            Some(symbol) => write!(f, "{symbol:02X}h")?,
+            None => write!(f, "{Epsilon}")?,
+        }
+        f.write_str(":")?;
+        for target in self.targets.iter() {
+            write!(f, " {target}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DfaViolation {}
+
+/// Checks if the given graph represents a valid DFA. Returns the first found
+/// violation otherwise.
 #[allow(clippy::mutable_key_type)]
-pub fn verify_dfa(graph: &Graph<'_>) -> bool {
-    let mut is_dfa = true;
+pub fn verify_dfa(graph: &Graph<'_>) -> Result<(), DfaViolation> {
+    let mut result = Ok(());
     algo::visit_nodes(graph.start_node(), |node| {
-        if !verify_dfa_node(node) {
-            is_dfa = false;
+        if let Err(violation) = verify_dfa_node(node) {
+            result = Err(violation);
             return Stop;
         }
         Recurse
     });
-    is_dfa
+    result
+}
+
+/// Returns `true` if the given graph represents a valid DFA. It's a shortcut
+/// for [`verify_dfa`] when the violation details aren't needed.
+pub fn is_dfa(graph: &Graph<'_>) -> bool {
+    verify_dfa(graph).is_ok()
 }
 
 /// Checks if the given node meets the requirements of a DFA.
-pub fn verify_dfa_node<'a>(node: Node<'a>) -> bool {
-    let mut has_epsilon = false;
+///
+/// A DFA node may have at most one Epsilon transition of every kind: a plain
+/// one, one asserting the start of the text, and one asserting the end of it.
+pub fn verify_dfa_node<'a>(node: Node<'a>) -> Result<(), DfaViolation> {
+    let epsilon_kind = |tr: &crate::Transition<'a>| {
+        if tr.instructs().any(|inst| inst == Inst::AssertStartText) {
+            1
+        } else if tr.instructs().any(|inst| inst == Inst::AssertEndText) {
+            2
+        } else {
+            4
+        }
+    };
+
+    let mut epsilon_mask = 0u8;
     let mut sym_mask = SetU8::empty();
     for (_, tr) in node.targets().iter() {
         if tr.contains(Epsilon) {
-            if !has_epsilon {
-                has_epsilon = true;
-            } else {
-                return false;
+            let kind = epsilon_kind(tr);
+            if epsilon_mask & kind != 0 {
+                let targets = node
+                    .targets()
+                    .iter()
+                    .filter(|(_, tr)| tr.contains(Epsilon) && epsilon_kind(tr) == kind)
+                    .map(|(target, _)| target.uid())
+                    .collect();
+                return Err(DfaViolation {
+                    node: node.uid(),
+                    symbol: None,
+                    targets,
+                });
             }
+            epsilon_mask |= kind;
         } else {
-            if sym_mask.intersects(tr.as_set().as_ref()) {
-                return false;
+            if let Some(symbol) = (u8::MIN..=u8::MAX)
+                .find(|symbol| sym_mask.contains(*symbol) && tr.contains(*symbol))
+            {
+                let targets = node
+                    .targets()
+                    .iter()
+                    .filter(|(_, tr)| tr.contains(symbol))
+                    .map(|(target, _)| target.uid())
+                    .collect();
+                return Err(DfaViolation {
+                    node: node.uid(),
+                    symbol: Some(symbol),
+                    targets,
+                });
             }
             sym_mask.include(tr.as_set().as_ref());
         }
     }
-    true
+    Ok(())
 }
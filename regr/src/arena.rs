@@ -2,14 +2,18 @@ use crate::node::{Node, NodeInner};
 use crate::transition::TransitionInner;
 use bumpalo::Bump;
 use smallvec::SmallVec;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 
 #[derive(Debug)]
 pub struct Arena {
     node_bump: Bump,
     nodes_len: Cell<usize>,
     bound_gid: Cell<Option<u32>>,
+    strict_dfa: Cell<bool>,
     pub(crate) shared_bump: Bump,
+    // the strings point into `shared_bump` and are cleared with it
+    strings: RefCell<HashSet<&'static str>>,
 }
 
 /// Public API
@@ -24,7 +28,9 @@ impl Arena {
             node_bump: Bump::with_capacity(capacity * std::mem::size_of::<NodeInner>()),
             nodes_len: Cell::new(0),
             bound_gid: Cell::new(None), // bound graph id
+            strict_dfa: Cell::new(false),
             shared_bump: Bump::with_capacity(capacity * std::mem::size_of::<TransitionInner>()),
+            strings: RefCell::new(HashSet::new()),
         }
     }
 
@@ -37,6 +43,52 @@ impl Arena {
     pub fn nodes(&self) -> impl ExactSizeIterator<Item = Node<'_>> {
         BumpIter::new(&self.node_bump, self.nodes_len.get()).map(|ptr| Node::from(unsafe { &*ptr }))
     }
+
+    /// Returns the number of nodes in the arena, i.e. the nodes of the bound
+    /// graph, or of the last bound one if it's dropped already.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.nodes_len.get()
+    }
+
+    /// Returns the number of bytes allocated by the arena for nodes and
+    /// transitions, including the unused capacity of the allocated chunks.
+    pub fn allocated_bytes(&self) -> usize {
+        self.node_bump.allocated_bytes() + self.shared_bump.allocated_bytes()
+    }
+
+    /// Copies the string into the arena, unless an equal string has been
+    /// interned already, and returns the arena's copy. So interning equal
+    /// strings returns the same slice.
+    pub fn intern<'a>(&'a self, s: &str) -> &'a str {
+        let mut strings = self.strings.borrow_mut();
+        if let Some(interned) = strings.get(s) {
+            return interned;
+        }
+        let interned: &'a str = self.shared_bump.alloc_str(s);
+        // SAFETY: the string lives in `shared_bump` until the arena is reset
+        // or dropped, and the set is cleared on reset, so the extended
+        // lifetime never leaks out of the arena.
+        let interned: &'static str =
+            unsafe { std::mem::transmute::<&'a str, &'static str>(interned) };
+        strings.insert(interned);
+        interned
+    }
+
+    /// Drops all the nodes and transitions in the arena, so it can be reused
+    /// for another graph. Only the last allocated chunks are kept to avoid
+    /// allocating them again.
+    ///
+    /// Panics if a graph is still bound to the arena.
+    pub fn reset(&mut self) {
+        if let Some(gid) = self.bound_gid.get() {
+            panic!("can't reset the arena bound to a graph(gid={gid})");
+        }
+
+        self.drop_nodes();
+        self.strings.get_mut().clear();
+        self.shared_bump.reset();
+    }
 }
 
 /// Crate API
@@ -78,6 +130,20 @@ impl Arena {
 
         self.drop_nodes();
         self.bound_gid.set(Some(gid));
+        self.strict_dfa.set(false);
+    }
+
+    /// Checks if the bound graph must stay a DFA. See [`Graph::new_strict_dfa_in`].
+    ///
+    /// [`Graph::new_strict_dfa_in`]: crate::Graph::new_strict_dfa_in
+    #[inline]
+    pub(crate) fn is_strict_dfa(&self) -> bool {
+        self.strict_dfa.get()
+    }
+
+    #[inline]
+    pub(crate) fn set_strict_dfa(&self, strict: bool) {
+        self.strict_dfa.set(strict);
     }
 
     /// Unbinds this arena from a graph. Should be run by the graph destructor.
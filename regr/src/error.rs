@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("repetitions expand the graph to more than {max} nodes")]
+    ExpansionLimit { max: usize },
+}
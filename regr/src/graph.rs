@@ -1,11 +1,14 @@
 use crate::arena::Arena;
-use crate::node::{ClosureOp, Node};
-use crate::symbol::Epsilon;
+use crate::isa::Inst;
+use crate::node::Node;
 use crate::tag::Tag;
-use redt::{Map, Set};
+use crate::transition::Transition;
+use redt::{IterSorted, Map, Set, SetU8, ops::*};
+use resy::GroupLabel;
 use std::cell::{Cell, RefCell};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt::Write;
+use std::ops::Deref;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU32, Ordering};
 
@@ -14,8 +17,9 @@ pub struct Graph<'a> {
     arena: &'a Arena,
     next_nid: Cell<u32>,
     start_node: Cell<Option<Node<'a>>>,
-    tag_bank: RefCell<Map<u32, Tag>>,          // id -> tag
-    tag_groups: RefCell<Map<u32, (u32, u32)>>, // label -> (open_tag_id, close_tag_id)
+    tag_bank: RefCell<Map<u32, Tag>>,                 // id -> tag
+    tag_groups: RefCell<Map<GroupLabel, (u32, u32)>>, // label -> (open_tag_id, close_tag_id)
+    tag_conflicts: Cell<bool>,
 }
 
 static NEXT_GRAPH_ID: AtomicU32 = AtomicU32::new(1);
@@ -36,9 +40,27 @@ impl<'a> Graph<'a> {
             start_node: Cell::new(None),
             tag_bank: RefCell::new(Map::new()),
             tag_groups: RefCell::new(Map::new()),
+            tag_conflicts: Cell::new(false),
         }
     }
 
+    /// Creates an empty graph that must stay a DFA. In debug builds, an
+    /// Epsilon transition between its nodes, or a byte merged into a
+    /// transition while another transition from the same node already has it,
+    /// panics at once instead of being caught by [`crate::algo::verify_dfa`]
+    /// later.
+    pub fn new_strict_dfa_in(arena: &'a mut Arena) -> Self {
+        let graph = Self::new_in(arena);
+        graph.arena.set_strict_dfa(true);
+        graph
+    }
+
+    /// Checks if the graph has been created by [`Graph::new_strict_dfa_in`].
+    #[inline]
+    pub fn is_strict_dfa(&self) -> bool {
+        self.arena.is_strict_dfa()
+    }
+
     /// This graph's ID.
     #[inline]
     pub fn gid(&self) -> u32 {
@@ -79,7 +101,7 @@ impl<'a> Graph<'a> {
         self.arena
     }
 
-    pub fn add_tag_group(&self, label: u32, open_tag: Tag, close_tag: Tag) {
+    pub fn add_tag_group(&self, label: impl Into<GroupLabel>, open_tag: Tag, close_tag: Tag) {
         let open_id = open_tag.id();
         let close_id = close_tag.id();
 
@@ -88,11 +110,38 @@ impl<'a> Graph<'a> {
         assert_eq!(tag_bank.entry(close_id).or_insert(close_tag).id(), close_id);
 
         let mut tag_table = self.tag_groups.borrow_mut();
-        tag_table.entry(label).or_insert((open_id, close_id));
+        tag_table.entry(label.into()).or_insert((open_id, close_id));
+    }
+
+    /// Registers a tag that isn't the open or close tag of any group, but
+    /// which positions of the groups can be relative to.
+    pub fn add_tag(&self, tag: Tag) {
+        let id = tag.id();
+        let mut tag_bank = self.tag_bank.borrow_mut();
+        assert_eq!(tag_bank.entry(id).or_insert(tag).id(), id);
+    }
+
+    /// Returns all tags registered in the graph, including the tags of the
+    /// groups, ordered by their ids.
+    pub fn tags(&self) -> impl std::iter::Iterator<Item = Tag> {
+        let tag_bank = self.tag_bank.borrow();
+        let mut tags: Vec<_> = tag_bank.values().copied().collect();
+        tags.sort_unstable_by_key(Tag::id);
+        tags.into_iter()
     }
 
-    pub fn tag_group(&self, label: u32) -> Option<(Tag, Tag)> {
-        if let Some((open_id, close_id)) = self.tag_groups.borrow().get(&label).cloned() {
+    /// Copies the tags and the tag groups of `other` into `self`.
+    fn copy_tags_from(&self, other: &Graph<'_>) {
+        for tag in other.tags() {
+            self.add_tag(tag);
+        }
+        for (label, (open_tag, close_tag)) in other.tag_groups() {
+            self.add_tag_group(label, open_tag, close_tag);
+        }
+    }
+
+    pub fn tag_group(&self, label: impl Into<GroupLabel>) -> Option<(Tag, Tag)> {
+        if let Some((open_id, close_id)) = self.tag_groups.borrow().get(&label.into()).cloned() {
             let tag_bank = self.tag_bank.borrow();
             Some((tag_bank[&open_id], tag_bank[&close_id]))
         } else {
@@ -102,7 +151,7 @@ impl<'a> Graph<'a> {
 
     /// Returns an iterator over all tag groups in the graph. The iterator
     /// yields tuples of the form `(label, (open_tag, close_tag))`.
-    pub fn tag_groups(&self) -> impl std::iter::Iterator<Item = (u32, (Tag, Tag))> {
+    pub fn tag_groups(&self) -> impl std::iter::Iterator<Item = (GroupLabel, (Tag, Tag))> {
         TagGroupIter::new(self)
     }
 
@@ -110,78 +159,1313 @@ impl<'a> Graph<'a> {
     ///
     /// If instead of NFA, this graph is a DFA, this method just builds a clone
     /// of it.
+    ///
+    /// Epsilon transitions with anchor assertions are kept in the DFA as
+    /// Epsilon transitions with the same instructions:
+    /// - the start node gets a transition with [`Inst::AssertStartText`] to
+    ///   the node that should be used instead of it at the beginning of the
+    ///   haystack;
+    /// - a node that is final only at the end of the haystack gets a
+    ///   transition with [`Inst::AssertEndText`] to a final node.
     pub fn determinize_in<'d>(&self, arena: &'d mut Arena) -> Graph<'d> {
-        type ConvertMap<'n, 'd> = BTreeMap<Rc<BTreeSet<Node<'n>>>, Node<'d>>;
+        self.determinize_with_map_in(arena).0
+    }
+
+    /// Builds a new DFA from `self` the same way as [`Graph::determinize_in`]
+    /// does, and also returns a map from UIDs of the DFA nodes to the sorted
+    /// UIDs of the NFA nodes, i.e. the subset, each of them represents.
+    ///
+    /// The final node added for the [`Inst::AssertEndText`] transitions
+    /// represents no NFA nodes, so it is absent in the map.
+    pub fn determinize_with_map_in<'d>(
+        &self,
+        arena: &'d mut Arena,
+    ) -> (Graph<'d>, Map<u64, Vec<u64>>) {
+        self.determinize_impl(arena, false)
+    }
+
+    /// Builds a new DFA from `self` the same way as [`Graph::determinize_in`]
+    /// does, but also keeps the tag instructions, i.e. [`Inst::WritePos`] and
+    /// [`Inst::InvalidateTag`], the [`Inst::Accept`] and [`Inst::Priority`]
+    /// instructions, and the tag groups of `self`.
+    ///
+    /// An instruction met on the Epsilon path to an NFA node is delayed until
+    /// a byte transition leaves this node, so it is attached only to the DFA
+    /// transitions for the bytes of that transition, and the position it
+    /// writes is the one before the byte. The instructions met on the paths to
+    /// final NFA nodes are attached to an Epsilon transition from the final
+    /// DFA node to a separate final node, the same one that is used for
+    /// [`Inst::AssertEndText`] transitions.
+    ///
+    /// The instructions of all the NFA paths merged into a DFA transition are
+    /// merged too, so for an ambiguous pattern a group can get positions of
+    /// any of these paths. [`Graph::has_tag_conflicts`] of the result tells
+    /// if the merged paths had different tag instructions.
+    pub fn determinize_with_tags_in<'d>(&self, arena: &'d mut Arena) -> Graph<'d> {
+        let (dfa, _) = self.determinize_impl(arena, true);
+        dfa.copy_tags_from(self);
+        dfa
+    }
+
+    /// Checks if [`Graph::determinize_with_tags_in`] merged NFA paths with
+    /// different [`Inst::WritePos`] or [`Inst::InvalidateTag`] instructions
+    /// into the same DFA transition or final state, e.g. the paths of `a` and
+    /// `ab` in `(a|ab)(c|bcd)`. The registers of such a DFA can mix positions
+    /// of several paths, so the groups must be resolved some other way.
+    ///
+    /// It's always `false` for a graph built any other way.
+    #[inline]
+    pub fn has_tag_conflicts(&self) -> bool {
+        self.tag_conflicts.get()
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn determinize_impl<'d>(
+        &self,
+        arena: &'d mut Arena,
+        tags: bool,
+    ) -> (Graph<'d>, Map<u64, Vec<u64>>) {
+        type ConvertMap<'n, 'd> = BTreeMap<Rc<Closure<'n>>, Node<'d>>;
 
         struct Lambda<'a, 'n, 'd> {
             #[allow(clippy::mutable_key_type)]
             convert_map: ConvertMap<'n, 'd>,
             dfa: &'a Graph<'d>,
+            end_node: Option<Node<'d>>,
+            tags: bool,
+            tag_conflicts: bool,
         }
         impl<'a, 'n, 'd> Lambda<'a, 'n, 'd> {
-            fn convert(&mut self, nfa_closure: Rc<BTreeSet<Node<'n>>>) -> Node<'d> {
+            fn convert(&mut self, nfa_closure: Rc<Closure<'n>>) -> Node<'d> {
                 if let Some(dfa_node) = self.convert_map.get(&nfa_closure) {
                     return *dfa_node;
                 }
 
                 let dfa_node = self.dfa.node();
-                for nfa_node in nfa_closure.iter() {
-                    if nfa_node.is_final() {
-                        dfa_node.finalize();
-                        break;
+                self.convert_map.insert(Rc::clone(&nfa_closure), dfa_node);
+                self.fill(dfa_node, nfa_closure);
+                dfa_node
+            }
+
+            /// Fills the `dfa_node` and all the new DFA nodes reachable from
+            /// it. The nodes are visited depth-first with an explicit stack
+            /// rather than recursively, so a long chain of states, like the
+            /// one of `a{5000}`, can't overflow the stack, and the nodes get
+            /// the same IDs as with a recursive descent.
+            fn fill(&mut self, dfa_node: Node<'d>, nfa_closure: Rc<Closure<'n>>) {
+                self.fill_finality(dfa_node, &nfa_closure);
+                let mut stack = vec![(dfa_node, nfa_closure, 0usize)];
+                while let Some((dfa_node, nfa_closure, next_symbol)) = stack.last_mut() {
+                    let Ok(symbol) = u8::try_from(*next_symbol) else {
+                        stack.pop();
+                        continue;
+                    };
+                    *next_symbol += 1;
+                    let dfa_node = *dfa_node;
+                    let Some((symbol_closure, symbol_insts)) =
+                        self.symbol_closure(nfa_closure, symbol)
+                    else {
+                        continue;
+                    };
+                    let target_dfa_node = match self.convert_map.get(&symbol_closure) {
+                        Some(target_dfa_node) => *target_dfa_node,
+                        None => {
+                            let target_dfa_node = self.dfa.node();
+                            self.convert_map
+                                .insert(Rc::clone(&symbol_closure), target_dfa_node);
+                            self.fill_finality(target_dfa_node, &symbol_closure);
+                            stack.push((target_dfa_node, symbol_closure, 0));
+                            target_dfa_node
+                        }
+                    };
+                    let tr = dfa_node.connect(target_dfa_node);
+                    tr.merge(symbol);
+                    tr.merge_instructs(symbol_insts, Some(symbol.into()));
+                }
+            }
+
+            #[allow(clippy::mutable_key_type)]
+            fn fill_finality(&mut self, dfa_node: Node<'d>, nfa_closure: &Closure<'n>) {
+                let (nodes, insts) = nfa_closure;
+                if nodes.iter().any(|nfa_node| nfa_node.is_final()) {
+                    dfa_node.finalize();
+                    let final_insts = self.final_insts(nodes, insts);
+                    if !final_insts.is_empty() {
+                        let dfa = self.dfa;
+                        let end_node = *self.end_node.get_or_insert_with(|| dfa.node().finalize());
+                        dfa_node
+                            .connect(end_node)
+                            .merge_instructs(final_insts, None);
+                    }
+                } else {
+                    let seeds = nodes.iter().map(|nfa_node| {
+                        (*nfa_node, insts.get(nfa_node).cloned().unwrap_or_default())
+                    });
+                    let (end_nodes, end_insts) =
+                        anchored_closure(seeds, false, true, self.tags, &mut self.tag_conflicts);
+                    if end_nodes.iter().any(|nfa_node| nfa_node.is_final()) {
+                        let dfa = self.dfa;
+                        let end_node = *self.end_node.get_or_insert_with(|| dfa.node().finalize());
+                        let tr = dfa_node.connect(end_node);
+                        tr.merge_instruct(Inst::AssertEndText, None);
+                        tr.merge_instructs(self.final_insts(&end_nodes, &end_insts), None);
                     }
                 }
-                self.convert_map.insert(Rc::clone(&nfa_closure), dfa_node);
+            }
+
+            /// Returns the instructions met on the paths to the final NFA
+            /// nodes of the closure, noting if the paths have different tag
+            /// instructions.
+            #[allow(clippy::mutable_key_type)]
+            fn final_insts(
+                &mut self,
+                nodes: &BTreeSet<Node<'n>>,
+                insts: &InstMap<'n>,
+            ) -> BTreeSet<Inst> {
+                let final_nodes = nodes.iter().filter(|nfa_node| nfa_node.is_final());
+                let final_insts = final_nodes.map(|nfa_node| insts.get(nfa_node));
+                self.tag_conflicts |=
+                    has_tag_conflicts(final_insts.clone().map(|insts| insts.into_iter().flatten()));
+                final_insts.flatten().flatten().copied().collect()
+            }
 
-                for symbol in u8::MIN..=u8::MAX {
-                    let symbol_closure = Rc::new(nfa_closure.closure(symbol));
-                    if !symbol_closure.is_empty() {
-                        let target_dfa_node = self.convert(symbol_closure);
-                        let tr = dfa_node.connect(target_dfa_node);
-                        tr.merge(symbol);
+            /// Returns the closure of the NFA nodes the `symbol` leads to from
+            /// the `nfa_closure`, and the instructions of these transitions.
+            #[allow(clippy::mutable_key_type)]
+            fn symbol_closure(
+                &mut self,
+                nfa_closure: &Closure<'n>,
+                symbol: u8,
+            ) -> Option<(Rc<Closure<'n>>, BTreeSet<Inst>)> {
+                let (nodes, insts) = nfa_closure;
+                let mut targets = Vec::new();
+                let mut path_insts = Vec::new();
+                for nfa_node in nodes.iter() {
+                    for (target, tr) in nfa_node.targets().iter() {
+                        if tr.contains(symbol) {
+                            targets.push((*target, BTreeSet::new()));
+                            if self.tags {
+                                let mut insts = insts.get(nfa_node).cloned().unwrap_or_default();
+                                insts.extend(tr.instructs_for(symbol));
+                                path_insts.push(insts);
+                            }
+                        }
                     }
                 }
-                dfa_node
+                if targets.is_empty() {
+                    return None;
+                }
+                self.tag_conflicts |= has_tag_conflicts(&path_insts);
+                let symbol_insts = path_insts.into_iter().flatten().collect();
+                let symbol_closure =
+                    anchored_closure(targets, false, false, self.tags, &mut self.tag_conflicts);
+                Some((Rc::new(symbol_closure), symbol_insts))
             }
         }
 
         let dfa = Graph::new_in(arena);
-        let start_e_closure = Rc::new(self.start_node().closure(Epsilon));
-        Lambda {
+        let mut lambda = Lambda {
             convert_map: ConvertMap::new(),
             dfa: &dfa,
+            end_node: None,
+            tags,
+            tag_conflicts: false,
+        };
+        let start_node = self.start_node();
+        let mut closure_of_start = |text_start| {
+            let seeds = [(start_node, BTreeSet::new())];
+            Rc::new(anchored_closure(
+                seeds,
+                text_start,
+                false,
+                tags,
+                &mut lambda.tag_conflicts,
+            ))
+        };
+        let start_closure = closure_of_start(false);
+        let text_start_closure = closure_of_start(true);
+        let start_dfa_node = lambda.convert(Rc::clone(&start_closure));
+        let mut subsets = Vec::with_capacity(lambda.convert_map.len() + 1);
+        if text_start_closure != start_closure {
+            // a separate node guarantees that the start node has no other
+            // transitions to it
+            let text_start_dfa_node = dfa.node();
+            lambda.fill(text_start_dfa_node, Rc::clone(&text_start_closure));
+            start_dfa_node
+                .connect(text_start_dfa_node)
+                .merge_instruct(Inst::AssertStartText, None);
+            subsets.push((text_start_dfa_node, text_start_closure.0.clone()));
+        }
+        dfa.tag_conflicts.set(lambda.tag_conflicts);
+
+        subsets.extend(
+            lambda
+                .convert_map
+                .into_iter()
+                .map(|(nfa_closure, dfa_node)| (dfa_node, nfa_closure.0.clone())),
+        );
+        subsets.sort_by_key(|(dfa_node, _)| dfa_node.nid());
+        let map = subsets
+            .into_iter()
+            .map(|(dfa_node, nfa_closure)| {
+                let mut nfa_uids: Vec<_> = nfa_closure.iter().map(Node::uid).collect();
+                nfa_uids.sort_unstable();
+                (dfa_node.uid(), nfa_uids)
+            })
+            .collect();
+        (dfa, map)
+    }
+
+    /// Builds a new DFA from `self` that accepts exactly the strings rejected
+    /// by `self`, see [`Graph::accepts`].
+    ///
+    /// The result is a total DFA: every missing byte transition leads to an
+    /// explicit dead state that loops on all the bytes. Then every state
+    /// changes its finality to the opposite one, so the dead state becomes
+    /// final.
+    ///
+    /// The [`Inst::AssertStartText`] transition of the start node is kept as
+    /// is. A node with an [`Inst::AssertEndText`] transition to a final node
+    /// accepts the end of the haystack, so it's turned into a non-final one,
+    /// and the transition is dropped. As a result, a node that isn't final in
+    /// `self` but accepts at the end isn't final in the complement at all,
+    /// i.e. only whole strings are complemented.
+    ///
+    /// Panics if `self` is not a DFA, or it has Epsilon transitions other than
+    /// the anchor ones.
+    #[allow(clippy::mutable_key_type)]
+    pub fn complement_in<'d>(&self, arena: &'d mut Arena) -> Graph<'d> {
+        assert!(
+            crate::algo::is_dfa(self),
+            "complement can be built only for a DFA"
+        );
+        let has_anchor = |tr: Transition<'a>, anchor: Inst| {
+            tr.is_epsilon() && tr.instructs().any(|inst| inst == anchor)
+        };
+
+        let dfa = Graph::new_in(arena);
+        let mut node_map = Map::<Node<'a>, Node<'d>>::new();
+        self.for_each_node(|node| {
+            let dfa_node = dfa.node();
+            let accepts_at_end = node.is_final()
+                || node
+                    .targets()
+                    .iter()
+                    .any(|(target, tr)| has_anchor(*tr, Inst::AssertEndText) && target.is_final());
+            if !accepts_at_end {
+                dfa_node.finalize();
+            }
+            node_map.insert(node, dfa_node);
+        });
+
+        let mut dead_node = None;
+        self.for_each_node(|node| {
+            let source = node_map[&node];
+            let mut missing = !SetU8::empty();
+            for (target, tr) in node.targets().iter() {
+                if has_anchor(*tr, Inst::AssertStartText) {
+                    source
+                        .connect(node_map[target])
+                        .merge_instructs(tr.instructs(), None);
+                    continue;
+                }
+                if has_anchor(*tr, Inst::AssertEndText) {
+                    continue;
+                }
+                assert!(
+                    !tr.is_epsilon(),
+                    "complement can't be built for a graph with non-anchor epsilon transitions"
+                );
+                source.connect(node_map[target]).merge(*tr);
+                missing.exclude(tr.as_set().deref());
+            }
+            if !missing.is_empty() {
+                let dead_node = *dead_node.get_or_insert_with(|| {
+                    let dead_node = dfa.node().finalize();
+                    dead_node.connect(dead_node).merge(!SetU8::empty());
+                    dead_node
+                });
+                source.connect(dead_node).merge(missing);
+            }
+        });
+        dfa
+    }
+
+    /// Builds a new NFA from `self` that accepts the reversed strings accepted
+    /// by `self`.
+    ///
+    /// Every transition is flipped keeping its symbols and instructions, the
+    /// old start node becomes the only final node, and a new start node gets
+    /// Epsilon transitions to all the nodes that were final. Anchor assertions
+    /// are copied as is, i.e. they aren't swapped.
+    #[allow(clippy::mutable_key_type)]
+    pub fn reverse_in<'d>(&self, arena: &'d mut Arena) -> Graph<'d> {
+        let nfa = Graph::new_in(arena);
+        let start_node = nfa.node();
+        let mut node_map = Map::<Node<'a>, Node<'d>>::new();
+        self.for_each_node(|node| {
+            node_map.insert(node, nfa.node());
+        });
+        node_map[&self.start_node()].finalize();
+
+        self.for_each_node(|node| {
+            let source = node_map[&node];
+            if node.is_final() {
+                start_node.connect_epsilon(source);
+            }
+            for (target, tr) in node.targets().iter() {
+                node_map[target].connect(source).merge(*tr);
+            }
+        });
+        nfa
+    }
+
+    /// Builds a new DFA that accepts the strings accepted by `self` or by
+    /// `other`, using the product construction, i.e. every state of the result
+    /// is a pair of states of the operands, where a missing state means that
+    /// the operand has already rejected the input.
+    ///
+    /// The instructions of the combined transitions are merged, and the tag
+    /// groups of both graphs are copied. The anchor Epsilon transitions are
+    /// combined the same way as the byte ones: the `^` transition leads to the
+    /// pair of the nodes used at the beginning of the haystack, where an
+    /// operand without it stays in its start node, and the `$` transition
+    /// leads to the pair of the targets of the `$` transitions of the
+    /// operands.
+    ///
+    /// Panics if any of the graphs is not a DFA.
+    #[allow(clippy::mutable_key_type)]
+    pub fn product_union_in<'o, 'd>(&self, other: &Graph<'o>, arena: &'d mut Arena) -> Graph<'d> {
+        type Pair<'a, 'o> = (Option<Node<'a>>, Option<Node<'o>>);
+
+        // a DFA node has at most one plain Epsilon transition, and one of each
+        // anchor kind
+        fn is_epsilon_of(tr: Transition<'_>, anchor: Option<Inst>) -> bool {
+            let is_anchor = |anchor| tr.instructs().any(|inst| inst == anchor);
+            tr.is_epsilon()
+                && match anchor {
+                    Some(anchor) => is_anchor(anchor),
+                    None => !is_anchor(Inst::AssertStartText) && !is_anchor(Inst::AssertEndText),
+                }
+        }
+
+        fn epsilon_target<'n>(
+            node: Option<Node<'n>>,
+            anchor: Option<Inst>,
+        ) -> Option<(Node<'n>, Transition<'n>)> {
+            let node = node?;
+            let targets = node.targets();
+            targets
+                .iter()
+                .find(|(_, tr)| is_epsilon_of(**tr, anchor))
+                .map(|(target, tr)| (*target, *tr))
+        }
+
+        fn target<'n>(node: Option<Node<'n>>, byte: u8) -> Option<(Node<'n>, Transition<'n>)> {
+            let node = node?;
+            let targets = node.targets();
+            targets
+                .iter()
+                .find(|(_, tr)| tr.contains(byte))
+                .map(|(target, tr)| (*target, *tr))
+        }
+
+        assert!(
+            crate::algo::is_dfa(self) && crate::algo::is_dfa(other),
+            "product union can be built only for DFAs"
+        );
+
+        let dfa = Graph::new_in(arena);
+        let mut node_map = Map::<Pair<'a, 'o>, Node<'d>>::new();
+        let mut queue = VecDeque::new();
+        let mut convert = |pair: Pair<'a, 'o>, queue: &mut VecDeque<Pair<'a, 'o>>| {
+            *node_map.entry(pair).or_insert_with(|| {
+                let dfa_node = dfa.node();
+                if pair.0.is_some_and(|node| node.is_final())
+                    || pair.1.is_some_and(|node| node.is_final())
+                {
+                    dfa_node.finalize();
+                }
+                queue.push_back(pair);
+                dfa_node
+            })
+        };
+
+        let start_pair = (Some(self.start_node()), Some(other.start_node()));
+        let start_node = convert(start_pair, &mut queue);
+        let self_start = epsilon_target(start_pair.0, Some(Inst::AssertStartText));
+        let other_start = epsilon_target(start_pair.1, Some(Inst::AssertStartText));
+        if self_start.is_some() || other_start.is_some() {
+            let text_start_pair = (
+                self_start.map_or(start_pair.0, |(target, _)| Some(target)),
+                other_start.map_or(start_pair.1, |(target, _)| Some(target)),
+            );
+            let tr = start_node.connect(convert(text_start_pair, &mut queue));
+            if let Some((_, start_tr)) = self_start {
+                tr.merge_instructs(start_tr.instructs(), None);
+            }
+            if let Some((_, start_tr)) = other_start {
+                tr.merge_instructs(start_tr.instructs(), None);
+            }
+        }
+
+        while let Some(pair) = queue.pop_front() {
+            let source = convert(pair, &mut queue);
+            for anchor in [Some(Inst::AssertEndText), None] {
+                let self_target = epsilon_target(pair.0, anchor);
+                let other_target = epsilon_target(pair.1, anchor);
+                let target_pair = (
+                    self_target.map(|(target, _)| target),
+                    other_target.map(|(target, _)| target),
+                );
+                if target_pair != (None, None) {
+                    let tr = source.connect(convert(target_pair, &mut queue));
+                    if let Some((_, epsilon_tr)) = self_target {
+                        tr.merge_instructs(epsilon_tr.instructs(), None);
+                    }
+                    if let Some((_, epsilon_tr)) = other_target {
+                        tr.merge_instructs(epsilon_tr.instructs(), None);
+                    }
+                }
+            }
+            for byte in u8::MIN..=u8::MAX {
+                let self_target = target(pair.0, byte);
+                let other_target = target(pair.1, byte);
+                let target_pair = (
+                    self_target.map(|(target, _)| target),
+                    other_target.map(|(target, _)| target),
+                );
+                if target_pair != (None, None) {
+                    let tr = source.connect(convert(target_pair, &mut queue));
+                    tr.merge(byte);
+                    if let Some((_, byte_tr)) = self_target {
+                        tr.merge_instructs(byte_tr.instructs_for(byte), Some(byte.into()));
+                    }
+                    if let Some((_, byte_tr)) = other_target {
+                        tr.merge_instructs(byte_tr.instructs_for(byte), Some(byte.into()));
+                    }
+                }
+            }
+        }
+        dfa.copy_tags_from(self);
+        dfa.copy_tags_from(other);
+        dfa
+    }
+
+    /// Builds a new NFA that accepts the strings accepted by any of `graphs`.
+    ///
+    /// The new graph gets a fresh start node with Epsilon transitions to the
+    /// copies of the start nodes of `graphs`. All nodes are copied with their
+    /// finality, transitions, instructions and tag groups, and get new
+    /// consecutive node IDs of the new graph. The result is ready to be
+    /// determinized with [`Graph::determinize_in`].
+    pub fn union_in<'d>(graphs: &[&Graph<'_>], arena: &'d mut Arena) -> Graph<'d> {
+        Self::union_impl(graphs, arena, false)
+    }
+
+    /// Builds a new NFA that accepts the strings accepted by any of `graphs`
+    /// like [`Graph::union_in`] does, but keeps track of which of them
+    /// accepts a string, so that one DFA can match several patterns, e.g. the
+    /// tokens of a lexer.
+    ///
+    /// The final nodes of the copies aren't final; instead, each of them gets
+    /// an Epsilon transition with [`Inst::Accept`] holding the index of its
+    /// graph in `graphs` to the common final node. The result should be
+    /// determinized with [`Graph::determinize_with_tags_in`] to keep these
+    /// instructions.
+    pub fn union_patterns_in<'d>(graphs: &[&Graph<'_>], arena: &'d mut Arena) -> Graph<'d> {
+        Self::union_impl(graphs, arena, true)
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    fn union_impl<'d>(graphs: &[&Graph<'_>], arena: &'d mut Arena, accept: bool) -> Graph<'d> {
+        let nfa = Graph::new_in(arena);
+        let start_node = nfa.node();
+        let end_node = accept.then(|| nfa.node().finalize());
+        for (index, graph) in graphs.iter().enumerate() {
+            let mut node_map = Map::new();
+            graph.for_each_node(|node| {
+                let nfa_node = nfa.node();
+                if node.is_final() {
+                    if let Some(end_node) = end_node {
+                        let tr = nfa_node.connect_epsilon(end_node);
+                        tr.merge_instruct(Inst::Accept(index as u32), None);
+                    } else {
+                        nfa_node.finalize();
+                    }
+                }
+                node_map.insert(node, nfa_node);
+            });
+            start_node.connect_epsilon(node_map[&graph.start_node()]);
+
+            graph.for_each_node(|node| {
+                let source = node_map[&node];
+                for (target, tr) in node.targets().iter() {
+                    source.connect(node_map[target]).merge(*tr);
+                }
+            });
+            nfa.copy_tags_from(graph);
+        }
+        nfa
+    }
+
+    /// Builds a DFA accepting exactly the `literals` directly from their trie,
+    /// without translating them into an NFA and determinizing it. Literals
+    /// with common prefixes share the nodes of these prefixes, and all the
+    /// literals that aren't prefixes of other ones end in one common final
+    /// node, so the DFA has as many states as the determinized NFA of the
+    /// alternation of the literals.
+    pub fn from_literals_in<'d, L: AsRef<[u8]>>(literals: &[L], arena: &'d mut Arena) -> Graph<'d> {
+        let trie = crate::algo::build_trie(literals.iter().map(AsRef::as_ref));
+        let dfa = Graph::new_strict_dfa_in(arena);
+        let mut nodes = vec![dfa.start_node(); trie.len()];
+        let mut leaf_node = None;
+        if trie[0].1 {
+            nodes[0].finalize();
+        }
+        for (index, (targets, _)) in trie.iter().enumerate() {
+            let node = nodes[index];
+            for (byte, target_index) in targets {
+                let (target_targets, target_is_end) = &trie[*target_index];
+                let target = if target_targets.is_empty() {
+                    *leaf_node.get_or_insert_with(|| dfa.node().finalize())
+                } else {
+                    let target = dfa.node();
+                    if *target_is_end {
+                        target.finalize();
+                    }
+                    nodes[*target_index] = target;
+                    target
+                };
+                node.connect(target).merge(*byte);
+            }
         }
-        .convert(start_e_closure);
         dfa
     }
 
+    /// Builds a deep copy of `self` in `arena`, e.g. to keep the graph before
+    /// destructive passes. All the nodes are copied with their finality,
+    /// transitions, instructions and tag groups, and keep their node IDs, so
+    /// the copy differs from the original by the graph ID only.
+    pub fn clone_in<'d>(&self, arena: &'d mut Arena) -> Graph<'d> {
+        let graph = Graph::new_in(arena);
+        graph.arena.set_strict_dfa(self.is_strict_dfa());
+        let nodes: Vec<_> = self
+            .arena
+            .nodes()
+            .map(|node| {
+                let new_node = graph.node();
+                if node.is_final() {
+                    new_node.finalize();
+                }
+                new_node
+            })
+            .collect();
+        for (node, source) in self.arena.nodes().zip(&nodes) {
+            for (target, tr) in node.targets().iter() {
+                source.connect(nodes[target.nid() as usize]).merge(*tr);
+            }
+        }
+        graph.copy_tags_from(self);
+        graph
+    }
+
+    /// Builds a new graph from `self` without the dead nodes, i.e. the nodes
+    /// from which no final node is reachable. Transitions into the dead nodes
+    /// are dropped, so the matcher goes to the implicit invalid state instead.
+    ///
+    /// The start node is always kept, even if it's dead itself, so the result
+    /// is never an empty graph.
+    #[allow(clippy::mutable_key_type)]
+    pub fn prune_dead_in<'d>(&self, arena: &'d mut Arena) -> Graph<'d> {
+        let live = self.live_nodes();
+        let graph = Graph::new_in(arena);
+        let mut node_map = Map::<Node<'a>, Node<'d>>::new();
+        self.for_each_node(|node| {
+            if live.contains(&node) || node == self.start_node() {
+                let new_node = graph.node();
+                if node.is_final() {
+                    new_node.finalize();
+                }
+                node_map.insert(node, new_node);
+            }
+        });
+
+        for (node, source) in node_map.iter() {
+            for (target, tr) in node.targets().iter() {
+                if live.contains(target) {
+                    source.connect(node_map[target]).merge(*tr);
+                }
+            }
+        }
+        graph.copy_tags_from(self);
+        graph
+    }
+
+    /// Checks if the DFA accepts the whole `input`, walking it byte by byte
+    /// from the start node. The `^` anchor is passed only before the first
+    /// byte, and the `$` anchor only after the last one.
+    ///
+    /// Panics if the graph isn't a DFA; use [`Graph::accepts_nfa`] for NFAs.
+    pub fn accepts(&self, input: &[u8]) -> bool {
+        assert!(
+            crate::algo::is_dfa(self),
+            "can't walk a non-DFA graph, use `accepts_nfa` instead"
+        );
+        if self.is_empty() {
+            return false;
+        }
+        let epsilon_target = |node: Node<'a>, anchor: Inst| {
+            node.targets()
+                .iter()
+                .find(|(_, tr)| tr.is_epsilon() && tr.instructs().any(|inst| inst == anchor))
+                .map(|(target, _)| *target)
+        };
+
+        let start_node = self.start_node();
+        let mut node = epsilon_target(start_node, Inst::AssertStartText).unwrap_or(start_node);
+        for byte in input {
+            let target = node
+                .targets()
+                .iter()
+                .find(|(_, tr)| tr.contains(*byte))
+                .map(|(target, _)| *target);
+            match target {
+                Some(target) => node = target,
+                None => return false,
+            }
+        }
+        node.is_final()
+            || epsilon_target(node, Inst::AssertEndText).is_some_and(|target| target.is_final())
+    }
+
+    /// Checks if the NFA accepts the whole `input`, simulating the subset
+    /// construction on the fly, i.e. without building a DFA. The anchors are
+    /// handled the same way as in [`Graph::accepts`]; other instructions are
+    /// ignored.
+    #[allow(clippy::mutable_key_type)]
+    pub fn accepts_nfa(&self, input: &[u8]) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let closure = |nodes: BTreeSet<Node<'a>>, text_start: bool, text_end: bool| {
+            let seeds = nodes.into_iter().map(|node| (node, BTreeSet::new()));
+            anchored_closure(seeds, text_start, text_end, false, &mut false).0
+        };
+
+        let mut nodes = closure(BTreeSet::from([self.start_node()]), true, input.is_empty());
+        for (i, byte) in input.iter().enumerate() {
+            let mut targets = BTreeSet::new();
+            for node in nodes.iter() {
+                for (target, tr) in node.targets().iter() {
+                    if tr.contains(*byte) {
+                        targets.insert(*target);
+                    }
+                }
+            }
+            if targets.is_empty() {
+                return false;
+            }
+            nodes = closure(targets, false, i + 1 == input.len());
+        }
+        nodes.iter().any(|node| node.is_final())
+    }
+
+    /// Checks if the graph accepts any string at all, i.e. a final node is
+    /// reachable from the start node over any transitions, so it works for
+    /// both DFAs and NFAs. The instructions, including the anchors, aren't
+    /// taken into account.
+    #[allow(clippy::mutable_key_type)]
+    pub fn accepts_any(&self) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let start_node = self.start_node();
+        let mut visited = Set::from_iter([start_node]);
+        let mut unvisited = vec![start_node];
+        while let Some(node) = unvisited.pop() {
+            if node.is_final() {
+                return true;
+            }
+            for target in node.targets().keys() {
+                if visited.insert(*target) {
+                    unvisited.push(*target);
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the dead nodes, i.e. the nodes reachable from the start node
+    /// from which no final node is reachable, in the order they are visited
+    /// by [`Graph::for_each_node`].
+    #[allow(clippy::mutable_key_type)]
+    pub fn dead_states(&self) -> Vec<Node<'a>> {
+        let live = self.live_nodes();
+        let mut dead = Vec::new();
+        self.for_each_node(|node| {
+            if !live.contains(&node) {
+                dead.push(node);
+            }
+        });
+        dead
+    }
+
+    /// Checks if a cycle passing at least one non-epsilon transition is
+    /// reachable from the start node, i.e. the graph can consume arbitrarily
+    /// long input. It's the automaton-level counterpart of an unbounded
+    /// maximal length in `Hir::len_hint`.
+    ///
+    /// Cycles of the epsilon transitions only are ignored, so the graph can be
+    /// an NFA. For a DFA, it's just a search for a back edge.
+    #[allow(clippy::mutable_key_type)]
+    pub fn has_cycle(&self) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        // Tarjan's algorithm: a non-epsilon transition makes a cycle if its
+        // source and target nodes are in the same strongly connected component.
+        // The descent is kept on an explicit stack of frames, each with the
+        // node's targets, the next target to visit and the node's low link
+        struct Tarjan<'a> {
+            indices: Map<Node<'a>, usize>,
+            stack: Vec<Node<'a>>,
+            components: Map<Node<'a>, usize>,
+        }
+        impl<'a> Tarjan<'a> {
+            fn enter(&mut self, node: Node<'a>) -> (Node<'a>, Vec<Node<'a>>, usize, usize) {
+                let index = self.indices.len();
+                self.indices.insert(node, index);
+                self.stack.push(node);
+                (node, node.targets().keys().copied().collect(), 0, index)
+            }
+
+            fn visit(&mut self, start_node: Node<'a>) {
+                let mut frames = vec![self.enter(start_node)];
+                while let Some((node, targets, next, low_link)) = frames.last_mut() {
+                    if let Some(target) = targets.get(*next).copied() {
+                        *next += 1;
+                        if let Some(target_index) = self.indices.get(&target) {
+                            if !self.components.contains_key(&target) {
+                                *low_link = (*low_link).min(*target_index);
+                            }
+                        } else {
+                            let frame = self.enter(target);
+                            frames.push(frame);
+                        }
+                        continue;
+                    }
+                    let (node, low_link) = (*node, *low_link);
+                    frames.pop();
+                    if low_link == self.indices[&node] {
+                        while let Some(member) = self.stack.pop() {
+                            self.components.insert(member, low_link);
+                            if member == node {
+                                break;
+                            }
+                        }
+                    }
+                    if let Some((_, _, _, parent_low_link)) = frames.last_mut() {
+                        *parent_low_link = (*parent_low_link).min(low_link);
+                    }
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            indices: Map::new(),
+            stack: Vec::new(),
+            components: Map::new(),
+        };
+        tarjan.visit(self.start_node());
+        tarjan.components.keys().any(|node| {
+            node.targets().iter().any(|(target, tr)| {
+                !tr.is_epsilon() && tarjan.components[node] == tarjan.components[target]
+            })
+        })
+    }
+
+    /// Returns the reachable nodes from which a final node is reachable,
+    /// computed by the backward reachability from the final nodes.
+    #[allow(clippy::mutable_key_type)]
+    fn live_nodes(&self) -> Set<Node<'a>> {
+        let mut sources = Map::<Node<'a>, Vec<Node<'a>>>::new();
+        let mut queue = VecDeque::new();
+        self.for_each_node(|node| {
+            if node.is_final() {
+                queue.push_back(node);
+            }
+            for target in node.targets().keys() {
+                sources.entry(*target).or_default().push(node);
+            }
+        });
+
+        let mut live = Set::from_iter(queue.iter().copied());
+        while let Some(node) = queue.pop_front() {
+            for source in sources.get(&node).into_iter().flatten() {
+                if live.insert(*source) {
+                    queue.push_back(*source);
+                }
+            }
+        }
+        live
+    }
+
+    /// Checks if some string is accepted by both `self` and `other`, i.e. the
+    /// intersection of their languages is non-empty.
+    ///
+    /// It's a reachability search over the pairs of nodes of the graphs, so
+    /// neither the intersection nor a DFA is built, and the search stops at
+    /// the first pair of final nodes. The graphs can be NFAs. The `^` anchor
+    /// is passed only before the first byte, and no byte can follow the `$`
+    /// anchor; other instructions are ignored.
+    pub fn overlaps(&self, other: &Graph<'_>) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+
+        // a pair of nodes, and flags if any byte is passed, and if the `$`
+        // anchor is passed in either graph
+        type Pair<'a, 'o> = (Node<'a>, Node<'o>, bool, bool, bool);
+        let key = |&(a, b, started, a_ended, b_ended): &Pair<'_, '_>| {
+            (a.uid(), b.uid(), started, a_ended, b_ended)
+        };
+        let passable = |tr: Transition<'_>, started: bool| {
+            tr.instructs()
+                .all(|inst| inst != Inst::AssertStartText || !started)
+        };
+        let is_end = |tr: Transition<'_>| tr.instructs().any(|inst| inst == Inst::AssertEndText);
+
+        let start = (self.start_node(), other.start_node(), false, false, false);
+        let mut visited = Set::new();
+        visited.insert(key(&start));
+        let mut unvisited = vec![start];
+        while let Some((a, b, started, a_ended, b_ended)) = unvisited.pop() {
+            if a.is_final() && b.is_final() {
+                return true;
+            }
+
+            let mut next = Vec::new();
+            for (a_target, a_tr) in a.targets().iter() {
+                if a_tr.is_epsilon() {
+                    if passable(*a_tr, started) {
+                        let a_ended = a_ended || is_end(*a_tr);
+                        next.push((*a_target, b, started, a_ended, b_ended));
+                    }
+                } else if !a_ended && !b_ended {
+                    for (b_target, b_tr) in b.targets().iter() {
+                        if !b_tr.is_epsilon() && a_tr.intersects(b_tr) {
+                            next.push((*a_target, *b_target, true, false, false));
+                        }
+                    }
+                }
+            }
+            for (b_target, b_tr) in b.targets().iter() {
+                if b_tr.is_epsilon() && passable(*b_tr, started) {
+                    let b_ended = b_ended || is_end(*b_tr);
+                    next.push((a, *b_target, started, a_ended, b_ended));
+                }
+            }
+
+            for pair in next {
+                if visited.insert(key(&pair)) {
+                    unvisited.push(pair);
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the `(source_nid, target_nid, symbols)` triples of all the
+    /// transitions of the graph sorted by `(source_nid, target_nid)`. The
+    /// order doesn't depend on the order the transitions were connected in,
+    /// so it's the canonical edge stream for serialization and hashing.
+    ///
+    /// Epsilon transitions give empty symbol sets, and the instructions are
+    /// omitted.
+    pub fn all_transitions_sorted(&self) -> impl Iterator<Item = (u32, u32, SetU8)> {
+        let mut transitions = Vec::new();
+        for node in self.arena().nodes() {
+            for (target, tr) in node.targets().iter() {
+                transitions.push((node.nid(), target.nid(), tr.as_set().clone()));
+            }
+        }
+        transitions.sort_by_key(|(source, target, _)| (*source, *target));
+        transitions.into_iter()
+    }
+
+    /// Returns the nodes reachable from the start node in the breadth-first
+    /// order, i.e. ordered by the number of transitions from the start node.
+    /// The targets of a node are discovered in the ascending order of their
+    /// UIDs, so the order doesn't depend on the collections the transitions
+    /// are stored in, and is the same for structurally equal graphs built the
+    /// same way.
+    ///
+    /// An empty graph gives an empty vector.
+    #[allow(clippy::mutable_key_type)]
+    pub fn nodes_in_bfs_order(&self) -> Vec<Node<'a>> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let start_node = self.start_node();
+        let mut visited = Set::from_iter([start_node]);
+        let mut nodes = vec![start_node];
+        let mut index = 0;
+        while index < nodes.len() {
+            let mut targets: Vec<Node<'a>> = nodes[index].targets().keys().copied().collect();
+            targets.sort_by_key(Node::uid);
+            for target in targets {
+                if visited.insert(target) {
+                    nodes.push(target);
+                }
+            }
+            index += 1;
+        }
+        nodes
+    }
+
+    /// Returns the node of this graph with the `uid`, or `None` if there is
+    /// no such node, e.g. the UID belongs to a node of another graph.
+    pub fn node_by_uid(&self, uid: u64) -> Option<Node<'a>> {
+        let nid = (uid & Node::ID_MASK) as usize;
+        if (uid >> Node::ID_BITS) as u32 != self.gid {
+            return None;
+        }
+        self.arena.nodes().nth(nid).filter(|node| node.uid() == uid)
+    }
+
     /// Visits each node of the graph, i.e. every node reachable from the start
     /// node.
+    #[allow(clippy::mutable_key_type)]
     pub fn for_each_node<F>(&self, f: F)
     where
         F: FnMut(Node<'a>),
     {
-        struct Lambda<'a, F: FnMut(Node<'a>)> {
-            visited: Set<Node<'a>>,
-            handler: F,
-        }
-        impl<'a, F: FnMut(Node<'a>)> Lambda<'a, F> {
-            fn visit(&mut self, node: Node<'a>) {
-                self.visited.insert(node);
-                (self.handler)(node);
-                for target in node.targets().keys() {
-                    if !self.visited.contains(target) {
-                        self.visit(*target);
-                    }
+        let mut handler = f;
+        let mut visited = Set::new();
+        let mut unvisited = vec![self.start_node()];
+        while let Some(node) = unvisited.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            handler(node);
+            // pushed in reverse so that targets are popped in their own order
+            let pushed = unvisited.len();
+            for target in node.targets().keys() {
+                if !visited.contains(target) {
+                    unvisited.push(*target);
                 }
             }
+            unvisited[pushed..].reverse();
         }
-        Lambda {
-            visited: Set::new(),
-            handler: f,
+    }
+
+    /// Renders the graph in the Graphviz DOT format. Final nodes are drawn as
+    /// double circles, and the start node is marked with an incoming arrow.
+    pub fn to_dot(&self) -> String {
+        self.dot_impl(|_| None)
+    }
+
+    /// Renders the graph in the Graphviz DOT format like [`Graph::to_dot`]
+    /// does, but labels each node with the IDs of the nodes it represents.
+    ///
+    /// The `map` is expected to be the one returned by
+    /// [`Graph::determinize_with_map_in`] for this graph.
+    pub fn to_dot_with_subsets(&self, map: &Map<u64, Vec<u64>>) -> String {
+        self.dot_impl(|node| {
+            map.get(&node.uid()).map(|uids| {
+                let nids: Vec<_> = uids
+                    .iter()
+                    .map(|uid| (uid & Node::ID_MASK).to_string())
+                    .collect();
+                format!("{{{}}}", nids.join(", "))
+            })
+        })
+    }
+
+    fn dot_impl<F>(&self, subset: F) -> String
+    where
+        F: Fn(Node<'a>) -> Option<String>,
+    {
+        fn escape(s: &str) -> String {
+            s.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+
+        let mut nodes = Vec::new();
+        if !self.is_empty() {
+            self.for_each_node(|node| nodes.push(node));
+        }
+        nodes.sort_by_key(|node| node.nid()); // make order consistent
+
+        let mut dot = String::from("digraph {\n    rankdir=LR;\n");
+        if let Some(start_node) = self.start_node.get() {
+            writeln!(dot, "    start [shape=point];").unwrap();
+            writeln!(dot, "    start -> n{};", start_node.nid()).unwrap();
         }
-        .visit(self.start_node());
+        for node in nodes.iter().copied() {
+            let shape = if node.is_final() {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            let mut label = node.nid().to_string();
+            if let Some(subset) = subset(node) {
+                write!(label, "\\n{}", escape(&subset)).unwrap();
+            }
+            writeln!(
+                dot,
+                "    n{} [shape={shape}, label=\"{label}\"];",
+                node.nid()
+            )
+            .unwrap();
+        }
+        for node in nodes {
+            let refer = node.targets();
+            let mut targets: Vec<_> = refer.iter().collect();
+            targets.sort_by_key(|(target, _)| target.nid());
+            for (target, transition) in targets {
+                let mut label = escape(&transition.to_string());
+                for inst in transition.instructs() {
+                    write!(label, "\\n{}", escape(&inst.to_string())).unwrap();
+                }
+                writeln!(
+                    dot,
+                    "    n{} -> n{} [label=\"{label}\"];",
+                    node.nid(),
+                    target.nid()
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Counts nodes and transitions of the graph reachable from the start
+    /// node.
+    pub fn stats(&self) -> GraphStats {
+        let mut stats = GraphStats::default();
+        self.for_each_node(|node| {
+            let targets = node.targets();
+            stats.nodes += 1;
+            stats.transitions += targets.len();
+            stats.epsilon_transitions += targets.values().filter(|tr| tr.is_epsilon()).count();
+            stats.max_out_degree = stats.max_out_degree.max(targets.len());
+            if node.is_final() {
+                stats.final_nodes += 1;
+            }
+        });
+        stats
     }
+
+    /// Collects the main properties of the automaton in one place. It is
+    /// useful for debugging of surprising match behavior.
+    ///
+    /// If the graph is not a DFA, `is_complete` and `is_minimal` are always
+    /// `false`.
+    #[allow(clippy::mutable_key_type)]
+    pub fn analyze(&self) -> DfaAnalysis {
+        let mut nodes = Vec::new();
+        self.for_each_node(|node| nodes.push(node));
+        let index_map: Map<Node<'a>, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (*node, index))
+            .collect();
+
+        let is_dfa = crate::algo::is_dfa(self);
+        let final_count = nodes.iter().filter(|node| node.is_final()).count();
+        let mut analysis = DfaAnalysis {
+            is_dfa,
+            is_complete: false,
+            state_count: nodes.len(),
+            final_count,
+            is_minimal: false,
+            language_empty: final_count == 0,
+        };
+        if !is_dfa {
+            return analysis;
+        }
+
+        // The last index is reserved for the implicit dead state.
+        let dead_index = nodes.len();
+        let mut delta = vec![[dead_index; 1 << u8::BITS]; nodes.len() + 1];
+        for (index, node) in nodes.iter().enumerate() {
+            for (target, tr) in node.targets().iter() {
+                for symbol in tr.symbols() {
+                    delta[index][symbol as usize] = index_map[target];
+                }
+            }
+        }
+        analysis.is_complete = delta[..dead_index]
+            .iter()
+            .all(|row| row.iter().all(|target| *target != dead_index));
+
+        // Moore's partition refinement
+        let mut classes: Vec<usize> = nodes
+            .iter()
+            .map(|node| node.is_final() as usize)
+            .chain([0])
+            .collect();
+        let mut classes_len = 0;
+        loop {
+            let mut signatures = BTreeMap::new();
+            let new_classes: Vec<usize> = (0..delta.len())
+                .map(|index| {
+                    let mut signature = Vec::with_capacity(delta[index].len() + 1);
+                    signature.push(classes[index]);
+                    signature.extend(delta[index].iter().map(|target| classes[*target]));
+                    let next_class = signatures.len();
+                    *signatures.entry(signature).or_insert(next_class)
+                })
+                .collect();
+            classes = new_classes;
+            if signatures.len() == classes_len {
+                break;
+            }
+            classes_len = signatures.len();
+        }
+
+        let dead_class = classes[dead_index];
+        let min_state_count = classes[..dead_index]
+            .iter()
+            .copied()
+            .filter(|class| analysis.is_complete || *class != dead_class)
+            .collect::<BTreeSet<_>>()
+            .len();
+        analysis.is_minimal = min_state_count == nodes.len();
+        analysis
+    }
+}
+
+/// Size summary of a graph collected by [`Graph::stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GraphStats {
+    /// Number of nodes reachable from the start node.
+    pub nodes: usize,
+
+    /// Number of transitions between the reachable nodes.
+    pub transitions: usize,
+
+    /// Number of Epsilon transitions among them.
+    pub epsilon_transitions: usize,
+
+    /// Number of reachable final nodes.
+    pub final_nodes: usize,
+
+    /// The largest number of outgoing transitions of a single node.
+    pub max_out_degree: usize,
+}
+
+/// Properties of an automaton collected by [`Graph::analyze`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DfaAnalysis {
+    /// The graph meets the DFA requirements.
+    pub is_dfa: bool,
+
+    /// Every state has a transition for each byte.
+    pub is_complete: bool,
+
+    /// Number of states reachable from the start node.
+    pub state_count: usize,
+
+    /// Number of final states reachable from the start node.
+    pub final_count: usize,
+
+    /// There is no DFA with fewer states accepting the same language.
+    pub is_minimal: bool,
+
+    /// The automaton doesn't accept any string.
+    pub language_empty: bool,
+}
+
+/// Instructions met on the Epsilon paths to the nodes of a closure.
+type InstMap<'n> = BTreeMap<Node<'n>, BTreeSet<Inst>>;
+
+/// Epsilon closure with the instructions met on the paths to its nodes.
+type Closure<'n> = (BTreeSet<Node<'n>>, InstMap<'n>);
+
+/// Builds Epsilon closure of the `nodes`, each of them with the instructions
+/// already met on the path to it. Epsilon transitions with anchor assertions
+/// are passed only if they are allowed by `text_start` and `text_end` flags.
+///
+/// If `tags` is set, the tag and accept instructions of the passed transitions
+/// are collected for every node of the closure, otherwise the instruction map is
+/// empty. `tag_conflicts` is set if a node is reached by paths with different
+/// tag instructions.
+#[allow(clippy::mutable_key_type)]
+fn anchored_closure<'n>(
+    nodes: impl IntoIterator<Item = (Node<'n>, BTreeSet<Inst>)>,
+    text_start: bool,
+    text_end: bool,
+    tags: bool,
+    tag_conflicts: &mut bool,
+) -> Closure<'n> {
+    let mut closure = BTreeSet::new();
+    let mut inst_map = InstMap::new();
+    let mut unvisited: Vec<_> = nodes.into_iter().collect();
+    while let Some((node, insts)) = unvisited.pop() {
+        let is_new = closure.insert(node);
+        if tags && !is_new {
+            *tag_conflicts |=
+                has_tag_conflicts([&insts, &inst_map.get(&node).cloned().unwrap_or_default()]);
+        }
+        let insts = if tags && !insts.is_empty() {
+            let node_insts: &mut BTreeSet<Inst> = inst_map.entry(node).or_default();
+            if !is_new && insts.is_subset(node_insts) {
+                continue;
+            }
+            node_insts.extend(insts);
+            node_insts.clone()
+        } else if is_new {
+            inst_map.get(&node).cloned().unwrap_or_default()
+        } else {
+            continue;
+        };
+        for (target, tr) in node.targets().iter() {
+            let is_passable = tr.is_epsilon()
+                && tr.instructs().all(|inst| match inst {
+                    Inst::AssertStartText => text_start,
+                    Inst::AssertEndText => text_end,
+                    _ => true,
+                });
+            if is_passable {
+                let mut target_insts = insts.clone();
+                if tags {
+                    target_insts.extend(tr.instructs().filter(|inst| {
+                        matches!(
+                            inst,
+                            Inst::WritePos(..)
+                                | Inst::InvalidateTag(_)
+                                | Inst::Accept(_)
+                                | Inst::Priority(_)
+                        )
+                    }));
+                }
+                unvisited.push((*target, target_insts));
+            }
+        }
+    }
+    (closure, inst_map)
+}
+
+/// Checks if the instruction sets differ in the [`Inst::WritePos`] or
+/// [`Inst::InvalidateTag`] instructions.
+fn has_tag_conflicts<'i, I>(inst_sets: impl IntoIterator<Item = I>) -> bool
+where
+    I: IntoIterator<Item = &'i Inst>,
+{
+    let mut tag_sets = inst_sets.into_iter().map(|insts| {
+        insts
+            .into_iter()
+            .filter(|inst| matches!(inst, Inst::WritePos(..) | Inst::InvalidateTag(_)))
+            .collect::<BTreeSet<_>>()
+    });
+    let Some(first) = tag_sets.next() else {
+        return false;
+    };
+    tag_sets.any(|tags| tags != first)
 }
 
 impl std::ops::Drop for Graph<'_> {
@@ -195,17 +1479,14 @@ macro_rules! impl_fmt {
         impl ::std::fmt::$trait for Graph<'_> {
             #[allow(clippy::mutable_key_type)]
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                fn recurse<'a>(node: Node<'a>, visited: &mut BTreeSet<Node<'a>>) {
-                    visited.insert(node);
-                    for target in node.targets().keys().copied() {
-                        if !visited.contains(&target) {
-                            recurse(target, visited);
-                        }
-                    }
-                }
                 if let Some(start_node) = self.start_node.get() {
                     let mut visited = BTreeSet::new();
-                    recurse(start_node, &mut visited);
+                    let mut unvisited = vec![start_node];
+                    while let Some(node) = unvisited.pop() {
+                        if visited.insert(node) {
+                            unvisited.extend(node.targets().keys().copied());
+                        }
+                    }
                     let mut first = true;
                     for node in visited.iter().copied() {
                         if first {
@@ -216,14 +1497,12 @@ macro_rules! impl_fmt {
                         let mut is_empty = true;
                         ::std::fmt::$trait::fmt(&node, f)?;
                         f.write_str(" {")?;
-                        let refer = node.targets();
-                        let mut targets: Vec<_> = refer.iter().collect();
-                        targets.sort_by_key(|(target, _)| target.nid()); // make order consistent
-                        for (target, transition) in targets.iter() {
+                        let targets = node.targets();
+                        for (target, transition) in targets.iter_sorted() {
                             f.write_str("\n    ")?;
                             ::std::fmt::$trait::fmt(transition, f)?;
                             f.write_str(" -> ")?;
-                            if node == **target {
+                            if node == *target {
                                 f.write_str("self")?;
                             } else {
                                 ::std::fmt::$trait::fmt(&target, f)?;
@@ -255,7 +1534,7 @@ impl_fmt!(std::fmt::LowerHex);
 
 struct TagGroupIter<'a, 'g> {
     graph: &'g Graph<'a>,
-    labels: Vec<u32>,
+    labels: Vec<GroupLabel>,
     index: usize,
 }
 
@@ -275,13 +1554,14 @@ impl<'a, 'g> TagGroupIter<'a, 'g> {
 }
 
 impl<'a, 'g> Iterator for TagGroupIter<'a, 'g> {
-    type Item = (u32, (Tag, Tag));
+    type Item = (GroupLabel, (Tag, Tag));
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index < self.labels.len() {
-            let label = self.labels[self.index];
+            let label = self.labels[self.index].clone();
             self.index += 1;
-            Some((label, self.graph.tag_group(label).unwrap()))
+            let tags = self.graph.tag_group(&label).unwrap();
+            Some((label, tags))
         } else {
             None
         }
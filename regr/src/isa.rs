@@ -1,12 +1,41 @@
 /// Instruction represents the actions that can be performed during a transition
 /// step.
+///
+/// Both `Display` and `Debug` print the short form used in the graph dumps,
+/// documented for every variant, so the graph snapshots in tests are
+/// independent of the formatting of the derived implementations.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Inst {
-    /// Store the current position to the specified register
+    /// Store the current position to the specified register.
+    ///
+    /// Printed as `wrpos t{tag}/r{reg}`.
     WritePos(/*tag id*/ u32, /*reg id*/ u32),
 
-    /// Invalidate the specified register
+    /// Invalidate the specified register.
+    ///
+    /// Printed as `invd t{tag}`.
     InvalidateTag(/*tag id*/ u32),
+
+    /// Allow the transition only at the beginning of the haystack.
+    ///
+    /// Printed as `asrt start`.
+    AssertStartText,
+
+    /// Allow the transition only at the end of the haystack.
+    ///
+    /// Printed as `asrt end`.
+    AssertEndText,
+
+    /// Mark the match as the one of the specified pattern.
+    ///
+    /// Printed as `accept p{pattern}`.
+    Accept(/*pattern id*/ u32),
+
+    /// Mark the match as the one of the alternative with the specified
+    /// priority; the lower value is preferred over a longer match.
+    ///
+    /// Printed as `prio {priority}`.
+    Priority(/*priority*/ u32),
 }
 
 macro_rules! impl_fmt {
@@ -16,6 +45,10 @@ macro_rules! impl_fmt {
                 match self {
                     Inst::WritePos(tag, reg) => write!(f, "wrpos t{tag}/r{reg}")?,
                     Inst::InvalidateTag(tag) => write!(f, "invd t{tag}")?,
+                    Inst::AssertStartText => f.write_str("asrt start")?,
+                    Inst::AssertEndText => f.write_str("asrt end")?,
+                    Inst::Accept(pattern) => write!(f, "accept p{pattern}")?,
+                    Inst::Priority(priority) => write!(f, "prio {priority}")?,
                 }
                 Ok(())
             }
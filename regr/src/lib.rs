@@ -3,8 +3,11 @@ pub mod algo;
 mod arena;
 pub use arena::Arena;
 
+mod error;
+pub use error::{Error, Result};
+
 mod graph;
-pub use graph::Graph;
+pub use graph::{DfaAnalysis, Graph, GraphStats};
 
 mod isa;
 pub use isa::Inst;
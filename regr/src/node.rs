@@ -67,7 +67,13 @@ impl<'a> Node<'a> {
         self.0.arena
     }
 
-    /// Connects this node to another node with an Epsilon transition.
+    /// Connects this node to another node, and returns the transition between
+    /// them.
+    ///
+    /// If the nodes are already connected, the existing transition is returned
+    /// as is. Otherwise, a new transition without symbols is created, i.e. it
+    /// is an Epsilon transition until any byte is merged into it. Use
+    /// [`Node::connect_epsilon`] to be sure that the result is an Epsilon one.
     pub fn connect(&self, to: Node<'a>) -> Transition<'a> {
         assert_eq!(
             self.gid(),
@@ -84,6 +90,53 @@ impl<'a> Node<'a> {
         }
     }
 
+    /// Connects this node to another node with an Epsilon transition, and
+    /// returns it.
+    ///
+    /// Panics if the nodes are already connected with a byte transition,
+    /// because a transition can't be an Epsilon and a byte one at once.
+    pub fn connect_epsilon(&self, to: Node<'a>) -> Transition<'a> {
+        debug_assert!(
+            !self.0.arena.is_strict_dfa(),
+            "a strict DFA can't have Epsilon transitions"
+        );
+        let tr = self.connect(to);
+        assert!(
+            tr.is_epsilon(),
+            "nodes are already connected with a non-Epsilon transition"
+        );
+        tr
+    }
+
+    /// Removes the transition from this node to `target`, and returns it, if
+    /// the nodes are connected.
+    ///
+    /// The transition is only unlinked from the targets of this node. Its
+    /// memory belongs to the arena, and is reclaimed when the arena is reset
+    /// or dropped.
+    pub fn disconnect(&self, target: Node<'a>) -> Option<Transition<'a>> {
+        self.0.targets.borrow_mut().remove(&target)
+    }
+
+    /// Removes the `symbol` from the transition from this node to `target`,
+    /// and returns `true` if the transition contained it.
+    ///
+    /// If no symbols are left, the transition is disconnected, rather than
+    /// turned into an Epsilon one.
+    pub fn remove_symbol(&self, target: Node<'a>, symbol: u8) -> bool {
+        let Some(tr) = self.targets().get(&target).copied() else {
+            return false;
+        };
+        if !tr.contains(symbol) {
+            return false;
+        }
+        tr.exclude(symbol);
+        if tr.is_epsilon() {
+            self.disconnect(target);
+        }
+        true
+    }
+
     #[allow(clippy::mutable_key_type)]
     pub fn closure<T>(&self, symbol: T) -> BTreeSet<Node<'a>>
     where
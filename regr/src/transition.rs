@@ -19,6 +19,7 @@ use std::ops::Deref;
 pub struct Transition<'a>(&'a TransitionInner<'a>);
 
 pub(crate) struct TransitionInner<'a> {
+    source: Node<'a>,
     symset: RefCell<SetU8>,
     insts: RefCell<BumpVec<'a, (Inst, &'a mut SetU8)>>,
     arena: &'a Arena,
@@ -35,11 +36,26 @@ impl<'a> Transition<'a> {
         );
         let arena = source.arena();
         Self(arena.alloc_with(|| TransitionInner {
+            source,
             symset: RefCell::new(SetU8::empty()),
             insts: RefCell::new(BumpVec::new_in(&arena.shared_bump)),
             arena,
         }))
     }
+
+    /// Panics in debug builds if the transition belongs to a strict DFA, and
+    /// shares a byte with another transition from the same node.
+    fn debug_assert_deterministic(&self) {
+        if cfg!(debug_assertions) && self.0.arena.is_strict_dfa() {
+            for tr in self.0.source.targets().values() {
+                assert!(
+                    tr.is(*self) || !tr.intersects(*self),
+                    "a strict DFA can't have overlapping transitions from node({})",
+                    self.0.source.nid()
+                );
+            }
+        }
+    }
 }
 
 impl<'a> Transition<'a> {
@@ -54,6 +70,20 @@ impl<'a> Transition<'a> {
         self.0.symset.borrow().is_empty()
     }
 
+    /// Returns the number of bytes in this transition. Epsilon isn't taken
+    /// into account.
+    #[inline]
+    pub fn symbol_count(self) -> u32 {
+        self.0.symset.borrow().len() as u32
+    }
+
+    /// Checks if this transition contains all the 256 bytes. Epsilon isn't
+    /// taken into account.
+    #[inline]
+    pub fn is_full(self) -> bool {
+        self.0.symset.borrow().is_full()
+    }
+
     /// Returns iterator over all symbols in this trasition instance in
     /// ascendent order.
     pub fn symbols(self) -> impl Iterator<Item = u8> {
@@ -68,6 +98,30 @@ impl<'a> Transition<'a> {
         RangeIter::new(borrow)
     }
 
+    /// Returns iterator over all symbols that are absent in this trasition
+    /// instance in ascendent order. Epsilon isn't taken into account, so an
+    /// epsilon transition yields all the bytes.
+    pub fn absent_symbols(self) -> impl Iterator<Item = u8> {
+        let borrow = self.0.symset.borrow();
+        ByteIter::complement(borrow)
+    }
+
+    /// Returns iterator over the maximal symbol ranges that are absent in this
+    /// trasition instance in ascendent order.
+    pub fn absent_ranges(self) -> impl Iterator<Item = RangeU8> {
+        let borrow = self.0.symset.borrow();
+        let mut ranges = RangeIter::complement(borrow).peekable();
+        std::iter::from_fn(move || {
+            let mut range = ranges.next()?;
+            while let Some(next) =
+                ranges.next_if(|next| range.last().checked_steps_between(next.start()) == Some(1))
+            {
+                range = RangeU8::new(range.start(), next.last());
+            }
+            Some(range)
+        })
+    }
+
     /// Returns a clone of the symbol set in this transition instance.
     pub fn as_set(&self) -> Ref<'_, SetU8> {
         self.0.symset.borrow()
@@ -89,6 +143,19 @@ impl<'a> Transition<'a> {
         Mergeable::merge(self, other);
     }
 
+    /// Removes the `other` symbols from the symbol set of this transition.
+    ///
+    /// Unlike [`Rejectable::reject`], the instruction bitmaps are left
+    /// untouched, so the instructions of the remaining symbols are kept as is,
+    /// and the removed symbols get their instructions back if they are merged
+    /// again.
+    pub fn exclude<T>(&self, other: T)
+    where
+        SetU8: Excludable<T>,
+    {
+        self.0.symset.borrow_mut().exclude(other);
+    }
+
     /// Adds an instruction to specific symbols in this transition. If the
     /// specified symbols are not present in this transition, they are ignored.
     /// For `None`, the instruction is added to all symbols.
@@ -203,6 +270,7 @@ where
 {
     fn merge(&self, rhs: T) -> &Self {
         self.0.symset.borrow_mut().include(rhs);
+        self.debug_assert_deterministic();
         self
     }
 }
@@ -212,6 +280,7 @@ impl<'a, 'b> Mergeable<Transition<'b>> for Transition<'a> {
         let other_symset = other.0.symset.borrow();
         let other_symset = other_symset.deref();
         self.0.symset.borrow_mut().include(other_symset);
+        self.debug_assert_deterministic();
         for (other_inst, other_symset) in other.0.insts.borrow().iter() {
             self.merge_instruct(*other_inst, Some((*other_symset).clone()));
         }
@@ -267,7 +336,7 @@ impl std::fmt::Display for Transition<'_> {
         while let Some(cur_range) = range {
             has_symbols = true;
             if let Some(next_range) = iter.next() {
-                if cur_range.last().steps_between(next_range.start()) == 1 {
+                if cur_range.last().checked_steps_between(next_range.start()) == Some(1) {
                     range = Some(RangeU8::new(cur_range.start(), next_range.last()));
                     continue;
                 } else {
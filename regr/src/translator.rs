@@ -1,10 +1,14 @@
+use crate::algo::build_trie;
+use crate::error::{Error, Result};
 use crate::graph::Graph;
 use crate::isa::Inst;
 use crate::node::Node;
 use crate::tag::{Tag, TagBank};
+use crate::transition::Transition;
 use redt::SetU8;
-use resy::{ConcatHir, DisjunctHir, GroupHir, Hir, RepeatHir};
+use resy::{AnchorKind, ConcatHir, DisjunctHir, GroupHir, Hir, RepeatHir};
 use std::cell::Cell;
+use std::collections::BTreeMap;
 
 struct Pair<'a> {
     first: Node<'a>,
@@ -15,42 +19,95 @@ fn pair<'a>(first: Node<'a>, last: Node<'a>) -> Pair<'a> {
     Pair { first, last }
 }
 
+/// Default maximal number of nodes a translator adds to the graph, see
+/// [`Translator::with_max_expansion`].
+const DEFAULT_MAX_EXPANSION: usize = 1 << 20;
+
 /// Translator for translating a HIR into a NFA.
 pub struct Translator<'a, 'g> {
     graph: &'g Graph<'a>,
     next_reg: Cell<u32>,
     tag_bank: TagBank,
+    pending_tags: BTreeMap<u32, (Tag, Vec<Transition<'a>>)>,
+    first_node_count: usize,
+    max_expansion: usize,
+    leftmost_first: bool,
+    prioritize_disjunct: bool,
 }
 
 impl<'a, 'g> Translator<'a, 'g> {
     pub fn new(graph: &'g Graph<'a>) -> Self {
-        // TODO: add optional checker for DFA graph
         Self {
             graph,
             next_reg: Cell::new(0),
             tag_bank: TagBank::new(),
+            pending_tags: BTreeMap::new(),
+            first_node_count: graph.arena().node_count(),
+            max_expansion: DEFAULT_MAX_EXPANSION,
+            leftmost_first: false,
+            prioritize_disjunct: false,
         }
     }
 
-    pub fn translate(&mut self, hir: &Hir, start_hode: Node<'a>, end_node: Node<'a>) {
+    /// Marks the alternatives of the top-level disjunction with the
+    /// [`Inst::Priority`] instructions in the order they are listed, so a
+    /// matcher can prefer the first-listed matching alternative over a longer
+    /// match of the later ones, i.e. `a|ab` matches `"a"` in `"ab"` like in
+    /// PCRE. Nested disjunctions keep the leftmost-longest semantics.
+    ///
+    /// The priorities are instructions on the paths to the end node, so the
+    /// DFA states reached by different alternatives aren't equivalent anymore.
+    /// A minimization ignoring the instructions would merge them, and the
+    /// preference would be lost; the literal trie isn't built for the
+    /// top-level disjunction for the same reason.
+    pub fn with_leftmost_first(mut self, enabled: bool) -> Self {
+        self.leftmost_first = enabled;
+        self
+    }
+
+    /// Limits the number of nodes the translator adds to the graph to
+    /// `max_nodes`, 2^20 by default. The counted repetitions, like `a{1000}`,
+    /// copy the subgraph of the repeated expression, so the limit keeps them
+    /// from exhausting the memory. The determinization and the walks over the
+    /// graph don't recurse, so a graph this large doesn't overflow the stack.
+    pub fn with_max_expansion(mut self, max_nodes: usize) -> Self {
+        self.max_expansion = max_nodes;
+        self
+    }
+
+    /// Translates the `hir` into the subgraph between the `start_node` and
+    /// the `end_node`.
+    ///
+    /// Returns an error if the repetitions expand the graph over the limit
+    /// set by [`Translator::with_max_expansion`]. The graph is left partially
+    /// built then.
+    pub fn translate(&mut self, hir: &Hir, start_node: Node<'a>, end_node: Node<'a>) -> Result<()> {
         let mut tag = None;
-        _ = self.translate_hir(hir, pair(start_hode, end_node), &mut tag);
+        self.prioritize_disjunct = self.leftmost_first && matches!(hir, Hir::Disjunct(_));
+        self.translate_hir(hir, pair(start_node, end_node), &mut tag)?;
+        Ok(())
     }
 
-    fn translate_hir(&mut self, hir: &Hir, sub: Pair<'a>, tag: &mut Option<Tag>) -> Summary {
+    fn translate_hir(
+        &mut self,
+        hir: &Hir,
+        sub: Pair<'a>,
+        tag: &mut Option<Tag>,
+    ) -> Result<Summary> {
         match hir {
-            Hir::Literal(literal) => self.translate_literal(literal, sub, tag),
-            Hir::Class(class) => self.translate_class(class, sub, tag),
+            Hir::Literal(literal) => Ok(self.translate_literal(literal, sub, tag)),
+            Hir::Class(class) => Ok(self.translate_class(class, sub, tag)),
             Hir::Group(group) => self.translate_group(group, sub, tag),
             Hir::Repeat(repeat) => self.translate_repeat(repeat, sub, tag),
             Hir::Concat(concat) => self.translate_concat(concat, sub, tag),
             Hir::Disjunct(disjunct) => self.translate_disjunct(disjunct, sub, tag),
+            Hir::Anchor(kind) => Ok(self.translate_anchor(*kind, sub)),
         }
     }
 
     fn translate_literal(&self, literal: &[u8], sub: Pair<'a>, tag: &mut Option<Tag>) -> Summary {
         if literal.is_empty() {
-            sub.first.connect(sub.last);
+            sub.first.connect_epsilon(sub.last);
             return Summary::empty();
         }
         let mut first = sub.first;
@@ -77,18 +134,31 @@ impl<'a, 'g> Translator<'a, 'g> {
         Summary::empty()
     }
 
+    /// Anchor is an Epsilon transition that can be passed only if the
+    /// corresponding assertion about the current position is true.
+    fn translate_anchor(&self, kind: AnchorKind, sub: Pair<'a>) -> Summary {
+        let inst = match kind {
+            AnchorKind::StartText => Inst::AssertStartText,
+            AnchorKind::EndText => Inst::AssertEndText,
+        };
+        sub.first
+            .connect_epsilon(sub.last)
+            .merge_instruct(inst, None);
+        Summary::empty()
+    }
+
     // Only this function can create a new tag
     fn translate_group(
         &mut self,
         group: &GroupHir,
         sub: Pair<'a>,
         tag: &mut Option<Tag>,
-    ) -> Summary {
+    ) -> Result<Summary> {
         let first = self.graph.node();
-        let tr_in = sub.first.connect(first);
+        let tr_in = sub.first.connect_epsilon(first);
 
         let last = self.graph.node();
-        let tr_out = last.connect(sub.last);
+        let tr_out = last.connect_epsilon(sub.last);
 
         let mut summary = Summary::empty();
 
@@ -98,6 +168,7 @@ impl<'a, 'g> Translator<'a, 'g> {
             tag.unwrap_or_else(|| self.tag_bank.absolute())
         };
 
+        self.resolve_pending_tag(open_tag, &mut summary);
         match open_tag {
             Tag::Absolute { id, reg } => {
                 tr_in.merge_instruct(Inst::WritePos(id, reg), None);
@@ -110,7 +181,7 @@ impl<'a, 'g> Translator<'a, 'g> {
         }
 
         let mut inner_tag = Some(self.tag_bank.relative(open_tag, 0));
-        let sum = self.translate_hir(group.inner(), pair(first, last), &mut inner_tag);
+        let sum = self.translate_hir(group.inner(), pair(first, last), &mut inner_tag)?;
         summary.merge(&sum);
 
         let close_tag = if let Some((_, close_tag)) = self.graph.tag_group(group.label()) {
@@ -119,6 +190,7 @@ impl<'a, 'g> Translator<'a, 'g> {
             inner_tag.unwrap_or_else(|| self.tag_bank.absolute())
         };
 
+        self.resolve_pending_tag(close_tag, &mut summary);
         match close_tag {
             Tag::Absolute { id, reg } => {
                 tr_out.merge_instruct(Inst::WritePos(id, reg), None);
@@ -133,7 +205,7 @@ impl<'a, 'g> Translator<'a, 'g> {
         *tag = Some(self.tag_bank.relative(close_tag, 0));
         self.graph.add_tag_group(group.label(), open_tag, close_tag);
 
-        summary
+        Ok(summary)
     }
 
     fn translate_repeat(
@@ -141,7 +213,7 @@ impl<'a, 'g> Translator<'a, 'g> {
         repeat: &RepeatHir,
         mut sub: Pair<'a>,
         tag: &mut Option<Tag>,
-    ) -> Summary {
+    ) -> Result<Summary> {
         match repeat.iter_hint() {
             // Kleene star
             //          ╭────ε────╮
@@ -153,10 +225,10 @@ impl<'a, 'g> Translator<'a, 'g> {
             (0, None) => {
                 let first = self.graph.node();
                 let last = self.graph.node();
-                sub.first.connect(first);
-                last.connect(sub.last);
-                last.connect(first);
-                sub.first.connect(sub.last);
+                sub.first.connect_epsilon(first);
+                last.connect_epsilon(sub.last);
+                last.connect_epsilon(first);
+                sub.first.connect_epsilon(sub.last);
                 *tag = None;
                 self.translate_hir(repeat.inner(), pair(first, last), tag)
             }
@@ -168,9 +240,9 @@ impl<'a, 'g> Translator<'a, 'g> {
             (1, None) => {
                 let first = self.graph.node();
                 let last = self.graph.node();
-                sub.first.connect(first);
-                last.connect(sub.last);
-                last.connect(first);
+                sub.first.connect_epsilon(first);
+                last.connect_epsilon(sub.last);
+                last.connect_epsilon(first);
                 *tag = None;
                 self.translate_hir(repeat.inner(), pair(first, last), tag)
             }
@@ -183,9 +255,10 @@ impl<'a, 'g> Translator<'a, 'g> {
                 let mut summary = Summary::empty();
                 let mut first = sub.first;
                 for _ in 1..n {
+                    self.check_expansion()?;
                     let last = self.graph.node();
                     *tag = None;
-                    let s = self.translate_hir(repeat.inner(), pair(first, last), tag);
+                    let s = self.translate_hir(repeat.inner(), pair(first, last), tag)?;
                     summary.merge(&s);
                     first = last;
                 }
@@ -193,12 +266,12 @@ impl<'a, 'g> Translator<'a, 'g> {
                 let first = self.graph.node();
                 let last = self.graph.node();
                 *tag = None;
-                let s = self.translate_hir(repeat.inner(), pair(first, last), tag);
+                let s = self.translate_hir(repeat.inner(), pair(first, last), tag)?;
                 summary.merge(&s);
-                sub.first.connect(first);
-                last.connect(sub.last);
-                last.connect(first);
-                summary
+                sub.first.connect_epsilon(first);
+                last.connect_epsilon(sub.last);
+                last.connect_epsilon(first);
+                Ok(summary)
             }
             //
             // (0)──'a'──(1)──'a'──...──'a'─→(n)
@@ -206,21 +279,22 @@ impl<'a, 'g> Translator<'a, 'g> {
             (n, Some(m)) if n == m => {
                 let mut summary = Summary::empty();
                 if n == 0 {
-                    sub.first.connect(sub.last);
+                    sub.first.connect_epsilon(sub.last);
                 } else {
                     let mut first = sub.first;
                     for _ in 0..n - 1 {
+                        self.check_expansion()?;
                         let last = self.graph.node();
                         *tag = None;
-                        let s = self.translate_hir(repeat.inner(), pair(first, last), tag);
+                        let s = self.translate_hir(repeat.inner(), pair(first, last), tag)?;
                         summary.merge(&s);
                         first = last;
                     }
                     *tag = None;
-                    let s = self.translate_hir(repeat.inner(), pair(first, sub.last), tag);
+                    let s = self.translate_hir(repeat.inner(), pair(first, sub.last), tag)?;
                     summary.merge(&s);
                 }
-                summary
+                Ok(summary)
             }
             //
             // (0)──'a'─..─'a'─→(n)──ε─→(○)──'a'─→(○)──ε─→(○)──ε─→(○)──'a'──(○)──ε─→(○)──...──ε─→(○)
@@ -233,26 +307,28 @@ impl<'a, 'g> Translator<'a, 'g> {
                 let mut summary = Summary::empty();
                 let mut first = sub.first;
                 for _ in 0..n {
+                    self.check_expansion()?;
                     let last = self.graph.node();
                     *tag = None;
-                    let s = self.translate_hir(repeat.inner(), pair(first, last), tag);
+                    let s = self.translate_hir(repeat.inner(), pair(first, last), tag)?;
                     summary.merge(&s);
                     first = last;
                 }
                 for _ in n..m {
+                    self.check_expansion()?;
                     let mid_one = self.graph.node();
-                    first.connect(mid_one);
+                    first.connect_epsilon(mid_one);
                     let mid_two = self.graph.node();
                     *tag = None;
-                    let s = self.translate_hir(repeat.inner(), pair(mid_one, mid_two), tag);
+                    let s = self.translate_hir(repeat.inner(), pair(mid_one, mid_two), tag)?;
                     summary.merge(&s);
                     let last = self.graph.node();
-                    mid_two.connect(last);
-                    first.connect(sub.last);
+                    mid_two.connect_epsilon(last);
+                    first.connect_epsilon(sub.last);
                     first = last;
                 }
-                first.connect(sub.last);
-                summary
+                first.connect_epsilon(sub.last);
+                Ok(summary)
             }
             (n, Some(m)) => {
                 panic!("invalid repetition counters: {{{n},{m}}}");
@@ -265,24 +341,24 @@ impl<'a, 'g> Translator<'a, 'g> {
         concat: &ConcatHir,
         sub: Pair<'a>,
         tag: &mut Option<Tag>,
-    ) -> Summary {
+    ) -> Result<Summary> {
         let items = concat.items();
         if items.is_empty() {
-            sub.first.connect(sub.last);
-            return Summary::empty();
+            sub.first.connect_epsilon(sub.last);
+            return Ok(Summary::empty());
         }
         let mut summary = Summary::empty();
         let mut first = sub.first;
         for hir in &items[..items.len() - 1] {
             let last = self.graph.node();
-            let sum = self.translate_hir(hir, pair(first, last), tag);
+            let sum = self.translate_hir(hir, pair(first, last), tag)?;
             summary.merge(&sum);
             first = last;
         }
         let hir = items.last().unwrap();
-        let sum = self.translate_hir(hir, pair(first, sub.last), tag);
+        let sum = self.translate_hir(hir, pair(first, sub.last), tag)?;
         summary.merge(&sum);
-        summary
+        Ok(summary)
     }
 
     /// ```txt
@@ -297,17 +373,40 @@ impl<'a, 'g> Translator<'a, 'g> {
         disjunct: &DisjunctHir,
         sub: Pair<'a>,
         tag: &mut Option<Tag>,
-    ) -> Summary {
+    ) -> Result<Summary> {
+        let alternatives = disjunct.alternatives();
+        let prioritize = std::mem::take(&mut self.prioritize_disjunct);
+        let exact_len = disjunct.exact_len();
+        if !prioritize && alternatives.len() > 1 && alternatives.iter().all(Hir::is_literal) {
+            if let Some(len) = exact_len
+                && let Some(tag) = tag
+            {
+                self.translate_literal_trie(alternatives, sub);
+                tag.add_offset(len);
+            } else if tag.is_some() {
+                let last = self.graph.node();
+                self.translate_literal_trie(alternatives, pair(sub.first, last));
+                let tr_out = last.connect_epsilon(sub.last);
+                *tag = Some(self.reset_tag(vec![tr_out]));
+            } else {
+                self.translate_literal_trie(alternatives, sub);
+            }
+            return Ok(Summary::empty());
+        }
+
         let mut tr_outs = Vec::new();
         let mut summaries = Vec::new();
-        for hir in disjunct.alternatives() {
+        for (priority, hir) in alternatives.iter().enumerate() {
             let first = self.graph.node();
             let last = self.graph.node();
-            sub.first.connect(first);
-            let tr_out = last.connect(sub.last);
+            sub.first.connect_epsilon(first);
+            let tr_out = last.connect_epsilon(sub.last);
+            if prioritize {
+                tr_out.merge_instruct(Inst::Priority(priority as u32), None);
+            }
             tr_outs.push(tr_out);
             let mut tag = tag.map(|t| self.tag_bank.pseudo_absolute(t));
-            let sum = self.translate_hir(hir, pair(first, last), &mut tag);
+            let sum = self.translate_hir(hir, pair(first, last), &mut tag)?;
             summaries.push(sum);
         }
         let mut summary = Summary::empty();
@@ -324,14 +423,97 @@ impl<'a, 'g> Translator<'a, 'g> {
                 }
             }
         }
-        if let Some(len) = disjunct.exact_len()
+        if let Some(len) = exact_len
             && let Some(tag) = tag
         {
             tag.add_offset(len);
-        } else {
-            *tag = None;
+        } else if tag.is_some() {
+            *tag = Some(self.reset_tag(tr_outs));
+        }
+        Ok(summary)
+    }
+
+    /// The alternatives of different lengths break the chain of the relative
+    /// tags, so the position at the exit of the disjunction is kept in a fresh
+    /// absolute tag, and the returned tag is relative to it.
+    ///
+    /// The absolute tag is pending until a group refers to it, so
+    /// disjunctions that aren't followed by any group cost no registers.
+    fn reset_tag(&mut self, tr_outs: Vec<Transition<'a>>) -> Tag {
+        let exit_tag = self.tag_bank.absolute();
+        self.pending_tags.insert(exit_tag.id(), (exit_tag, tr_outs));
+        self.tag_bank.relative(exit_tag, 0)
+    }
+
+    /// Writes the pending exit tag of a disjunction that `tag` starts from
+    /// into the exit transitions of the disjunction.
+    fn resolve_pending_tag(&mut self, tag: Tag, summary: &mut Summary) {
+        let starting_tag = match tag {
+            Tag::Absolute { .. } => return,
+            Tag::PseudoAbsolute { starting_tag, .. } | Tag::Relative { starting_tag, .. } => {
+                starting_tag
+            }
+        };
+        let Some((exit_tag, tr_outs)) = self.pending_tags.remove(&starting_tag) else {
+            return;
+        };
+        let Tag::Absolute { id, reg } = exit_tag else {
+            unreachable!("exit tag of a disjunction must be absolute");
+        };
+        for tr_out in tr_outs {
+            tr_out.merge_instruct(Inst::WritePos(id, reg), None);
+        }
+        summary.absolute_tags.insert(id);
+        self.graph.add_tag(exit_tag);
+    }
+
+    /// Translates alternatives consisting only of literals into a trie, i.e.
+    /// literals with common prefixes share the nodes of these prefixes.
+    fn translate_literal_trie(&self, literals: &[Hir], sub: Pair<'a>) {
+        let trie = build_trie(literals.iter().map(|hir| {
+            let Hir::Literal(literal) = hir else {
+                unreachable!("only literals can be translated into a trie");
+            };
+            literal.as_slice()
+        }));
+
+        // a literal ending in a trie node is represented by a transition to
+        // the last node in parallel with the one to the trie node, because
+        // an Epsilon transition to the last node would be merged with
+        // transitions of the leaves
+        let mut nodes = vec![sub.first; trie.len()];
+        let (root_targets, root_is_end) = &trie[0];
+        if *root_is_end {
+            sub.first.connect_epsilon(sub.last);
+            if !root_targets.is_empty() {
+                nodes[0] = self.graph.node();
+                sub.first.connect_epsilon(nodes[0]);
+            }
+        }
+        for (index, (targets, _)) in trie.iter().enumerate() {
+            let node = nodes[index];
+            for (byte, target_index) in targets {
+                let (target_targets, target_is_end) = &trie[*target_index];
+                if !target_targets.is_empty() {
+                    nodes[*target_index] = self.graph.node();
+                    node.connect(nodes[*target_index]).merge(*byte);
+                }
+                if *target_is_end {
+                    node.connect(sub.last).merge(*byte);
+                }
+            }
+        }
+    }
+
+    /// Checks that the nodes added to the graph don't exceed the limit.
+    fn check_expansion(&self) -> Result<()> {
+        let node_count = self.graph.arena().node_count() - self.first_node_count;
+        if node_count > self.max_expansion {
+            return Err(Error::ExpansionLimit {
+                max: self.max_expansion,
+            });
         }
-        summary
+        Ok(())
     }
 
     pub fn next_reg(&self) -> u32 {
@@ -79,7 +79,7 @@ fn translate_repeat() {
             unreachable!()
         };
         let mut tag = None;
-        translator.translate_repeat(repeat, pair, &mut tag);
+        translator.translate_repeat(repeat, pair, &mut tag).unwrap();
         graph.to_string()
     }
 
@@ -226,7 +226,7 @@ fn translate_repeat_fails() {
         unreachable!()
     };
     let mut tag = None;
-    translator.translate_repeat(&repeat, sub, &mut tag);
+    translator.translate_repeat(&repeat, sub, &mut tag).unwrap();
 }
 
 #[test]
@@ -260,7 +260,7 @@ fn translate_concat() {
         unreachable!()
     };
     let mut tag = None;
-    translator.translate_concat(&concat, sub, &mut tag);
+    translator.translate_concat(&concat, sub, &mut tag).unwrap();
     assert_eq!(
         graph.to_string(),
         lit!(
@@ -280,7 +280,11 @@ fn translate_concat() {
 
 #[test]
 fn translate_disjunct() {
-    let disjunct = Hir::disjunct([Hir::literal("a"), Hir::literal("b"), Hir::literal("c")]);
+    let disjunct = Hir::disjunct([
+        Hir::literal("a"),
+        Hir::class(SetU8::from(Range::from(b'b'))),
+        Hir::literal("c"),
+    ]);
     let mut arena = Arena::new();
     let graph = Graph::new_in(&mut arena);
     let mut translator = Translator::new(&graph);
@@ -289,7 +293,9 @@ fn translate_disjunct() {
         unreachable!()
     };
     let mut tag = None;
-    translator.translate_disjunct(&disjunct, sub, &mut tag);
+    translator
+        .translate_disjunct(&disjunct, sub, &mut tag)
+        .unwrap();
     assert_eq!(
         graph.to_string(),
         lit!(
@@ -321,6 +327,80 @@ fn translate_disjunct() {
     );
 }
 
+#[test]
+fn translate_disjunct_literals() {
+    let disjunct = Hir::disjunct([
+        Hir::literal("cat"),
+        Hir::literal("car"),
+        Hir::literal("card"),
+        Hir::literal("do"),
+    ]);
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let mut translator = Translator::new(&graph);
+    let sub = pair(graph.node(), graph.node());
+    let Hir::Disjunct(disjunct) = disjunct else {
+        unreachable!()
+    };
+    let mut tag = None;
+    translator
+        .translate_disjunct(&disjunct, sub, &mut tag)
+        .unwrap();
+    assert_eq!(
+        graph.to_string(),
+        lit!(
+            ///node(0) {
+            ///    ['c'] -> node(2)
+            ///    ['d'] -> node(3)
+            ///}
+            ///node(1) {}
+            ///node(2) {
+            ///    ['a'] -> node(4)
+            ///}
+            ///node(3) {
+            ///    ['o'] -> node(1)
+            ///}
+            ///node(4) {
+            ///    ['r' | 't'] -> node(1)
+            ///    ['r'] -> node(5)
+            ///}
+            ///node(5) {
+            ///    ['d'] -> node(1)
+            ///}
+        )
+    );
+
+    let disjunct = Hir::disjunct([Hir::empty(), Hir::literal("a"), Hir::literal("ab")]);
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let mut translator = Translator::new(&graph);
+    let sub = pair(graph.node(), graph.node());
+    let Hir::Disjunct(disjunct) = disjunct else {
+        unreachable!()
+    };
+    let mut tag = None;
+    translator
+        .translate_disjunct(&disjunct, sub, &mut tag)
+        .unwrap();
+    assert_eq!(
+        graph.to_string(),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(1)
+            ///    [Epsilon] -> node(2)
+            ///}
+            ///node(1) {}
+            ///node(2) {
+            ///    ['a'] -> node(1)
+            ///    ['a'] -> node(3)
+            ///}
+            ///node(3) {
+            ///    ['b'] -> node(1)
+            ///}
+        )
+    );
+}
+
 #[test]
 fn translate_group() {
     let group = Hir::group(1, Hir::empty());
@@ -332,7 +412,7 @@ fn translate_group() {
         unreachable!()
     };
     let mut tag = None;
-    translator.translate_group(&group, sub, &mut tag);
+    translator.translate_group(&group, sub, &mut tag).unwrap();
     assert_eq!(
         graph.to_string(),
         lit!(
@@ -362,7 +442,7 @@ fn translate_group() {
         unreachable!()
     };
     let mut tag = None;
-    translator.translate_group(&group, sub, &mut tag);
+    translator.translate_group(&group, sub, &mut tag).unwrap();
     assert_eq!(
         graph.to_string(),
         lit!(
@@ -394,7 +474,7 @@ fn translate_group() {
     let mut tag_bank = TagBank::new();
     let abs = tag_bank.absolute();
     let mut tag = Some(tag_bank.relative(abs, 0));
-    translator.translate_group(&group, sub, &mut tag);
+    translator.translate_group(&group, sub, &mut tag).unwrap();
     assert_eq!(
         graph.to_string(),
         lit!(
@@ -1,7 +1,7 @@
 use redt::range;
 use regr::{
     Arena, Graph,
-    algo::{self, VisitResult::*},
+    algo::{self, DfaViolation, VisitResult::*},
 };
 
 #[test]
@@ -13,13 +13,53 @@ fn verify_dfa() {
     let c = nfa.node();
     let d = nfa.node();
     a.connect(a).merge(range(1, 255));
-    a.connect(b);
+    a.connect_epsilon(b);
     b.connect(c).merge(b'a');
     c.connect(d).merge(b'b');
-    assert!(algo::verify_dfa(&nfa));
+    assert_eq!(algo::verify_dfa(&nfa), Ok(()));
+    assert!(algo::is_dfa(&nfa));
 
     a.connect(b).merge(b'a');
-    assert!(!algo::verify_dfa(&nfa));
+    assert!(!algo::is_dfa(&nfa));
+    assert_eq!(
+        algo::verify_dfa(&nfa),
+        Err(DfaViolation {
+            node: a.uid(),
+            symbol: Some(b'a'),
+            targets: vec![a.uid(), b.uid()],
+        })
+    );
+}
+
+#[test]
+fn verify_dfa_epsilon() {
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let a = nfa.node();
+    let b = nfa.node();
+    let c = nfa.node();
+    a.connect_epsilon(b);
+    a.connect_epsilon(c);
+    b.connect(c).merge(b'b');
+
+    let violation = algo::verify_dfa(&nfa).unwrap_err();
+    assert_eq!(
+        violation.to_string(),
+        format!(
+            "node {} has several targets for Epsilon: {} {}",
+            a.uid(),
+            b.uid(),
+            c.uid()
+        )
+    );
+    assert_eq!(
+        violation,
+        DfaViolation {
+            node: a.uid(),
+            symbol: None,
+            targets: vec![b.uid(), c.uid()],
+        }
+    );
 }
 
 #[test]
@@ -32,11 +72,11 @@ fn visit_nodes() {
     let d = gr.node();
     let e = gr.node();
 
-    a.connect(b);
-    b.connect(c);
-    c.connect(a);
-    a.connect(d);
-    e.connect(a);
+    a.connect_epsilon(b);
+    b.connect_epsilon(c);
+    c.connect_epsilon(a);
+    a.connect_epsilon(d);
+    e.connect_epsilon(a);
 
     let mut vec = Vec::new();
     algo::visit_nodes(a, |node| {
@@ -67,14 +107,14 @@ fn visit_nodes_in_tree() {
     let f = gr.node();
     let g = gr.node();
 
-    a.connect(b);
-    a.connect(c);
+    a.connect_epsilon(b);
+    a.connect_epsilon(c);
 
-    b.connect(d);
-    b.connect(e);
+    b.connect_epsilon(d);
+    b.connect_epsilon(e);
 
-    e.connect(f);
-    e.connect(g);
+    e.connect_epsilon(f);
+    e.connect_epsilon(g);
 
     let mut vec = Vec::new();
     algo::visit_nodes(a, |node| {
@@ -103,11 +143,11 @@ fn for_each_transition() {
     let d = gr.node();
     let e = gr.node();
 
-    a.connect(b);
-    b.connect(c);
-    c.connect(a);
-    a.connect(d);
-    e.connect(a);
+    a.connect_epsilon(b);
+    b.connect_epsilon(c);
+    c.connect_epsilon(a);
+    a.connect_epsilon(d);
+    e.connect_epsilon(a);
 
     let mut vec = Vec::new();
     algo::visit_transitions(a, |source, _transition, target| {
@@ -138,14 +178,14 @@ fn for_each_transition_in_tree() {
     let f = gr.node();
     let g = gr.node();
 
-    a.connect(b);
-    a.connect(c);
+    a.connect_epsilon(b);
+    a.connect_epsilon(c);
 
-    b.connect(d);
-    b.connect(e);
+    b.connect_epsilon(d);
+    b.connect_epsilon(e);
 
-    e.connect(f);
-    e.connect(g);
+    e.connect_epsilon(f);
+    e.connect_epsilon(g);
 
     let mut vec = Vec::new();
     algo::visit_transitions(a, |source, _, target| {
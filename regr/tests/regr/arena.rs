@@ -52,3 +52,69 @@ fn arena_alloc_during_iteration() {
         arena.nodes().map(|node| node.nid()).collect::<Vec<_>>()
     );
 }
+
+#[test]
+fn arena_reset() {
+    let mut arena = Arena::new();
+    assert_eq!(arena.node_count(), 0);
+
+    let gr = Graph::new_in(&mut arena);
+    for _ in 0..100 {
+        gr.node().connect(gr.node()).merge(b'a');
+    }
+    drop(gr);
+    assert_eq!(arena.node_count(), 200);
+    let allocated_bytes = arena.allocated_bytes();
+    assert!(allocated_bytes > 0);
+
+    arena.reset();
+    assert_eq!(arena.node_count(), 0);
+    assert_eq!(arena.nodes().len(), 0);
+    assert!(arena.allocated_bytes() <= allocated_bytes);
+
+    // the nodes of the previous graph are gone
+    let gr = Graph::new_in(&mut arena);
+    let node = gr.node();
+    assert_eq!(node.nid(), 0);
+    assert_eq!(gr.arena().node_count(), 1);
+    assert_eq!(
+        gr.arena()
+            .nodes()
+            .map(|node| node.uid())
+            .collect::<Vec<_>>(),
+        [node.uid()]
+    );
+    drop(gr);
+
+    // resetting an empty arena is fine
+    arena.reset();
+    arena.reset();
+    assert_eq!(arena.node_count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "can't reset the arena bound to a graph")]
+fn arena_reset_bound() {
+    let mut arena = Arena::new();
+    let gr = Graph::new_in(&mut arena);
+    std::mem::forget(gr);
+    arena.reset();
+}
+
+#[test]
+fn arena_intern() {
+    let mut arena = Arena::new();
+    let name = String::from("name");
+    let first = arena.intern(&name);
+    let second = arena.intern("name");
+    assert_eq!(first, "name");
+    assert!(std::ptr::eq(first, second));
+
+    let other = arena.intern("other");
+    assert_eq!(other, "other");
+    assert!(!std::ptr::eq(first, other));
+    assert!(std::ptr::eq(arena.intern(""), arena.intern("")));
+
+    arena.reset();
+    assert_eq!(arena.intern("name"), "name");
+}
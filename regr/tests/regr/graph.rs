@@ -1,7 +1,25 @@
-use pretty_assertions::assert_eq;
+use pretty_assertions::{assert_eq, assert_ne};
 use redt::lit;
-use redt::{RangeU8, range};
-use regr::{Arena, Graph, Tag, TagBank};
+use redt::{RangeU8, SetU8, range};
+use regr::ops::Rejectable;
+use regr::{Arena, DfaAnalysis, Graph, GraphStats, Inst, Node, Tag, TagBank, Translator};
+use resy::{GroupLabel, Parser, enc::Utf8Encoder};
+
+fn run_dfa(start_node: Node<'_>, input: &[u8]) -> bool {
+    let mut node = start_node;
+    for byte in input {
+        let target = node
+            .targets()
+            .iter()
+            .find(|(_, tr)| tr.contains(*byte))
+            .map(|(target, _)| *target);
+        match target {
+            Some(target) => node = target,
+            None => return false,
+        }
+    }
+    node.is_final()
+}
 
 #[test]
 fn graph_node() {
@@ -49,7 +67,7 @@ fn graph_determine_0() {
     let mut arena = Arena::new();
     let nfa = Graph::new_in(&mut arena);
     let a = nfa.node();
-    a.connect(a);
+    a.connect_epsilon(a);
     assert_eq!(
         nfa.to_string(),
         lit!(
@@ -73,7 +91,7 @@ fn graph_determine_1() {
     let c = nfa.node();
     let d = nfa.node();
     a.connect(a).merge(range(1, 255));
-    a.connect(b);
+    a.connect_epsilon(b);
     b.connect(c).merge(b'a');
     c.connect(d).merge(b'b');
     assert_eq!(
@@ -123,11 +141,11 @@ fn graph_determine_klenee_star() {
     let b = nfa.node();
     let c = nfa.node();
     let d = nfa.node();
-    a.connect(b);
-    a.connect(d);
+    a.connect_epsilon(b);
+    a.connect_epsilon(d);
     b.connect(c).merge(b'a');
-    c.connect(b);
-    c.connect(d);
+    c.connect_epsilon(b);
+    c.connect_epsilon(d);
     assert_eq!(
         nfa.to_string(),
         lit!(
@@ -187,6 +205,70 @@ fn graph_for_each_node() {
     assert_eq!(visited.len(), 4);
 }
 
+#[test]
+fn graph_nodes_in_bfs_order() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    assert!(graph.nodes_in_bfs_order().is_empty());
+
+    let a = graph.node();
+    let b = graph.node();
+    let c = graph.node();
+    let d = graph.node();
+    let e = graph.node();
+    let unreachable = graph.node();
+    // connected in the reversed order of the UIDs
+    a.connect(d).merge(b'd');
+    a.connect(b).merge(b'b');
+    d.connect(e).merge(b'e');
+    b.connect(c).merge(b'c');
+    b.connect(a).merge(b'a');
+    e.connect(d).merge(b'd');
+    unreachable.connect(a).merge(b'a');
+
+    assert_eq!(graph.nodes_in_bfs_order(), [a, b, d, c, e]);
+    // the order is the same on every call
+    assert_eq!(graph.nodes_in_bfs_order(), graph.nodes_in_bfs_order());
+
+    // the same graph built with another connection order
+    let mut other_arena = Arena::new();
+    let other = Graph::new_in(&mut other_arena);
+    let nodes: Vec<_> = (0..5).map(|_| other.node()).collect();
+    nodes[1].connect(nodes[0]).merge(b'a');
+    nodes[1].connect(nodes[2]).merge(b'c');
+    nodes[0].connect(nodes[1]).merge(b'b');
+    nodes[0].connect(nodes[3]).merge(b'd');
+    nodes[4].connect(nodes[3]).merge(b'd');
+    nodes[3].connect(nodes[4]).merge(b'e');
+    let nids = |graph: &Graph<'_>| {
+        graph
+            .nodes_in_bfs_order()
+            .iter()
+            .map(|node| node.nid())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(nids(&other), nids(&graph));
+    assert_eq!(nids(&graph), [0, 1, 3, 2, 4]);
+}
+
+#[test]
+fn graph_node_by_uid() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let a = graph.node();
+    let b = graph.node();
+    assert_eq!(graph.node_by_uid(a.uid()), Some(a));
+    assert_eq!(graph.node_by_uid(b.uid()), Some(b));
+    assert_eq!(graph.node_by_uid(b.uid() + 1), None);
+
+    let mut other_arena = Arena::new();
+    let other = Graph::new_in(&mut other_arena);
+    let other_a = other.node();
+    assert_eq!(other.node_by_uid(other_a.uid()), Some(other_a));
+    assert_eq!(other.node_by_uid(a.uid()), None);
+    assert_eq!(graph.node_by_uid(other_a.uid()), None);
+}
+
 #[test]
 fn graph_display_fmt_0() {
     let mut arena = Arena::with_capacity(1);
@@ -237,11 +319,11 @@ fn graph_display_fmt_1() {
     let n4 = graph.node();
     n0.connect(n1).merge(RangeU8::from(b'a'..=b'b'));
     n0.connect(n1).merge(RangeU8::from(b'd'..=b'z'));
-    n1.connect(n2);
-    n1.connect(n4);
+    n1.connect_epsilon(n2);
+    n1.connect_epsilon(n4);
     n2.connect(n3).merge(b'a');
-    n3.connect(n4);
-    n3.connect(n2);
+    n3.connect_epsilon(n4);
+    n3.connect_epsilon(n2);
     assert_eq!(
         graph.to_string(),
         lit!(
@@ -276,14 +358,14 @@ fn graph_display_fmt_2() {
     let n5 = graph.node();
     let n6 = graph.node();
     let n7 = graph.node();
-    n0.connect(n2);
-    n0.connect(n5);
+    n0.connect_epsilon(n2);
+    n0.connect_epsilon(n5);
     n2.connect(n3).merge(b'a');
     n3.connect(n4).merge(b'b');
-    n4.connect(n1);
+    n4.connect_epsilon(n1);
     n5.connect(n6).merge(b'c');
     n6.connect(n7).merge(b'd');
-    n7.connect(n1);
+    n7.connect_epsilon(n1);
     assert_eq!(
         graph.to_string(),
         lit!(
@@ -339,6 +421,35 @@ fn graph_display_fmt_3() {
     );
 }
 
+#[test]
+fn graph_display_fmt_insertion_order() {
+    let mut arena_0 = Arena::new();
+    let graph_0 = Graph::new_in(&mut arena_0);
+    let a = graph_0.node();
+    let b = graph_0.node();
+    let c = graph_0.node();
+    let d = graph_0.node();
+    a.connect(b).merge(1);
+    a.connect(c).merge(2);
+    a.connect(d).merge(3);
+    c.connect(a).merge(4);
+    c.connect(d).merge(5);
+
+    let mut arena_1 = Arena::new();
+    let graph_1 = Graph::new_in(&mut arena_1);
+    let a = graph_1.node();
+    let b = graph_1.node();
+    let c = graph_1.node();
+    let d = graph_1.node();
+    c.connect(d).merge(5);
+    a.connect(d).merge(3);
+    c.connect(a).merge(4);
+    a.connect(c).merge(2);
+    a.connect(b).merge(1);
+
+    assert_eq!(graph_0.to_string(), graph_1.to_string());
+}
+
 #[test]
 fn graph_tags() {
     let mut arena = Arena::new();
@@ -346,6 +457,7 @@ fn graph_tags() {
     let mut tag_bank = TagBank::default();
     graph.add_tag_group(0, tag_bank.absolute(), tag_bank.absolute());
     graph.add_tag_group(1, tag_bank.absolute(), tag_bank.absolute());
+    graph.add_tag_group("year", tag_bank.absolute(), tag_bank.absolute());
 
     let mut tag_bank = TagBank::default();
     assert_eq!(
@@ -353,25 +465,1321 @@ fn graph_tags() {
         Some((tag_bank.absolute(), tag_bank.absolute()))
     );
     assert_eq!(graph.tag_group(2), None);
+    assert_eq!(
+        graph.tag_group("year"),
+        Some((
+            Tag::Absolute { id: 4, reg: 4 },
+            Tag::Absolute { id: 5, reg: 5 }
+        ))
+    );
+    assert_eq!(graph.tag_group("month"), None);
     let mut tag_groups = graph.tag_groups().collect::<Vec<_>>();
-    tag_groups.sort_by_key(|(id, _)| *id);
+    tag_groups.sort_by(|(a, _), (b, _)| a.cmp(b));
     assert_eq!(
         tag_groups,
         [
             (
-                0,
+                GroupLabel::Index(0),
                 (
                     Tag::Absolute { id: 0, reg: 0 },
                     Tag::Absolute { id: 1, reg: 1 },
                 ),
             ),
             (
-                1,
+                GroupLabel::Index(1),
                 (
                     Tag::Absolute { id: 2, reg: 2 },
                     Tag::Absolute { id: 3, reg: 3 },
                 ),
             ),
+            (
+                GroupLabel::from("year"),
+                (
+                    Tag::Absolute { id: 4, reg: 4 },
+                    Tag::Absolute { id: 5, reg: 5 },
+                ),
+            ),
         ]
     );
 }
+
+#[test]
+fn graph_complement() {
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("abc").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    assert!(run_dfa(dfa.start_node(), b"abc"));
+    assert!(!run_dfa(dfa.start_node(), b"ab"));
+
+    let mut compl_arena = Arena::new();
+    let compl = dfa.complement_in(&mut compl_arena);
+    assert!(!run_dfa(compl.start_node(), b"abc"));
+    assert!(run_dfa(compl.start_node(), b"ab"));
+    assert!(run_dfa(compl.start_node(), b"abcd"));
+    assert!(run_dfa(compl.start_node(), b""));
+    assert!(run_dfa(compl.start_node(), b"\xFF\x00abc"));
+    assert_eq!(
+        compl.to_string(),
+        lit!(
+            ///node((0)) {
+            ///    ['a'] -> node((1))
+            ///    [00h-'`' | 'b'-FFh] -> node((4))
+            ///}
+            ///node((1)) {
+            ///    ['b'] -> node((2))
+            ///    [00h-'a' | 'c'-FFh] -> node((4))
+            ///}
+            ///node((2)) {
+            ///    ['c'] -> node(3)
+            ///    [00h-'b' | 'd'-FFh] -> node((4))
+            ///}
+            ///node(3) {
+            ///    [00h-FFh] -> node((4))
+            ///}
+            ///node((4)) {
+            ///    [00h-FFh] -> self
+            ///}
+        )
+    );
+}
+
+#[test]
+fn graph_complement_with_anchors() {
+    let inputs: [&[u8]; 9] = [
+        b"", b"a", b"b", b"ab", b"ba", b"aab", b"abb", b"xab", b"abx",
+    ];
+    for pattern in ["^ab", "ab$", "^a$|b", "(^|x)ab($|x)"] {
+        let mut nfa_arena = Arena::new();
+        let nfa = Graph::new_in(&mut nfa_arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+
+        let mut dfa_arena = Arena::new();
+        let dfa = nfa.determinize_in(&mut dfa_arena);
+        let mut compl_arena = Arena::new();
+        let compl = dfa.complement_in(&mut compl_arena);
+        for input in inputs {
+            assert_eq!(
+                compl.accepts(input),
+                !dfa.accepts(input),
+                "pattern {pattern:?}, input {:?}",
+                input.escape_ascii().to_string()
+            );
+        }
+    }
+}
+
+#[test]
+fn graph_reverse() {
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("ab").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+
+    let mut rev_arena = Arena::new();
+    let rev = nfa.reverse_in(&mut rev_arena);
+    assert_eq!(
+        rev.to_string(),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(3)
+            ///}
+            ///node((1)) {}
+            ///node(2) {
+            ///    ['a'] -> node((1))
+            ///}
+            ///node(3) {
+            ///    ['b'] -> node(2)
+            ///}
+        )
+    );
+
+    let mut dfa_arena = Arena::new();
+    let dfa = rev.determinize_in(&mut dfa_arena);
+    assert!(run_dfa(dfa.start_node(), b"ba"));
+    assert!(!run_dfa(dfa.start_node(), b"ab"));
+    assert!(!run_dfa(dfa.start_node(), b"b"));
+    assert!(!run_dfa(dfa.start_node(), b""));
+
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("a[bc]*|xy").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+    let mut rev_arena = Arena::new();
+    let rev = nfa.reverse_in(&mut rev_arena);
+    let mut dfa_arena = Arena::new();
+    let dfa = rev.determinize_in(&mut dfa_arena);
+    for input in [&b"a"[..], b"cba", b"bbcca", b"yx"] {
+        assert!(run_dfa(dfa.start_node(), input), "{input:?}");
+    }
+    for input in [&b"ab"[..], b"xy", b"ya", b"cb"] {
+        assert!(!run_dfa(dfa.start_node(), input), "{input:?}");
+    }
+}
+
+#[test]
+fn graph_product_union() {
+    fn build_dfa<'d>(pattern: &str, arena: &'d mut Arena) -> Graph<'d> {
+        let mut nfa_arena = Arena::new();
+        let nfa = Graph::new_in(&mut nfa_arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        nfa.determinize_in(arena)
+    }
+
+    let mut abc_arena = Arena::new();
+    let abc = build_dfa("abc", &mut abc_arena);
+    let mut xyz_arena = Arena::new();
+    let xyz = build_dfa("xyz|ab", &mut xyz_arena);
+
+    let mut union_arena = Arena::new();
+    let union = abc.product_union_in(&xyz, &mut union_arena);
+    assert!(regr::algo::is_dfa(&union));
+    assert!(run_dfa(union.start_node(), b"abc"));
+    assert!(run_dfa(union.start_node(), b"xyz"));
+    assert!(run_dfa(union.start_node(), b"ab"));
+    assert!(!run_dfa(union.start_node(), b"aby"));
+    assert!(!run_dfa(union.start_node(), b"a"));
+    assert!(!run_dfa(union.start_node(), b"abcz"));
+    assert!(!run_dfa(union.start_node(), b""));
+    assert_eq!(
+        union.to_string(),
+        lit!(
+            ///node(0) {
+            ///    ['a'] -> node(1)
+            ///    ['x'] -> node(2)
+            ///}
+            ///node(1) {
+            ///    ['b'] -> node((3))
+            ///}
+            ///node(2) {
+            ///    ['y'] -> node(4)
+            ///}
+            ///node((3)) {
+            ///    ['c'] -> node((5))
+            ///}
+            ///node(4) {
+            ///    ['z'] -> node((6))
+            ///}
+            ///node((5)) {}
+            ///node((6)) {}
+        )
+    );
+}
+
+#[test]
+fn graph_product_union_anchored() {
+    fn build_dfa<'d>(pattern: &str, arena: &'d mut Arena) -> Graph<'d> {
+        let mut nfa_arena = Arena::new();
+        let nfa = Graph::new_in(&mut nfa_arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        nfa.determinize_with_tags_in(arena)
+    }
+
+    let mut start_arena = Arena::new();
+    let start = build_dfa("^a(?<1>b)", &mut start_arena);
+    let mut end_arena = Arena::new();
+    let end = build_dfa("c$", &mut end_arena);
+
+    let mut union_arena = Arena::new();
+    let union = start.product_union_in(&end, &mut union_arena);
+    assert!(regr::algo::is_dfa(&union));
+    assert!(union.accepts(b"ab"));
+    assert!(union.accepts(b"c"));
+    assert!(!union.accepts(b"a"));
+    assert!(!union.accepts(b""));
+    assert_eq!(union.tag_groups().count(), 1);
+    assert_eq!(
+        union.to_string(),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(1)
+            ///        asrt start
+            ///    ['c'] -> node(2)
+            ///}
+            ///node(1) {
+            ///    ['c'] -> node(2)
+            ///    ['a'] -> node(3)
+            ///}
+            ///node(2) {
+            ///    [Epsilon] -> node((4))
+            ///        asrt end
+            ///}
+            ///node(3) {
+            ///    ['b'] -> node((5))
+            ///        wrpos t0/r0
+            ///}
+            ///node((4)) {}
+            ///node((5)) {}
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "product union can be built only for DFAs")]
+fn graph_product_union_of_nfa() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let a = graph.node();
+    let b = graph.node().finalize();
+    let c = graph.node().finalize();
+    a.connect(b).merge(b'a');
+    a.connect(c).merge(b'a');
+
+    let mut other_arena = Arena::new();
+    let other = Graph::new_in(&mut other_arena);
+    other.node().finalize();
+
+    let mut union_arena = Arena::new();
+    let _ = graph.product_union_in(&other, &mut union_arena);
+}
+
+#[test]
+fn graph_union() {
+    fn build_nfa<'n>(pattern: &str, arena: &'n mut Arena) -> Graph<'n> {
+        let nfa = Graph::new_in(arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        nfa
+    }
+
+    let mut cat_arena = Arena::new();
+    let mut dog_arena = Arena::new();
+    let cat = build_nfa("cat", &mut cat_arena);
+    let dog = build_nfa("dog", &mut dog_arena);
+
+    let mut union_arena = Arena::new();
+    let union = Graph::union_in(&[&cat, &dog], &mut union_arena);
+    assert_eq!(
+        union.to_string(),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(1)
+            ///    [Epsilon] -> node(5)
+            ///}
+            ///node(1) {
+            ///    ['c'] -> node(2)
+            ///}
+            ///node(2) {
+            ///    ['a'] -> node(3)
+            ///}
+            ///node(3) {
+            ///    ['t'] -> node((4))
+            ///}
+            ///node((4)) {}
+            ///node(5) {
+            ///    ['d'] -> node(6)
+            ///}
+            ///node(6) {
+            ///    ['o'] -> node(7)
+            ///}
+            ///node(7) {
+            ///    ['g'] -> node((8))
+            ///}
+            ///node((8)) {}
+        )
+    );
+
+    let mut dfa_arena = Arena::new();
+    let dfa = union.determinize_in(&mut dfa_arena);
+    assert!(regr::algo::is_dfa(&dfa));
+    assert!(run_dfa(dfa.start_node(), b"cat"));
+    assert!(run_dfa(dfa.start_node(), b"dog"));
+    assert!(!run_dfa(dfa.start_node(), b"cog"));
+    assert!(!run_dfa(dfa.start_node(), b"ca"));
+    assert!(!run_dfa(dfa.start_node(), b""));
+}
+
+#[test]
+fn graph_union_patterns() {
+    fn build_nfa<'n>(pattern: &str, arena: &'n mut Arena) -> Graph<'n> {
+        let nfa = Graph::new_in(arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        nfa
+    }
+
+    /// Walks the DFA and returns the lowest pattern accepted at the end.
+    fn accepted_pattern(dfa: &Graph<'_>, input: &[u8]) -> Option<u32> {
+        let mut node = dfa.start_node();
+        for byte in input {
+            let target = node
+                .targets()
+                .iter()
+                .find(|(_, tr)| tr.contains(*byte))
+                .map(|(target, _)| *target);
+            node = target?;
+        }
+        let targets = node.targets();
+        let (_, tr) = targets
+            .iter()
+            .find(|(target, tr)| tr.is_epsilon() && target.is_final())?;
+        tr.instructs()
+            .filter_map(|inst| match inst {
+                Inst::Accept(pattern) => Some(pattern),
+                _ => None,
+            })
+            .min()
+    }
+
+    let mut keyword_arena = Arena::new();
+    let mut ident_arena = Arena::new();
+    let keyword = build_nfa("if", &mut keyword_arena);
+    let ident = build_nfa("[a-z]+", &mut ident_arena);
+
+    let mut union_arena = Arena::new();
+    let union = Graph::union_patterns_in(&[&keyword, &ident], &mut union_arena);
+    assert_eq!(
+        union.to_string(),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(2)
+            ///    [Epsilon] -> node(5)
+            ///}
+            ///node((1)) {}
+            ///node(2) {
+            ///    ['i'] -> node(3)
+            ///}
+            ///node(3) {
+            ///    ['f'] -> node(4)
+            ///}
+            ///node(4) {
+            ///    [Epsilon] -> node((1))
+            ///        accept p0
+            ///}
+            ///node(5) {
+            ///    [Epsilon] -> node(6)
+            ///}
+            ///node(6) {
+            ///    ['a'-'z'] -> node(7)
+            ///}
+            ///node(7) {
+            ///    [Epsilon] -> node(6)
+            ///    [Epsilon] -> node(8)
+            ///}
+            ///node(8) {
+            ///    [Epsilon] -> node((1))
+            ///        accept p1
+            ///}
+        )
+    );
+
+    let mut dfa_arena = Arena::new();
+    let dfa = union.determinize_with_tags_in(&mut dfa_arena);
+    assert_eq!(accepted_pattern(&dfa, b"if"), Some(0));
+    assert_eq!(accepted_pattern(&dfa, b"ifx"), Some(1));
+    assert_eq!(accepted_pattern(&dfa, b"i"), Some(1));
+    assert_eq!(accepted_pattern(&dfa, b"x"), Some(1));
+    assert_eq!(accepted_pattern(&dfa, b""), None);
+    assert_eq!(accepted_pattern(&dfa, b"if1"), None);
+}
+
+#[test]
+fn graph_from_literals_in() {
+    let keywords = [
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+    ];
+    let pattern = keywords.join("|");
+
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse(&pattern).unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+    let mut generic_arena = Arena::new();
+    let generic = nfa.determinize_in(&mut generic_arena);
+
+    let mut trie_arena = Arena::new();
+    let trie = Graph::from_literals_in(&keywords, &mut trie_arena);
+    let analysis = trie.analyze();
+    assert!(analysis.is_dfa);
+    assert_eq!(analysis.state_count, generic.analyze().state_count);
+    assert_eq!(analysis.final_count, generic.analyze().final_count);
+
+    for input in keywords
+        .iter()
+        .chain(&["", "a", "asy", "asyncs", "fnn", "i", "matc", "x"])
+    {
+        assert_eq!(
+            trie.accepts(input.as_bytes()),
+            generic.accepts(input.as_bytes()),
+            "input {input:?}"
+        );
+        assert_eq!(trie.accepts(input.as_bytes()), keywords.contains(input));
+    }
+}
+
+#[test]
+fn graph_from_literals_in_edge_cases() {
+    let mut arena = Arena::new();
+    let graph = Graph::from_literals_in::<&str>(&[], &mut arena);
+    assert_eq!(graph.to_string(), "node(0) {}");
+
+    let mut arena = Arena::new();
+    let graph = Graph::from_literals_in(&["", "ab", "a"], &mut arena);
+    assert_eq!(
+        graph.to_string(),
+        lit!(
+            ///node((0)) {
+            ///    ['a'] -> node((1))
+            ///}
+            ///node((1)) {
+            ///    ['b'] -> node((2))
+            ///}
+            ///node((2)) {}
+        )
+    );
+}
+
+#[test]
+fn graph_strict_dfa() {
+    let mut arena = Arena::new();
+    let dfa = Graph::new_strict_dfa_in(&mut arena);
+    assert!(dfa.is_strict_dfa());
+    let a = dfa.node();
+    let b = dfa.node().finalize();
+    a.connect(b).merge(range(b'a', b'c'));
+    a.connect(a).merge(b'd');
+    a.connect(b).merge(b'e');
+    b.connect(a).merge(range(0, 255));
+    assert!(dfa.accepts(b"dda"));
+
+    // the strictness belongs to the graph, not to the arena
+    drop(dfa);
+    let nfa = Graph::new_in(&mut arena);
+    assert!(!nfa.is_strict_dfa());
+    nfa.node().connect_epsilon(nfa.node());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "a strict DFA can't have Epsilon transitions")]
+fn graph_strict_dfa_epsilon_panics() {
+    let mut arena = Arena::new();
+    let dfa = Graph::new_strict_dfa_in(&mut arena);
+    dfa.node().connect_epsilon(dfa.node());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "a strict DFA can't have overlapping transitions from node(0)")]
+fn graph_strict_dfa_overlap_panics() {
+    let mut arena = Arena::new();
+    let dfa = Graph::new_strict_dfa_in(&mut arena);
+    let a = dfa.node();
+    let b = dfa.node();
+    let c = dfa.node();
+    a.connect(b).merge(range(b'a', b'c'));
+    a.connect(c).merge(b'c');
+}
+
+#[test]
+fn graph_clone_in() {
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let hir = Parser::new(Utf8Encoder).parse("(?<x>a+)b").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+
+    let mut clone_arena = Arena::new();
+    let clone = nfa.clone_in(&mut clone_arena);
+    let original = nfa.to_string();
+    assert_eq!(clone.to_string(), original);
+    assert_ne!(clone.gid(), nfa.gid());
+    assert_eq!(
+        clone.tag_groups().collect::<Vec<_>>(),
+        nfa.tag_groups().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        clone.tags().collect::<Vec<_>>(),
+        nfa.tags().collect::<Vec<_>>()
+    );
+
+    // the copies don't share nodes and transitions
+    let cloned = clone.to_string();
+    nfa.start_node().connect(nfa.node()).merge(b'z');
+    assert_eq!(clone.to_string(), cloned);
+    clone
+        .start_node()
+        .targets()
+        .values()
+        .for_each(|tr| tr.merge(b'y'));
+    assert_ne!(nfa.to_string(), original);
+    assert!(!nfa.to_string().contains("'y'"));
+}
+
+#[test]
+fn graph_prune_dead() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let a = graph.node();
+    let b = graph.node().finalize();
+    let trap = graph.node();
+    a.connect(b).merge(b'a');
+    a.connect(trap).merge(b'x');
+    trap.connect(trap).merge(b'x');
+    b.connect(trap).merge(b'b');
+
+    let mut pruned_arena = Arena::new();
+    let pruned = graph.prune_dead_in(&mut pruned_arena);
+    assert_eq!(
+        pruned.to_string(),
+        lit!(
+            ///node(0) {
+            ///    ['a'] -> node((1))
+            ///}
+            ///node((1)) {}
+        )
+    );
+
+    // the trap becomes live, when it can go back to the start node
+    trap.connect(a).merge(b'y');
+    let mut pruned_arena = Arena::new();
+    let pruned = graph.prune_dead_in(&mut pruned_arena);
+    assert_eq!(
+        pruned.to_string(),
+        lit!(
+            ///node(0) {
+            ///    ['a'] -> node((1))
+            ///    ['x'] -> node(2)
+            ///}
+            ///node((1)) {
+            ///    ['b'] -> node(2)
+            ///}
+            ///node(2) {
+            ///    ['y'] -> node(0)
+            ///    ['x'] -> self
+            ///}
+        )
+    );
+
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("abc").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    let mut complement_arena = Arena::new();
+    let complement = dfa.complement_in(&mut complement_arena);
+    let mut pruned_arena = Arena::new();
+    let pruned = complement.complement_in(&mut pruned_arena);
+    let mut arena = Arena::new();
+    let pruned = pruned.prune_dead_in(&mut arena);
+    assert_eq!(pruned.to_string(), dfa.to_string());
+}
+
+#[test]
+fn graph_prune_dead_keeps_start() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let a = graph.node();
+    let b = graph.node();
+    a.connect(b).merge(b'a');
+    b.connect(a).merge(b'b');
+
+    let mut pruned_arena = Arena::new();
+    let pruned = graph.prune_dead_in(&mut pruned_arena);
+    assert_eq!(pruned.to_string(), "node(0) {}");
+}
+
+#[test]
+fn graph_dead_states() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let a = graph.node();
+    let b = graph.node().finalize();
+    let trap_0 = graph.node();
+    let trap_1 = graph.node();
+    let _unreachable = graph.node();
+    a.connect(b).merge(b'a');
+    a.connect(trap_0).merge(b'x');
+    trap_0.connect(trap_1).merge(b'x');
+    trap_1.connect(trap_0).merge(b'x');
+    b.connect(trap_1).merge(b'b');
+    assert_eq!(graph.dead_states(), vec![trap_1, trap_0]);
+
+    trap_1.connect(b).merge(b'y');
+    assert_eq!(graph.dead_states(), vec![]);
+
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let a = graph.node();
+    a.connect(a).merge(b'a');
+    assert_eq!(graph.dead_states(), vec![a]);
+}
+
+#[test]
+#[should_panic(expected = "complement can be built only for a DFA")]
+fn graph_complement_of_nfa() {
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let a = nfa.node();
+    a.connect(nfa.node()).merge(b'a');
+    a.connect(nfa.node()).merge(b'a');
+
+    let mut compl_arena = Arena::new();
+    _ = nfa.complement_in(&mut compl_arena);
+}
+
+#[test]
+fn graph_stats() {
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let a = nfa.node();
+    let b = nfa.node();
+    let c = nfa.node();
+    let d = nfa.node();
+    a.connect_epsilon(b);
+    a.connect_epsilon(d);
+    b.connect(c).merge(b'a');
+    c.connect_epsilon(b);
+    c.connect_epsilon(d);
+    d.finalize();
+    assert_eq!(
+        nfa.stats(),
+        GraphStats {
+            nodes: 4,
+            transitions: 5,
+            epsilon_transitions: 4,
+            final_nodes: 1,
+            max_out_degree: 2,
+        }
+    );
+}
+
+#[test]
+fn graph_analyze() {
+    // strings ending with 'a'
+    let mut arena = Arena::new();
+    let dfa = Graph::new_in(&mut arena);
+    let a = dfa.node();
+    let b = dfa.node().finalize();
+    a.connect(a).merge(range(0, b'a' - 1));
+    a.connect(a).merge(range(b'a' + 1, u8::MAX));
+    a.connect(b).merge(b'a');
+    b.connect(a).merge(range(0, b'a' - 1));
+    b.connect(a).merge(range(b'a' + 1, u8::MAX));
+    b.connect(b).merge(b'a');
+    assert_eq!(
+        dfa.analyze(),
+        DfaAnalysis {
+            is_dfa: true,
+            is_complete: true,
+            state_count: 2,
+            final_count: 1,
+            is_minimal: true,
+            language_empty: false,
+        }
+    );
+
+    // redundant state equivalent to `b`
+    let c = dfa.node().finalize();
+    a.connect(c).merge(b'b');
+    a.connect(a).reject(b'b');
+    c.connect(a).merge(!SetU8::from(b'a'));
+    c.connect(b).merge(b'a');
+    let analysis = dfa.analyze();
+    assert_eq!(analysis.state_count, 3);
+    assert_eq!(analysis.final_count, 2);
+    assert!(analysis.is_complete);
+    assert!(!analysis.is_minimal);
+}
+
+#[test]
+fn graph_analyze_incomplete() {
+    let mut arena = Arena::new();
+    let dfa = Graph::new_in(&mut arena);
+    let a = dfa.node();
+    let b = dfa.node().finalize();
+    a.connect(b).merge(b'a');
+    assert_eq!(
+        dfa.analyze(),
+        DfaAnalysis {
+            is_dfa: true,
+            is_complete: false,
+            state_count: 2,
+            final_count: 1,
+            is_minimal: true,
+            language_empty: false,
+        }
+    );
+
+    // dead state is redundant for incomplete DFA
+    let dead = dfa.node();
+    b.connect(dead).merge(b'b');
+    let analysis = dfa.analyze();
+    assert!(!analysis.is_minimal);
+    assert!(!analysis.is_complete);
+
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let a = nfa.node();
+    a.connect(nfa.node()).merge(b'a');
+    a.connect(nfa.node()).merge(b'a');
+    assert_eq!(
+        nfa.analyze(),
+        DfaAnalysis {
+            is_dfa: false,
+            is_complete: false,
+            state_count: 3,
+            final_count: 0,
+            is_minimal: false,
+            language_empty: true,
+        }
+    );
+}
+
+#[test]
+fn graph_determinize_long_chain() {
+    // every state of the DFA is reached from the previous one only, so a
+    // recursive descent would be as deep as the chain is long
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("a{20000}").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    assert_eq!(dfa.stats().nodes, 20001);
+    assert!(dfa.accepts(&[b'a'; 20000]));
+    assert!(!dfa.accepts(&[b'a'; 19999]));
+    assert!(!dfa.has_cycle());
+}
+
+#[test]
+fn graph_determinize_anchors() {
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("^a|b$").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    assert!(regr::algo::is_dfa(&dfa));
+    assert_eq!(
+        dfa.to_string(),
+        lit!(
+            ///node(0) {
+            ///    ['b'] -> node(1)
+            ///    [Epsilon] -> node(3)
+            ///        asrt start
+            ///}
+            ///node(1) {
+            ///    [Epsilon] -> node((2))
+            ///        asrt end
+            ///}
+            ///node((2)) {}
+            ///node(3) {
+            ///    ['b'] -> node(1)
+            ///    ['a'] -> node((4))
+            ///}
+            ///node((4)) {}
+        )
+    );
+}
+
+#[test]
+fn graph_literal_trie() {
+    fn nfa_len(pattern: &str) -> usize {
+        let mut arena = Arena::new();
+        let nfa = Graph::new_in(&mut arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        nfa.arena().nodes().len()
+    }
+    // the last alternative isn't a literal, so the trie isn't built
+    assert!(nfa_len("cat|car|card") < nfa_len("cat|car|car[d]"));
+
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("cat|car|card").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    assert!(regr::algo::is_dfa(&dfa));
+    assert!(run_dfa(dfa.start_node(), b"cat"));
+    assert!(run_dfa(dfa.start_node(), b"car"));
+    assert!(run_dfa(dfa.start_node(), b"card"));
+    assert!(!run_dfa(dfa.start_node(), b"ca"));
+    assert!(!run_dfa(dfa.start_node(), b"cart"));
+    assert!(!run_dfa(dfa.start_node(), b"catd"));
+}
+
+#[test]
+fn graph_with_alphabet() {
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let parser = Parser::new(Utf8Encoder).with_alphabet(SetU8::from(b' '..=b'~'));
+    let hir = parser.parse("a.*").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    dfa.for_each_node(|node| {
+        for (_, tr) in node.targets().iter() {
+            assert!(tr.symbols().all(|byte| (b' '..=b'~').contains(&byte)));
+        }
+    });
+    assert!(run_dfa(dfa.start_node(), b"a~ b"));
+    assert!(!run_dfa(dfa.start_node(), b"a\tb"));
+
+    assert!(parser.parse("a\\x00").is_err());
+}
+
+#[test]
+fn graph_to_dot_with_subsets() {
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let a = nfa.node();
+    let b = nfa.node();
+    let c = nfa.node().finalize();
+    let d = nfa.node().finalize();
+    a.connect_epsilon(b);
+    a.connect(c).merge(b'x');
+    b.connect(d).merge(b'x');
+    b.connect(d).merge(b'"');
+
+    let mut dfa_arena = Arena::new();
+    let (dfa, map) = nfa.determinize_with_map_in(&mut dfa_arena);
+    assert_eq!(map.len(), 3);
+    assert_eq!(
+        map.values().cloned().collect::<Vec<_>>(),
+        [
+            vec![a.uid(), b.uid()],
+            vec![d.uid()],
+            vec![c.uid(), d.uid()]
+        ]
+    );
+
+    assert_eq!(
+        dfa.to_dot_with_subsets(&map),
+        lit!(
+            ///digraph {
+            ///    rankdir=LR;
+            ///    start [shape=point];
+            ///    start -> n0;
+            ///    n0 [shape=circle, label="0\n{0, 1}"];
+            ///    n1 [shape=doublecircle, label="1\n{3}"];
+            ///    n2 [shape=doublecircle, label="2\n{2, 3}"];
+            ///    n0 -> n1 [label="['\"']"];
+            ///    n0 -> n2 [label="['x']"];
+            ///}
+            ///
+        )
+    );
+    assert_eq!(
+        dfa.to_dot(),
+        lit!(
+            ///digraph {
+            ///    rankdir=LR;
+            ///    start [shape=point];
+            ///    start -> n0;
+            ///    n0 [shape=circle, label="0"];
+            ///    n1 [shape=doublecircle, label="1"];
+            ///    n2 [shape=doublecircle, label="2"];
+            ///    n0 -> n1 [label="['\"']"];
+            ///    n0 -> n2 [label="['x']"];
+            ///}
+            ///
+        )
+    );
+}
+
+#[test]
+fn graph_determinize_with_tags() {
+    fn dfa_string(pattern: &str) -> String {
+        let mut nfa_arena = Arena::new();
+        let nfa = Graph::new_in(&mut nfa_arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+
+        let mut dfa_arena = Arena::new();
+        let dfa = nfa.determinize_with_tags_in(&mut dfa_arena);
+        assert!(regr::algo::is_dfa(&dfa));
+        assert_eq!(dfa.tag_groups().count(), nfa.tag_groups().count());
+        dfa.to_string()
+    }
+
+    // the second group is entered only by the 'b' transition
+    assert_eq!(
+        dfa_string("(?<1>a)(?<2>b)?"),
+        lit!(
+            ///node(0) {
+            ///    ['a'] -> node((1))
+            ///        wrpos t0/r0
+            ///}
+            ///node((1)) {
+            ///    ['b'] -> node((2))
+            ///        wrpos t3/r1
+            ///}
+            ///node((2)) {}
+        )
+    );
+    // the group's end is written, and the other alternative invalidates the
+    // group, on the Epsilon transitions to the separate final node
+    assert_eq!(
+        dfa_string("(?<1>a+)|b"),
+        lit!(
+            ///node(0) {
+            ///    ['a'] -> node((1))
+            ///        wrpos t0/r0
+            ///    ['b'] -> node((3))
+            ///}
+            ///node((1)) {
+            ///    ['a'] -> self
+            ///    [Epsilon] -> node((2))
+            ///        wrpos t2/r1
+            ///}
+            ///node((2)) {}
+            ///node((3)) {
+            ///    [Epsilon] -> node((2))
+            ///        invd t0
+            ///        invd t2
+            ///}
+        )
+    );
+}
+
+#[test]
+fn graph_determinize_with_tag_conflicts() {
+    fn has_tag_conflicts(pattern: &str) -> bool {
+        let mut nfa_arena = Arena::new();
+        let nfa = Graph::new_in(&mut nfa_arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+
+        let mut dfa_arena = Arena::new();
+        let dfa = nfa.determinize_with_tags_in(&mut dfa_arena);
+        assert!(!nfa.determinize_in(&mut Arena::new()).has_tag_conflicts());
+        dfa.has_tag_conflicts()
+    }
+
+    assert!(!has_tag_conflicts("(?<1>a)(?<2>b)?"));
+    assert!(!has_tag_conflicts("(?<1>a+)|(?<2>b)"));
+    assert!(!has_tag_conflicts("((?<1>a)|b)+"));
+    // after `a`, one path has closed the first group, and the other one
+    // closes it only after `b`
+    assert!(has_tag_conflicts("(?<1>a|ab)(?<2>c|bcd)"));
+    assert!(has_tag_conflicts("(?<1>a*)(?<2>a*)"));
+    // only one of the paths passing `a` opens the group
+    assert!(has_tag_conflicts("(?<1>a)|ab"));
+}
+
+#[test]
+fn graph_overlaps() {
+    fn overlaps(pattern: &str, other_pattern: &str) -> bool {
+        let mut arena = Arena::new();
+        let mut other_arena = Arena::new();
+        let nfa = Graph::new_in(&mut arena);
+        let other_nfa = Graph::new_in(&mut other_arena);
+        for (graph, pattern) in [(&nfa, pattern), (&other_nfa, other_pattern)] {
+            let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+            let start_node = graph.start_node();
+            let end_node = graph.node().finalize();
+            Translator::new(graph)
+                .translate(&hir, start_node, end_node)
+                .unwrap();
+        }
+        let result = nfa.overlaps(&other_nfa);
+        assert_eq!(other_nfa.overlaps(&nfa), result);
+        result
+    }
+
+    assert!(overlaps("[a-c]+", ".*b.*"));
+    assert!(!overlaps("abc", "xyz"));
+    assert!(!overlaps("[a-c]+", "[d-f]+"));
+    assert!(overlaps("a*", "b*"));
+    assert!(overlaps("(ab)+", "a(ba)*b"));
+    assert!(!overlaps("(ab)+", "(ab)*a"));
+    assert!(overlaps("^a", "a$"));
+    assert!(!overlaps("a$b", "ab"));
+    assert!(!overlaps("a^b", "ab"));
+
+    let mut arena = Arena::new();
+    let empty = Graph::new_in(&mut arena);
+    let mut other_arena = Arena::new();
+    let other = Graph::new_in(&mut other_arena);
+    other.node().finalize();
+    assert!(!empty.overlaps(&other));
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn graph_has_cycle() {
+    fn has_cycle(pattern: &str) -> (bool, bool) {
+        let mut arena = Arena::new();
+        let nfa = Graph::new_in(&mut arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        let mut dfa_arena = Arena::new();
+        let dfa = nfa.determinize_in(&mut dfa_arena);
+        (nfa.has_cycle(), dfa.has_cycle())
+    }
+
+    assert_eq!(has_cycle("a+"), (true, true));
+    assert_eq!(has_cycle("a*"), (true, true));
+    assert_eq!(has_cycle("x(ab)*y"), (true, true));
+    assert_eq!(has_cycle("abc|d+"), (true, true));
+    assert_eq!(has_cycle("abc"), (false, false));
+    assert_eq!(has_cycle("a{3}"), (false, false));
+    assert_eq!(has_cycle("a{1,5}|b?"), (false, false));
+
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    assert!(!graph.has_cycle());
+
+    // a cycle of epsilon transitions only doesn't consume input
+    let start_node = graph.start_node();
+    let node = graph.node();
+    start_node.connect_epsilon(node);
+    node.connect_epsilon(start_node);
+    node.connect(graph.node().finalize()).merge(b'a');
+    assert!(!graph.has_cycle());
+
+    let back = graph.node();
+    node.connect(back).merge(b'b');
+    back.connect_epsilon(start_node);
+    assert!(graph.has_cycle());
+}
+
+#[test]
+fn graph_accepts_any() {
+    fn accepts_any(pattern: &str) -> (bool, bool) {
+        let mut arena = Arena::new();
+        let nfa = Graph::new_in(&mut arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        let mut dfa_arena = Arena::new();
+        let dfa = nfa.determinize_in(&mut dfa_arena);
+        (nfa.accepts_any(), dfa.accepts_any())
+    }
+
+    assert_eq!(accepts_any("abc"), (true, true));
+    assert_eq!(accepts_any(""), (true, true));
+    assert_eq!(accepts_any("a*|b"), (true, true));
+
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    assert!(!graph.accepts_any());
+
+    // the final node is isolated
+    let start_node = graph.start_node();
+    let node = graph.node();
+    start_node.connect(node).merge(b'a');
+    node.connect_epsilon(start_node);
+    let final_node = graph.node().finalize();
+    assert!(!graph.accepts_any());
+
+    node.connect(final_node).merge(b'b');
+    assert!(graph.accepts_any());
+}
+
+#[test]
+fn graph_all_transitions_sorted() {
+    let mut arena_a = Arena::new();
+    let graph_a = Graph::new_in(&mut arena_a);
+    let [a0, a1, a2] = [graph_a.node(), graph_a.node(), graph_a.node()];
+    a0.connect(a1).merge(b'a');
+    a0.connect(a2).merge(range(b'x', b'z'));
+    a1.connect(a2).merge(b'b');
+    a1.connect(a2).merge(b'c');
+    a2.connect_epsilon(a0);
+    a2.finalize();
+
+    // the same graph with the transitions connected in the other order
+    let mut arena_b = Arena::new();
+    let graph_b = Graph::new_in(&mut arena_b);
+    let [b0, b1, b2] = [graph_b.node(), graph_b.node(), graph_b.node()];
+    b2.connect_epsilon(b0);
+    b1.connect(b2).merge(b'c');
+    b0.connect(b2).merge(range(b'y', b'z'));
+    b1.connect(b2).merge(b'b');
+    b0.connect(b1).merge(b'a');
+    b0.connect(b2).merge(b'x');
+    b2.finalize();
+
+    let transitions = graph_a.all_transitions_sorted().collect::<Vec<_>>();
+    assert_eq!(
+        transitions,
+        [
+            (0, 1, SetU8::from(b'a')),
+            (0, 2, SetU8::from(b'x'..=b'z')),
+            (1, 2, SetU8::from(b"bc")),
+            (2, 0, SetU8::new()),
+        ]
+    );
+    assert_eq!(
+        graph_b.all_transitions_sorted().collect::<Vec<_>>(),
+        transitions
+    );
+
+    let mut arena = Arena::new();
+    assert_eq!(
+        Graph::new_in(&mut arena).all_transitions_sorted().count(),
+        0
+    );
+}
+
+#[test]
+fn graph_accepts() {
+    // the NFA of `graph_determine_1` with the final node
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let a = nfa.node();
+    let b = nfa.node();
+    let c = nfa.node();
+    let d = nfa.node().finalize();
+    a.connect(a).merge(range(1, 255));
+    a.connect_epsilon(b);
+    b.connect(c).merge(b'a');
+    c.connect(d).merge(b'b');
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    for (input, expected) in [
+        (&b"ab"[..], true),
+        (b"xxab", true),
+        (b"abab", true),
+        (b"aab", true),
+        (b"", false),
+        (b"a", false),
+        (b"abx", false),
+        (b"\x00ab", false),
+    ] {
+        assert_eq!(nfa.accepts_nfa(input), expected, "{input:?}");
+        assert_eq!(dfa.accepts(input), expected, "{input:?}");
+    }
+
+    for (pattern, input, expected) in [
+        ("a*b", &b"aab"[..], true),
+        ("a*b", b"ba", false),
+        ("^ab|b$", b"ab", true),
+        ("^ab|b$", b"b", true),
+        ("^ab|b$", b"abb", false),
+        ("(ab)+c?", b"ababc", true),
+        ("(ab)+c?", b"abcc", false),
+        ("", b"", true),
+        ("", b"a", false),
+    ] {
+        let mut arena = Arena::new();
+        let nfa = Graph::new_in(&mut arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = nfa.start_node();
+        let end_node = nfa.node().finalize();
+        Translator::new(&nfa)
+            .translate(&hir, start_node, end_node)
+            .unwrap();
+        let mut dfa_arena = Arena::new();
+        let dfa = nfa.determinize_in(&mut dfa_arena);
+        assert_eq!(
+            nfa.accepts_nfa(input),
+            expected,
+            "{pattern:?} against {input:?}"
+        );
+        assert_eq!(
+            dfa.accepts(input),
+            expected,
+            "{pattern:?} against {input:?}"
+        );
+    }
+
+    let mut arena = Arena::new();
+    let empty = Graph::new_in(&mut arena);
+    assert!(!empty.accepts(b""));
+    assert!(!empty.accepts_nfa(b""));
+}
+
+#[test]
+#[should_panic(expected = "can't walk a non-DFA graph")]
+fn graph_accepts_nfa_panics() {
+    let mut arena = Arena::new();
+    let nfa = Graph::new_in(&mut arena);
+    let start_node = nfa.start_node();
+    start_node.connect(nfa.node()).merge(b'a');
+    start_node.connect(nfa.node()).merge(b'a');
+    nfa.accepts(b"a");
+}
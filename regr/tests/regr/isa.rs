@@ -0,0 +1,28 @@
+use pretty_assertions::assert_eq;
+use regr::Inst;
+
+#[test]
+fn inst_fmt() {
+    // every variant must be listed here, so a new one can't be added without
+    // deciding how it looks in the graph dumps
+    let check = |inst: Inst, expected: &str| {
+        match inst {
+            Inst::WritePos(..)
+            | Inst::InvalidateTag(_)
+            | Inst::AssertStartText
+            | Inst::AssertEndText
+            | Inst::Accept(_)
+            | Inst::Priority(_) => (),
+        }
+        assert_eq!(inst.to_string(), expected);
+        assert_eq!(format!("{inst:?}"), expected);
+    };
+    check(Inst::WritePos(0, 0), "wrpos t0/r0");
+    check(Inst::WritePos(12, 3), "wrpos t12/r3");
+    check(Inst::InvalidateTag(3), "invd t3");
+    check(Inst::AssertStartText, "asrt start");
+    check(Inst::AssertEndText, "asrt end");
+    check(Inst::Accept(1), "accept p1");
+    check(Inst::Priority(0), "prio 0");
+    check(Inst::Priority(u32::MAX), "prio 4294967295");
+}
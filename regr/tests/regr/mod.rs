@@ -1,6 +1,7 @@
 mod algo;
 mod arena;
 mod graph;
+mod isa;
 mod node;
 mod symbol;
 mod transition;
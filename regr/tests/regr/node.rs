@@ -70,7 +70,7 @@ fn node_connect_nfa() {
     node_a.connect(node_b).merge(b'a');
     node_a.connect(node_c).merge(b'a');
     node_a.connect(node_c).merge(b'a');
-    node_c.connect(node_a);
+    node_c.connect_epsilon(node_a);
 }
 
 #[test]
@@ -82,6 +82,97 @@ fn node_connect_dfa() {
     node_a.connect(node_b).merge(b'a');
 }
 
+#[test]
+fn node_connect_twice() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let node_a = graph.node();
+    let node_b = graph.node();
+    let tr = node_a.connect(node_b);
+    tr.merge(b'x');
+    node_a.connect(node_b).merge(b'y');
+
+    // there are never parallel transitions between two nodes
+    assert_eq!(node_a.targets().len(), 1);
+    assert!(node_a.targets()[&node_b].is(tr));
+    assert_eq!(tr.symbols().collect::<Vec<_>>(), b"xy");
+    assert_eq!(tr.to_string(), "['x'-'y']");
+}
+
+#[test]
+fn node_disconnect() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let node_a = graph.node();
+    let node_b = graph.node();
+    let node_c = graph.node().finalize();
+    let tr = node_a.connect(node_b);
+    tr.merge(b'a');
+    node_a.connect(node_c).merge(b'c');
+    node_b.connect(node_c).merge(b'b');
+    assert_eq!(node_a.targets().len(), 2);
+
+    assert!(node_a.disconnect(node_b).unwrap().is(tr));
+    assert_eq!(node_a.targets().len(), 1);
+    assert!(!node_a.targets().contains_key(&node_b));
+    assert!(node_a.disconnect(node_b).is_none());
+    assert_eq!(node_b.targets().len(), 1);
+    assert!(!graph.accepts_nfa(b"ab"));
+    assert!(graph.accepts_nfa(b"c"));
+}
+
+#[test]
+fn node_remove_symbol() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let node_a = graph.node();
+    let node_b = graph.node();
+    node_a.connect(node_b).merge(range(b'a', b'c'));
+
+    assert!(node_a.remove_symbol(node_b, b'b'));
+    assert!(!node_a.remove_symbol(node_b, b'b'));
+    assert!(!node_a.remove_symbol(node_b, b'x'));
+    assert_eq!(node_a.targets()[&node_b].to_string(), "['a' | 'c']");
+
+    // the last symbol takes the whole transition away
+    assert!(node_a.remove_symbol(node_b, b'a'));
+    assert!(node_a.remove_symbol(node_b, b'c'));
+    assert_eq!(node_a.targets().len(), 0);
+    assert!(!node_a.remove_symbol(node_b, b'c'));
+
+    node_a.connect_epsilon(node_b);
+    assert!(!node_a.remove_symbol(node_b, b'a'));
+    assert_eq!(node_a.targets().len(), 1);
+}
+
+#[test]
+fn node_connect_epsilon() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let node_a = graph.node();
+    let node_b = graph.node();
+    let tr = node_a.connect_epsilon(node_b);
+    assert!(tr.is_epsilon());
+    assert!(tr.contains(Epsilon));
+    assert!(node_a.connect_epsilon(node_b).is(tr));
+    assert!(node_a.connect(node_b).is(tr));
+
+    // a bare `connect` returns the existing transition with its symbols
+    node_a.connect(node_a).merge(b'a');
+    assert!(!node_a.connect(node_a).is_epsilon());
+}
+
+#[test]
+#[should_panic(expected = "nodes are already connected with a non-Epsilon transition")]
+fn node_connect_epsilon_panics() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let node_a = graph.node();
+    let node_b = graph.node();
+    node_a.connect(node_b).merge(b'a');
+    node_a.connect_epsilon(node_b);
+}
+
 #[test]
 #[should_panic(expected = "only nodes of the same graph can be joint")]
 fn node_connect_panics() {
@@ -94,6 +185,18 @@ fn node_connect_panics() {
     node_a.connect(node_b);
 }
 
+#[test]
+#[should_panic(expected = "only nodes of the same graph can be joint")]
+fn node_connect_epsilon_across_graphs_panics() {
+    let mut arena_a = Arena::new();
+    let mut arena_b = Arena::new();
+    let graph_a = Graph::new_in(&mut arena_a);
+    let graph_b = Graph::new_in(&mut arena_b);
+    let node_a = graph_a.node();
+    let node_b = graph_b.node();
+    node_a.connect_epsilon(node_b);
+}
+
 #[test]
 fn node_closure() {
     let mut arena = Arena::new();
@@ -106,9 +209,9 @@ fn node_closure() {
 
     a.connect(b).merge(b'a');
     b.connect(c).merge(b'a');
-    c.connect(d);
-    b.connect(a);
-    a.connect(d);
+    c.connect_epsilon(d);
+    b.connect_epsilon(a);
+    a.connect_epsilon(d);
     d.connect(e).merge(b'a');
 
     #[allow(clippy::mutable_key_type)]
@@ -125,13 +228,13 @@ fn node_eclosure() {
     let c = graph.node();
     let d = graph.node();
 
-    a.connect(b);
-    b.connect(c);
+    a.connect_epsilon(b);
+    b.connect_epsilon(c);
     c.connect(d).merge(b'c');
-    b.connect(a);
-    d.connect(a);
-    d.connect(b);
-    d.connect(c);
+    b.connect_epsilon(a);
+    d.connect_epsilon(a);
+    d.connect_epsilon(b);
+    d.connect_epsilon(c);
 
     #[allow(clippy::mutable_key_type)]
     let set = BTreeSet::from_iter(vec![a, b, c]);
@@ -184,13 +287,13 @@ fn node_collect_epsilon_targets() {
     let c = graph.node();
     let d = graph.node();
 
-    a.connect(b);
-    b.connect(c);
+    a.connect_epsilon(b);
+    b.connect_epsilon(c);
     c.connect(d).merge(b'c');
-    b.connect(a);
-    d.connect(a);
-    d.connect(b);
-    d.connect(c);
+    b.connect_epsilon(a);
+    d.connect_epsilon(a);
+    d.connect_epsilon(b);
+    d.connect_epsilon(c);
 
     assert_eq!(a.collect_epsilon_targets::<Vec<_>>(), vec![b]);
     assert_eq!(c.collect_epsilon_targets::<Vec<_>>(), vec![]);
@@ -204,11 +307,11 @@ fn node_iter_and_connect_overlap_panics() {
     let a = graph.node();
     let b = graph.node();
     let c = graph.node();
-    a.connect(b);
-    b.connect(c);
+    a.connect_epsilon(b);
+    b.connect_epsilon(c);
     a.for_each_epsilon_target(|b| {
         b.for_each_epsilon_target(|c| {
-            b.connect(c);
+            b.connect_epsilon(c);
         });
     });
 }
@@ -1,5 +1,5 @@
 use pretty_assertions::assert_eq;
-use redt::{RangeU8, range};
+use redt::{RangeU8, SetU8, range};
 use regr::{
     Arena, Epsilon, Graph,
     Inst::{InvalidateTag, WritePos},
@@ -158,6 +158,47 @@ fn tr_ranges() {
     handle_epsilon(|tr| assert_eq!(tr.ranges().next(), None));
 }
 
+#[test]
+fn tr_absent_symbols() {
+    handle_tr_from_symbols(b"abc", |tr| {
+        let absent = tr.absent_symbols().collect::<Vec<_>>();
+        assert_eq!(absent.len(), 253);
+        assert_eq!(absent[..3], [0, 1, 2]);
+        assert!(!absent.contains(&b'a') && !absent.contains(&b'c'));
+        assert!(absent.contains(&b'`') && absent.contains(&b'd'));
+        assert_eq!(absent.last(), Some(&u8::MAX));
+    });
+    handle_tr_from_chunks(&[u64::MAX, u64::MAX, u64::MAX, u64::MAX], |tr| {
+        assert_eq!(tr.absent_symbols().next(), None)
+    });
+    handle_epsilon(|tr| assert_eq!(tr.absent_symbols().count(), 256));
+}
+
+#[test]
+fn tr_absent_ranges() {
+    fn absent_ranges(symbols: &[u8]) -> Vec<RangeU8> {
+        handle_tr_from_symbols(symbols, |tr| tr.absent_ranges().collect())
+    }
+
+    assert_eq!(
+        absent_ranges(b"abc"),
+        [range(0, b'`'), range(b'd', u8::MAX)]
+    );
+    assert_eq!(absent_ranges(&[0, 255]), [range(1, 254)]);
+    assert_eq!(
+        absent_ranges(&[63, 64, 200]),
+        [range(0, 62), range(65, 199), range(201, 255)]
+    );
+    assert_eq!(absent_ranges(&[0]), [range(1, 255)]);
+    assert_eq!(
+        handle_tr_from_chunks(&[u64::MAX, u64::MAX, u64::MAX, u64::MAX], |tr| {
+            tr.absent_ranges().collect::<Vec<_>>()
+        }),
+        []
+    );
+    handle_epsilon(|tr| assert_eq!(tr.absent_ranges().collect::<Vec<_>>(), [range(0, 255)]));
+}
+
 #[test]
 fn tr_instructs_for() {
     let t0 = 0;
@@ -468,6 +509,55 @@ fn tr_merge_instructs() {
     assert_ne!(tr_a, tr_b);
 }
 
+#[test]
+fn tr_exclude() {
+    let mut arena = Arena::new();
+    let gr = Graph::new_in(&mut arena);
+    let tr = gr.node().connect(gr.node());
+    tr.merge(range(0, 10));
+    tr.merge_instruct(WritePos(0, 0), None);
+    tr.merge_instruct(InvalidateTag(1), Some(SetU8::from(4..=8)));
+
+    tr.exclude(range(5, 7));
+    assert_eq!(tr.ranges().collect::<Vec<_>>(), [range(0, 4), range(8, 10)]);
+    assert_eq!(tr.instructs_for(0).collect::<Vec<_>>(), [WritePos(0, 0)]);
+    assert_eq!(
+        tr.instructs_for(4).collect::<Vec<_>>(),
+        [WritePos(0, 0), InvalidateTag(1)]
+    );
+    assert_eq!(
+        tr.instructs_for(8).collect::<Vec<_>>(),
+        [WritePos(0, 0), InvalidateTag(1)]
+    );
+    assert_eq!(tr.instructs_for(9).collect::<Vec<_>>(), [WritePos(0, 0)]);
+
+    // the instructions of the removed symbols come back with them
+    tr.merge(range(5, 7));
+    assert_eq!(
+        tr.instructs_for(6).collect::<Vec<_>>(),
+        [WritePos(0, 0), InvalidateTag(1)]
+    );
+
+    tr.exclude(b'0');
+    tr.exclude(range(0, 10));
+    assert!(tr.is_epsilon());
+}
+
+#[test]
+fn tr_symbol_count() {
+    handle_tr(|tr| {
+        assert_eq!(tr.symbol_count(), 0);
+        assert!(!tr.is_full());
+        tr.merge(range(0, 255));
+        assert_eq!(tr.symbol_count(), 256);
+        assert!(tr.is_full());
+    });
+    handle_tr_from_symbols(b"abc", |tr| {
+        assert_eq!(tr.symbol_count(), 3);
+        assert!(!tr.is_full());
+    });
+}
+
 #[test]
 fn tr_display_fmt() {
     fn tr(bytes: &[u8]) -> String {
@@ -484,6 +574,19 @@ fn tr_display_fmt() {
         tr.merge(range(5, 6));
         assert_eq!(format!("{tr}"), "[02h-06h]");
     });
+
+    // a standalone range is formatted the same way as a transition with it
+    for r in [
+        range(b'a', b'c'),
+        range(2, 2),
+        range(2, 6),
+        range(b'~', 0x80),
+    ] {
+        handle_tr(|tr| {
+            tr.merge(r);
+            assert_eq!(format!("{tr}"), format!("[{r}]"));
+        });
+    }
 }
 
 #[test]
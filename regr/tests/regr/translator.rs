@@ -1,6 +1,6 @@
 use pretty_assertions::assert_eq;
 use redt::lit;
-use regr::{Arena, Graph, Translator};
+use regr::{Arena, Error, Graph, Translator};
 use resy::{Parser, enc::Utf8Encoder};
 
 fn parse(pattern: &str) -> String {
@@ -11,10 +11,36 @@ fn parse(pattern: &str) -> String {
     let mut translator = Translator::new(&graph);
     let start_node = graph.start_node();
     let end_node = graph.node();
-    translator.translate(&hir, start_node, end_node);
+    translator.translate(&hir, start_node, end_node).unwrap();
     graph.to_string()
 }
 
+#[test]
+fn translate_empty() {
+    assert_eq!(
+        parse(""),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(1)
+            ///}
+            ///node(1) {}
+        )
+    );
+
+    let mut nfa_arena = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_arena);
+    let hir = Parser::new(Utf8Encoder).parse("").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+    let mut dfa_arena = Arena::new();
+    let dfa = nfa.determinize_in(&mut dfa_arena);
+    assert!(dfa.start_node().is_final());
+    assert_eq!(dfa.to_string(), "node((0)) {}");
+}
+
 #[test]
 fn translate_literal() {
     assert_eq!(
@@ -188,8 +214,8 @@ fn translate_group_1() {
             ///}
             ///node(1) {}
             ///node(2) {
-            ///    [Epsilon] -> node(6)
-            ///    [Epsilon] -> node(8)
+            ///    ['a'] -> node(6)
+            ///    ['b'] -> node(7)
             ///}
             ///node(3) {
             ///    [Epsilon] -> node(4)
@@ -198,28 +224,19 @@ fn translate_group_1() {
             ///    [Epsilon] -> node(2)
             ///}
             ///node(5) {
-            ///    [Epsilon] -> node(11)
-            ///        wrpos t5/r1
+            ///    [Epsilon] -> node(8)
             ///}
             ///node(6) {
-            ///    ['a'] -> node(7)
+            ///    [Epsilon] -> node(5)
+            ///        wrpos t3/r1
             ///}
             ///node(7) {
-            ///    [Epsilon] -> node(5)
+            ///    ['c'] -> node(6)
             ///}
             ///node(8) {
-            ///    ['b'] -> node(10)
+            ///    [Epsilon] -> node(9)
             ///}
             ///node(9) {
-            ///    [Epsilon] -> node(5)
-            ///}
-            ///node(10) {
-            ///    ['c'] -> node(9)
-            ///}
-            ///node(11) {
-            ///    [Epsilon] -> node(12)
-            ///}
-            ///node(12) {
             ///    [Epsilon] -> node(1)
             ///}
         )
@@ -233,8 +250,7 @@ fn translate_group_1() {
             ///}
             ///node(1) {}
             ///node(2) {
-            ///    [Epsilon] -> node(6)
-            ///    [Epsilon] -> node(8)
+            ///    ['a'-'b'] -> node(5)
             ///}
             ///node(3) {
             ///    [Epsilon] -> node(4)
@@ -243,24 +259,12 @@ fn translate_group_1() {
             ///    [Epsilon] -> node(2)
             ///}
             ///node(5) {
-            ///    [Epsilon] -> node(10)
+            ///    [Epsilon] -> node(6)
             ///}
             ///node(6) {
-            ///    ['a'] -> node(7)
+            ///    [Epsilon] -> node(7)
             ///}
             ///node(7) {
-            ///    [Epsilon] -> node(5)
-            ///}
-            ///node(8) {
-            ///    ['b'] -> node(9)
-            ///}
-            ///node(9) {
-            ///    [Epsilon] -> node(5)
-            ///}
-            ///node(10) {
-            ///    [Epsilon] -> node(11)
-            ///}
-            ///node(11) {
             ///    [Epsilon] -> node(1)
             ///}
         )
@@ -444,3 +448,223 @@ fn translate_group_3() {
         )
     );
 }
+
+#[test]
+fn translate_anchor() {
+    assert_eq!(
+        parse("^a$"),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(2)
+            ///        asrt start
+            ///}
+            ///node(1) {}
+            ///node(2) {
+            ///    ['a'] -> node(3)
+            ///}
+            ///node(3) {
+            ///    [Epsilon] -> node(1)
+            ///        asrt end
+            ///}
+        )
+    );
+}
+
+#[test]
+fn translate_disjunct_tag_reset() {
+    // both alternatives pass through the position write at the exit, so the
+    // group 2 is relative to it whatever the length of the taken one is
+    assert_eq!(
+        parse("(?<1>a|bb)(?<2>c)"),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(3)
+            ///        wrpos t0/r0
+            ///}
+            ///node(1) {}
+            ///node(2) {
+            ///    [Epsilon] -> node(7)
+            ///}
+            ///node(3) {
+            ///    ['a'] -> node(5)
+            ///    ['b'] -> node(6)
+            ///}
+            ///node(4) {
+            ///    [Epsilon] -> node(2)
+            ///}
+            ///node(5) {
+            ///    [Epsilon] -> node(4)
+            ///        wrpos t2/r1
+            ///}
+            ///node(6) {
+            ///    ['b'] -> node(5)
+            ///}
+            ///node(7) {
+            ///    ['c'] -> node(8)
+            ///}
+            ///node(8) {
+            ///    [Epsilon] -> node(1)
+            ///}
+        )
+    );
+    // the exit position isn't written if no group refers to it
+    assert_eq!(
+        parse("(?<1>)(a|bb)c"),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(3)
+            ///        wrpos t0/r0
+            ///}
+            ///node(1) {}
+            ///node(2) {
+            ///    ['a'] -> node(6)
+            ///    ['b'] -> node(7)
+            ///}
+            ///node(3) {
+            ///    [Epsilon] -> node(4)
+            ///}
+            ///node(4) {
+            ///    [Epsilon] -> node(2)
+            ///}
+            ///node(5) {
+            ///    ['c'] -> node(1)
+            ///}
+            ///node(6) {
+            ///    [Epsilon] -> node(5)
+            ///}
+            ///node(7) {
+            ///    ['b'] -> node(6)
+            ///}
+        )
+    );
+}
+
+#[test]
+fn translate_disjunct_duplicates() {
+    // duplicate alternatives get no NFA branches
+    assert_eq!(parse("a+|b|a+"), parse("a+|b"));
+    assert_eq!(parse("(ab)*|(ab)*"), parse("(ab)*"));
+}
+
+#[test]
+fn translate_disjunct_empty() {
+    let node_count = |graph: &str| graph.lines().filter(|l| l.starts_with("node(")).count();
+
+    // a trailing empty alternative turns into an optional repetition, which
+    // needs fewer nodes than a separate branch of the disjunction
+    assert_eq!(parse("(a+|)"), parse("(a+)?"));
+    assert_eq!(node_count(&parse("(a+|)")), 7);
+    assert!(node_count(&parse("(a+|)")) < node_count(&parse("(a+|b)")));
+
+    // an empty alternative of literals costs only an epsilon in the trie
+    assert_eq!(
+        parse("(a|)"),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(1)
+            ///    [Epsilon] -> node(2)
+            ///}
+            ///node(1) {}
+            ///node(2) {
+            ///    ['a'] -> node(1)
+            ///}
+        )
+    );
+}
+
+#[test]
+fn translate_leftmost_first() {
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let hir = Parser::new(Utf8Encoder).parse("a|ab").unwrap();
+    let start_node = graph.start_node();
+    let end_node = graph.node();
+    Translator::new(&graph)
+        .with_leftmost_first(true)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+    // no literal trie, so the alternatives end with their own priorities
+    assert_eq!(
+        graph.to_string(),
+        lit!(
+            ///node(0) {
+            ///    [Epsilon] -> node(2)
+            ///    [Epsilon] -> node(4)
+            ///}
+            ///node(1) {}
+            ///node(2) {
+            ///    ['a'] -> node(3)
+            ///}
+            ///node(3) {
+            ///    [Epsilon] -> node(1)
+            ///        prio 0
+            ///}
+            ///node(4) {
+            ///    ['a'] -> node(6)
+            ///}
+            ///node(5) {
+            ///    [Epsilon] -> node(1)
+            ///        prio 1
+            ///}
+            ///node(6) {
+            ///    ['b'] -> node(5)
+            ///}
+        )
+    );
+
+    // nested disjunctions have no priorities
+    let mut arena = Arena::new();
+    let graph = Graph::new_in(&mut arena);
+    let hir = Parser::new(Utf8Encoder).parse("x(a|ab)").unwrap();
+    let start_node = graph.start_node();
+    let end_node = graph.node();
+    Translator::new(&graph)
+        .with_leftmost_first(true)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+    assert!(!graph.to_string().contains("prio"));
+}
+
+#[test]
+fn translate_max_expansion() {
+    fn translate(pattern: &str, max_nodes: Option<usize>) -> regr::Result<()> {
+        let mut arena = Arena::new();
+        let graph = Graph::new_in(&mut arena);
+        let hir = Parser::new(Utf8Encoder).parse(pattern).unwrap();
+        let start_node = graph.start_node();
+        let end_node = graph.node();
+        let mut translator = Translator::new(&graph);
+        if let Some(max_nodes) = max_nodes {
+            translator = translator.with_max_expansion(max_nodes);
+        }
+        translator.translate(&hir, start_node, end_node)
+    }
+
+    assert_eq!(translate("a{1000}", None), Ok(()));
+    assert_eq!(
+        translate("a{100000000}", None),
+        Err(Error::ExpansionLimit { max: 1 << 20 })
+    );
+    assert_eq!(
+        translate("(a{2000}){1000}", None),
+        Err(Error::ExpansionLimit { max: 1 << 20 })
+    );
+    assert_eq!(
+        translate("a{100000000,}", None),
+        Err(Error::ExpansionLimit { max: 1 << 20 })
+    );
+
+    assert_eq!(translate("a{8}", Some(10)), Ok(()));
+    assert_eq!(
+        translate("a{2,20}", Some(10)),
+        Err(Error::ExpansionLimit { max: 10 })
+    );
+    assert_eq!(
+        translate("a{3}{5}", Some(10)),
+        Err(Error::ExpansionLimit { max: 10 })
+    );
+    assert_eq!(
+        Error::ExpansionLimit { max: 10 }.to_string(),
+        "repetitions expand the graph to more than 10 nodes"
+    );
+}
@@ -1,31 +1,284 @@
+use crate::resolver::{Op, Resolver};
 use proc_macro2::{Delimiter, Group, TokenStream};
 use quote::{TokenStreamExt, quote};
-use regr::Graph;
-use std::collections::HashMap;
+use regr::{Graph, Inst, Tag};
+use resy::{GroupLabel, Hir};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::str::FromStr;
 
 type TransitionTable = Vec<[usize; 1 << u8::BITS]>;
 
+/// A state of the sparse transition table: a default target and a sorted list
+/// of `(first, last, target)` arcs for byte ranges leading elsewhere.
+type SparseLine = (usize, Vec<(u8, u8, usize)>);
+
+/// The sparse transition table is generated only if the average number of
+/// arcs per state doesn't exceed this threshold.
+const MAX_SPARSE_ARCS_PER_STATE: usize = 8;
+
+/// An operation on a register of the generated state machine. Clearing goes
+/// before writing, so a position written on a path is never lost because of
+/// an invalidation merged from another path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum RegOp {
+    Clear(usize),
+    Write(usize),
+}
+
+/// A position of a group's tag: the register holding the base position, the
+/// offset from it, and the registers that must be valid for the tag to be
+/// valid.
+type TagPos = (usize, usize, Vec<usize>);
+
+/// Register operations and group positions for capture-group extraction.
+struct Captures {
+    /// For every register, if it's valid at the start, i.e. it's a validity
+    /// flag of a pseudo-absolute tag rather than a position.
+    initial_regs: Vec<bool>,
+    /// For every state, `(first, last, ops)` arcs of byte ranges with the
+    /// operations to apply before the transition.
+    tr_ops: Vec<Vec<(u8, u8, Vec<RegOp>)>>,
+    /// For every state, the operations to apply if the match ends in it.
+    final_ops: Vec<Vec<RegOp>>,
+    /// For every state, the operations to apply if the match ends in it at
+    /// the end of the haystack.
+    final_at_end_ops: Vec<Vec<RegOp>>,
+    groups: Vec<(GroupLabel, TagPos, TagPos)>,
+}
+
+/// Patterns accepted by the final states of a multi-pattern automaton.
+struct Patterns {
+    /// For every state, the pattern accepted if the match ends in it.
+    final_patterns: Vec<u32>,
+    /// For every state, the pattern accepted if the match ends in it at the
+    /// end of the haystack.
+    final_at_end_patterns: Vec<u32>,
+    patterns_num: usize,
+}
+
+/// Priorities of the alternatives for the leftmost-first match semantics. The
+/// lower value is preferred, and `u32::MAX` means no match.
+struct Priorities {
+    /// For every state, the priority of the match ending in it.
+    final_priorities: Vec<u32>,
+    /// For every state, the priority of the match ending in it at the end of
+    /// the haystack.
+    final_at_end_priorities: Vec<u32>,
+    /// For every state, the best priority of the matches reachable from it.
+    live_priorities: Vec<u32>,
+}
+
 pub struct CodeGen {
     tr_table: TransitionTable,
     invalid_id: usize,
     start_id: usize,
+    text_start_id: usize,
     first_non_final_id: usize,
+    final_at_end: Vec<bool>,
+    min_len: usize,
+    match_len: Option<usize>,
+    dead_state_errors: bool,
+    sink_state: bool,
+    sparse_table: bool,
+    byte_classes: bool,
+    flat_table: bool,
+    anchored_start: bool,
+    char_offsets: bool,
+    no_std: bool,
+    bytes_only: bool,
+    captures: Option<Captures>,
+    tag_conflicts: bool,
+    resolver: Option<Resolver>,
+    patterns: Option<Patterns>,
+    priorities: Option<Priorities>,
 }
 
 impl<'a> CodeGen {
+    /// Creates a code generator for the DFA graph.
+    ///
+    /// The empty pattern gives a graph with the final start node, so the
+    /// generated matcher matches the empty string at any position. A graph
+    /// without final nodes, i.e. the empty language, is allowed too, and its
+    /// matcher never matches.
+    ///
+    /// If the graph has tag groups, e.g. it's built with
+    /// [`Graph::determinize_with_tags_in`], the generated state machine keeps
+    /// registers with positions of the groups, and the generated `Match` gets
+    /// the `group` method. [`CodeGen::with_capture_resolver`] replaces the
+    /// registers if they can't tell the groups apart.
+    ///
+    /// Panics if the graph has no nodes at all, because there is no start
+    /// state to generate code for.
     pub fn new(graph: &Graph<'a>) -> Self {
         assert!(!graph.is_empty(), "can't generate code for an empty graph");
 
         let (id_map, invalid_id, start_id, first_non_final_id) = Self::build_id_map(graph);
         let tr_table = Self::build_tr_table(graph, invalid_id, &id_map);
+        let (text_start_id, final_at_end) = Self::build_anchors(graph, start_id, &id_map);
+        let min_len = Self::find_min_len(
+            &tr_table,
+            &[start_id, text_start_id],
+            first_non_final_id,
+            &final_at_end,
+        );
+        let captures = Self::build_captures(graph, &id_map);
+        let patterns = Self::build_patterns(graph, &id_map);
+        let priorities = Self::build_priorities(graph, &id_map, &tr_table);
 
         CodeGen {
             tr_table,
             invalid_id,
             start_id,
+            text_start_id,
             first_non_final_id,
+            final_at_end,
+            min_len,
+            match_len: None,
+            dead_state_errors: false,
+            sink_state: false,
+            sparse_table: false,
+            byte_classes: false,
+            flat_table: false,
+            anchored_start: false,
+            char_offsets: false,
+            no_std: false,
+            bytes_only: false,
+            captures,
+            tag_conflicts: graph.has_tag_conflicts(),
+            resolver: None,
+            patterns,
+            priorities,
+        }
+    }
+
+    /// Makes the generated `match_at` method run over the whole haystack
+    /// without a per-byte check for the invalid state, which is a non-final
+    /// sink with transitions to itself.
+    ///
+    /// The check is still generated if dead state errors are enabled, because
+    /// they need the offset of the first byte without a valid transition.
+    ///
+    /// Since every `match_at` call reads the rest of the haystack, `find`,
+    /// `find_iter` and the other searching methods, which call `match_at` at
+    /// every start position, take quadratic time in the haystack length. The
+    /// sink state pays off only for anchored matching of short haystacks.
+    pub fn with_sink_state(mut self, enabled: bool) -> Self {
+        self.sink_state = enabled;
+        self
+    }
+
+    /// Allows to generate a sparse transition table, i.e. a list of byte range
+    /// arcs with a default target for every state, instead of the dense table
+    /// of 256 targets per state.
+    ///
+    /// The sparse table is generated only if the automaton uses few arcs per
+    /// state, otherwise the dense one is still used.
+    pub fn with_sparse_table(mut self, enabled: bool) -> Self {
+        self.sparse_table = enabled;
+        self
+    }
+
+    /// Makes the dense transition table indexed by byte equivalence classes
+    /// instead of bytes, i.e. bytes leading to the same targets from every
+    /// state share a single column of the table.
+    ///
+    /// If the sparse table is generated, this option has no effect.
+    pub fn with_byte_classes(mut self, enabled: bool) -> Self {
+        self.byte_classes = enabled;
+        self
+    }
+
+    /// Makes the dense transition table a flat array of `STATES_NUM * STRIDE`
+    /// targets, where `STRIDE` is the number of bytes, instead of the array of
+    /// rows. The target for a byte is at `state * STRIDE + byte`.
+    ///
+    /// If the sparse table or byte classes are generated, this option has no
+    /// effect.
+    pub fn with_flat_table(mut self, enabled: bool) -> Self {
+        self.flat_table = enabled;
+        self
+    }
+
+    /// Makes the generated search methods, like `find_iter`, stop at the first
+    /// position without a match instead of skipping it, so every match starts
+    /// exactly where the previous one ends, and the first one starts at the
+    /// beginning of the haystack.
+    ///
+    /// An empty match stops the search too, because it doesn't move the
+    /// position forward.
+    pub fn with_anchored_start(mut self, enabled: bool) -> Self {
+        self.anchored_start = enabled;
+        self
+    }
+
+    /// Makes the generated `Match` keep a reference to the whole haystack, and
+    /// get the `char_start` and `char_end` methods returning offsets of the
+    /// match in chars. They count the chars from the haystack start on every
+    /// call, i.e. take time linear in the offset, so cache them if they are
+    /// needed often.
+    pub fn with_char_offsets(mut self, enabled: bool) -> Self {
+        self.char_offsets = enabled;
+        self
+    }
+
+    /// Makes the generated code use `core` and `alloc` only, so it builds in a
+    /// `#![no_std]` crate with `extern crate alloc`. The allocating methods,
+    /// e.g. `find_all` and `replacen`, refer to `alloc` types; the state
+    /// machine and the matching methods don't allocate at all.
+    pub fn with_no_std(mut self, enabled: bool) -> Self {
+        self.no_std = enabled;
+        self
+    }
+
+    /// Makes the generated `Regex` have the byte slice methods only, i.e.
+    /// `match_at_bytes` and `match_segments`, returning `BytesMatch`. It's
+    /// needed for the patterns encoded with a single-byte encoding, e.g.
+    /// Latin-1, because a match of them may end inside a UTF-8 char of a
+    /// `&str` haystack.
+    ///
+    /// The `Match` type, the search methods and the `Lexer` aren't generated.
+    pub fn with_bytes_only(mut self, enabled: bool) -> Self {
+        self.bytes_only = enabled;
+        self
+    }
+
+    /// Sets the exact length of every match in bytes, e.g. the one returned by
+    /// [`resy::Hir::exact_len`] for the pattern, as the `MATCH_LEN` constant
+    /// of the generated `Regex`.
+    ///
+    /// With the length, the generated `match_at` method checks if the rest of
+    /// the haystack is long enough up front, and then runs the state machine
+    /// over exactly that many bytes without tracking the last final state. It
+    /// isn't done for captures, several patterns, priorities and dead state
+    /// errors, which need the general loop.
+    pub fn with_match_len(mut self, len: Option<usize>) -> Self {
+        self.match_len = len;
+        self
+    }
+
+    /// Makes the generated `Match` get the groups from a backtracking pass of
+    /// the `patterns` over the match, if the graph has tag conflicts, see
+    /// [`Graph::has_tag_conflicts`]. The registers of such a graph may mix
+    /// positions of several paths, so they aren't generated then.
+    ///
+    /// The pass prefers the earlier alternatives and the greedy repetitions.
+    /// It tries every pair of an instruction and a position at most once, so
+    /// it takes `O(m * n)` time and bits of memory, where `m` is the size of
+    /// the program and `n` is the length of the match.
+    pub fn with_capture_resolver(mut self, patterns: &[Hir]) -> Self {
+        if self.tag_conflicts && self.captures.is_some() {
+            self.captures = None;
+            self.resolver = Some(Resolver::new(patterns));
         }
+        self
+    }
+
+    /// Makes the generated `match_at` method return `Result<Match, MatchError>`
+    /// instead of `Option<Match>`, where the error names the offset and the
+    /// value of the byte that had no valid transition.
+    pub fn with_dead_state_errors(mut self, enabled: bool) -> Self {
+        self.dead_state_errors = enabled;
+        self
     }
 
     /// Builds a map from node IDs to their respective indices in the transition
@@ -84,18 +337,390 @@ impl<'a> CodeGen {
         tr_table
     }
 
-    pub fn gen_state_machine(&self) -> TokenStream {
-        let tr_table_len: usize = self.tr_table.len();
-        let mut tr_table_lines = Vec::new();
-        for line in &self.tr_table {
-            let mut token_line = TokenStream::new();
-            for num in line {
-                // to remove suffix
-                let stream = TokenStream::from_str(&format!("{num},")).unwrap();
-                token_line.append_all(stream);
+    /// Looks for the anchor assertions left by the determinization.
+    ///
+    /// Returns a tuple containing the ID of the state used at the beginning of
+    /// a haystack, and flags of the states that are final only at the end of
+    /// a haystack.
+    fn build_anchors(
+        graph: &Graph<'a>,
+        start_id: usize,
+        id_map: &HashMap<u64, usize>,
+    ) -> (usize, Vec<bool>) {
+        let mut text_start_id = start_id;
+        let mut final_at_end = vec![false; id_map.len()];
+        graph.for_each_node(|node| {
+            let node_id = id_map[&node.uid()];
+            for (target, tr) in node.targets().iter() {
+                if !tr.is_epsilon() {
+                    continue;
+                }
+                for inst in tr.instructs() {
+                    match inst {
+                        Inst::AssertStartText if node_id == start_id => {
+                            text_start_id = id_map[&target.uid()];
+                        }
+                        Inst::AssertEndText if target.is_final() => {
+                            final_at_end[node_id] = true;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        });
+        (text_start_id, final_at_end)
+    }
+
+    /// Collects the register operations of the transitions, and resolves the
+    /// tags of the groups into registers. Absolute tags get registers for
+    /// their positions, and pseudo-absolute ones get registers for their
+    /// validity flags, which are cleared by the tag invalidation.
+    ///
+    /// Returns `None` if the graph has no tag groups.
+    fn build_captures(graph: &Graph<'a>, id_map: &HashMap<u64, usize>) -> Option<Captures> {
+        let mut tag_groups: Vec<_> = graph.tag_groups().collect();
+        if tag_groups.is_empty() {
+            return None;
+        }
+        tag_groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let tags: BTreeMap<_, _> = graph.tags().map(|tag| (tag.id(), tag)).collect();
+        let mut absolute_tags: Vec<_> = tags
+            .values()
+            .filter_map(|tag| match tag {
+                Tag::Absolute { id, reg } => Some((*reg, *id)),
+                _ => None,
+            })
+            .collect();
+        absolute_tags.sort_unstable();
+        let pseudo_absolute_tags = tags
+            .values()
+            .filter(|tag| matches!(tag, Tag::PseudoAbsolute { .. }))
+            .map(Tag::id);
+        let mut slots = HashMap::new();
+        let mut initial_regs = Vec::new();
+        for (_, id) in absolute_tags {
+            slots.insert(id, initial_regs.len());
+            initial_regs.push(false);
+        }
+        for id in pseudo_absolute_tags {
+            slots.insert(id, initial_regs.len());
+            initial_regs.push(true);
+        }
+
+        fn resolve(tag: Tag, tags: &BTreeMap<u32, Tag>, slots: &HashMap<u32, usize>) -> TagPos {
+            let starting_pos = |starting_tag: u32| {
+                let tag = tags
+                    .get(&starting_tag)
+                    .unwrap_or_else(|| panic!("unknown starting tag {starting_tag}"));
+                resolve(*tag, tags, slots)
+            };
+            match tag {
+                Tag::Absolute { id, .. } => (slots[&id], 0, Vec::new()),
+                Tag::PseudoAbsolute {
+                    id,
+                    starting_tag,
+                    offset,
+                } => {
+                    let (slot, base_offset, mut guards) = starting_pos(starting_tag);
+                    guards.push(slots[&id]);
+                    (slot, base_offset + offset, guards)
+                }
+                Tag::Relative {
+                    starting_tag,
+                    offset,
+                    ..
+                } => {
+                    let (slot, base_offset, guards) = starting_pos(starting_tag);
+                    (slot, base_offset + offset, guards)
+                }
+            }
+        }
+        let groups = tag_groups
+            .into_iter()
+            .map(|(label, (open_tag, close_tag))| {
+                let open_pos = resolve(open_tag, &tags, &slots);
+                let close_pos = resolve(close_tag, &tags, &slots);
+                (label, open_pos, close_pos)
+            })
+            .collect();
+
+        let reg_ops = |insts: &mut dyn Iterator<Item = Inst>| {
+            let mut ops: Vec<_> = insts
+                .filter_map(|inst| match inst {
+                    Inst::WritePos(tag, _) => slots.get(&tag).map(|slot| RegOp::Write(*slot)),
+                    Inst::InvalidateTag(tag) => slots.get(&tag).map(|slot| RegOp::Clear(*slot)),
+                    _ => None,
+                })
+                .collect();
+            ops.sort_unstable();
+            ops.dedup();
+            ops
+        };
+        let mut tr_ops = vec![Vec::new(); id_map.len()];
+        let mut final_ops = vec![Vec::new(); id_map.len()];
+        let mut final_at_end_ops = vec![Vec::new(); id_map.len()];
+        graph.for_each_node(|node| {
+            let node_id = id_map[&node.uid()];
+            let mut byte_ops = vec![Vec::new(); 1 << u8::BITS];
+            for (target, tr) in node.targets().iter() {
+                if tr.is_epsilon() {
+                    if !target.is_final()
+                        || tr.instructs().any(|inst| inst == Inst::AssertStartText)
+                    {
+                        continue;
+                    }
+                    let ops = reg_ops(&mut tr.instructs());
+                    if tr.instructs().any(|inst| inst == Inst::AssertEndText) {
+                        final_at_end_ops[node_id] = ops;
+                    } else if node.is_final() {
+                        final_ops[node_id] = ops;
+                    }
+                } else {
+                    for sym in tr.symbols() {
+                        byte_ops[sym as usize] = reg_ops(&mut tr.instructs_for(sym));
+                    }
+                }
+            }
+            let arcs: &mut Vec<(u8, u8, Vec<RegOp>)> = &mut tr_ops[node_id];
+            for (byte, ops) in byte_ops.into_iter().enumerate() {
+                if ops.is_empty() {
+                    continue;
+                }
+                let byte = byte as u8;
+                match arcs.last_mut() {
+                    Some((_, last, last_ops))
+                        if *last_ops == ops && *last as usize + 1 == byte as usize =>
+                    {
+                        *last = byte;
+                    }
+                    _ => arcs.push((byte, byte, ops)),
+                }
+            }
+        });
+
+        Some(Captures {
+            initial_regs,
+            tr_ops,
+            final_ops,
+            final_at_end_ops,
+            groups,
+        })
+    }
+
+    /// Looks for the [`Inst::Accept`] instructions on the transitions to final
+    /// nodes. If several patterns are accepted in a state, the first of them
+    /// wins.
+    ///
+    /// Returns `None` if the graph has no such instructions.
+    fn build_patterns(graph: &Graph<'a>, id_map: &HashMap<u64, usize>) -> Option<Patterns> {
+        let mut final_patterns = vec![None; id_map.len()];
+        let mut final_at_end_patterns = vec![None; id_map.len()];
+        let mut patterns_num = 0;
+        graph.for_each_node(|node| {
+            let node_id = id_map[&node.uid()];
+            for (target, tr) in node.targets().iter() {
+                if !tr.is_epsilon() || !target.is_final() {
+                    continue;
+                }
+                let pattern = tr
+                    .instructs()
+                    .filter_map(|inst| match inst {
+                        Inst::Accept(pattern) => Some(pattern),
+                        _ => None,
+                    })
+                    .min();
+                let Some(pattern) = pattern else {
+                    continue;
+                };
+                patterns_num = patterns_num.max(pattern as usize + 1);
+                if tr.instructs().any(|inst| inst == Inst::AssertEndText) {
+                    final_at_end_patterns[node_id] = Some(pattern);
+                } else if node.is_final() {
+                    final_patterns[node_id] = Some(pattern);
+                }
+            }
+        });
+        if patterns_num == 0 {
+            return None;
+        }
+        let unwrap =
+            |patterns: Vec<Option<u32>>| patterns.into_iter().map(|p| p.unwrap_or(0)).collect();
+        Some(Patterns {
+            final_patterns: unwrap(final_patterns),
+            final_at_end_patterns: unwrap(final_at_end_patterns),
+            patterns_num,
+        })
+    }
+
+    /// Looks for the [`Inst::Priority`] instructions on the transitions to
+    /// final nodes, and propagates the best of them backward over the
+    /// transition table, so the matcher knows when no preferred match is
+    /// reachable anymore.
+    ///
+    /// Returns `None` if the graph has no such instructions.
+    fn build_priorities(
+        graph: &Graph<'a>,
+        id_map: &HashMap<u64, usize>,
+        tr_table: &TransitionTable,
+    ) -> Option<Priorities> {
+        let mut final_priorities = vec![u32::MAX; id_map.len()];
+        let mut final_at_end_priorities = vec![u32::MAX; id_map.len()];
+        let mut found = false;
+        graph.for_each_node(|node| {
+            let node_id = id_map[&node.uid()];
+            for (target, tr) in node.targets().iter() {
+                if !tr.is_epsilon() || !target.is_final() {
+                    continue;
+                }
+                let priority = tr
+                    .instructs()
+                    .filter_map(|inst| match inst {
+                        Inst::Priority(priority) => Some(priority),
+                        _ => None,
+                    })
+                    .min();
+                let Some(priority) = priority else {
+                    continue;
+                };
+                found = true;
+                if tr.instructs().any(|inst| inst == Inst::AssertEndText) {
+                    let end_priority = &mut final_at_end_priorities[node_id];
+                    *end_priority = (*end_priority).min(priority);
+                } else if node.is_final() {
+                    final_priorities[node_id] = final_priorities[node_id].min(priority);
+                }
+            }
+        });
+        if !found {
+            return None;
+        }
+
+        // a match ending in a state is a match at the end of the haystack too
+        for (end_priority, priority) in final_at_end_priorities.iter_mut().zip(&final_priorities) {
+            *end_priority = (*end_priority).min(*priority);
+        }
+        let mut live_priorities = final_at_end_priorities.clone();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (state, line) in tr_table.iter().enumerate() {
+                for target in line {
+                    if let Some(&priority) = live_priorities.get(*target)
+                        && priority < live_priorities[state]
+                    {
+                        live_priorities[state] = priority;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        Some(Priorities {
+            final_priorities,
+            final_at_end_priorities,
+            live_priorities,
+        })
+    }
+
+    /// Finds the length of the shortest path from the start states to any
+    /// final state, i.e. the minimal length of a match in bytes.
+    ///
+    /// If there is no reachable final state, returns `0`.
+    fn find_min_len(
+        tr_table: &TransitionTable,
+        start_ids: &[usize],
+        first_non_final_id: usize,
+        final_at_end: &[bool],
+    ) -> usize {
+        let mut distances = vec![None; tr_table.len()];
+        let mut queue = VecDeque::with_capacity(tr_table.len());
+        for start_id in start_ids.iter().copied() {
+            if distances[start_id].is_none() {
+                distances[start_id] = Some(0);
+                queue.push_back(start_id);
+            }
+        }
+        while let Some(id) = queue.pop_front() {
+            let distance = distances[id].unwrap();
+            if id < first_non_final_id || final_at_end[id] {
+                return distance;
             }
-            tr_table_lines.push(Group::new(Delimiter::Bracket, token_line))
+            for target in tr_table[id].iter().copied() {
+                if target < tr_table.len() && distances[target].is_none() {
+                    distances[target] = Some(distance + 1);
+                    queue.push_back(target);
+                }
+            }
+        }
+        0
+    }
+
+    /// Converts the lines of the dense transition table into the sparse ones,
+    /// if the average number of arcs per state is small enough.
+    fn build_sparse_table(lines: &[[usize; 1 << u8::BITS]]) -> Option<Vec<SparseLine>> {
+        let mut sparse_lines = Vec::with_capacity(lines.len());
+        let mut arcs_num = 0;
+        for line in lines {
+            // the most frequent target becomes the default one
+            let mut counts = HashMap::new();
+            for target in line.iter().copied() {
+                *counts.entry(target).or_insert(0usize) += 1;
+            }
+            let default = counts
+                .into_iter()
+                .max_by_key(|&(target, count)| (count, std::cmp::Reverse(target)))
+                .map(|(target, _)| target)
+                .unwrap();
+
+            let mut arcs = Vec::<(u8, u8, usize)>::new();
+            for (byte, target) in line.iter().copied().enumerate() {
+                if target == default {
+                    continue;
+                }
+                let byte = byte as u8;
+                match arcs.last_mut() {
+                    Some((_, last, last_target))
+                        if *last_target == target && *last as usize + 1 == byte as usize =>
+                    {
+                        *last = byte;
+                    }
+                    _ => arcs.push((byte, byte, target)),
+                }
+            }
+            arcs_num += arcs.len();
+            sparse_lines.push((default, arcs));
+        }
+        if arcs_num <= MAX_SPARSE_ARCS_PER_STATE * lines.len() {
+            Some(sparse_lines)
+        } else {
+            None
+        }
+    }
+
+    /// Splits the bytes into equivalence classes, i.e. groups of bytes with
+    /// identical columns in the transition table. Classes are numbered in the
+    /// order of their first bytes.
+    ///
+    /// Returns the class of every byte and the number of classes.
+    fn build_byte_classes(lines: &[[usize; 1 << u8::BITS]]) -> ([u8; 1 << u8::BITS], usize) {
+        let mut class_map = HashMap::new();
+        let mut class_of = [0u8; 1 << u8::BITS];
+        for (byte, class) in class_of.iter_mut().enumerate() {
+            let column: Vec<_> = lines.iter().map(|line| line[byte]).collect();
+            let classes_num = class_map.len();
+            *class = *class_map.entry(column).or_insert(classes_num) as u8;
         }
+        (class_of, class_map.len())
+    }
+
+    pub fn gen_state_machine(&self) -> TokenStream {
+        let tr_table_len: usize = self.tr_table.len();
+        // The invalid state always has its own line of transitions to itself,
+        // so the unchecked lookups of the table stay in bounds even if the
+        // state machine is fed with bytes after it has become invalid.
+        let sink_line = [self.invalid_id; 1 << u8::BITS];
+        let sink_lines = std::slice::from_ref(&sink_line);
+        let lines = self.tr_table.iter().chain(sink_lines);
 
         let state_type = {
             // we need to leave one value for the invalid state
@@ -111,43 +736,253 @@ impl<'a> CodeGen {
         // Number of all possible bytes, i.e. transitions.
         let bytes_num: usize = 1 << u8::BITS;
 
-        // Number of states in the automaton.
-        let states_num: usize = tr_table_len;
+        // Number of states in the automaton including the sink one.
+        let states_num: usize = tr_table_len + sink_lines.len();
 
         let start_state = self.start_id;
+        let text_start_state = self.text_start_id;
+        let final_at_end_states = self
+            .final_at_end
+            .iter()
+            .chain(sink_lines.iter().map(|_| &false));
         let invalid_state = self.invalid_id;
         let first_non_final_state = self.first_non_final_id;
 
+        let sparse_table = if self.sparse_table {
+            Self::build_sparse_table(&lines.clone().copied().collect::<Vec<_>>())
+        } else {
+            None
+        };
+        let (tr_table, next_state) = match sparse_table {
+            Some(sparse_lines) => {
+                let defaults = sparse_lines.iter().map(|(default, _)| {
+                    // to remove suffix
+                    TokenStream::from_str(&default.to_string()).unwrap()
+                });
+                let arcs_lines = sparse_lines.iter().map(|(_, arcs)| {
+                    let arcs = arcs.iter().map(|&(first, last, target)| {
+                        TokenStream::from_str(&format!("({first}, {last}, {target})")).unwrap()
+                    });
+                    quote! { &[#(#arcs),*] }
+                });
+                let tr_table = quote! {
+                    const DEFAULT_TRANSITIONS: [#state_type; Self::STATES_NUM] = [
+                        #(#defaults),*
+                    ];
+
+                    const TRANSITION_ARCS: [&[(u8, u8, #state_type)]; Self::STATES_NUM] = [
+                        #(#arcs_lines),*
+                    ];
+                };
+                let next_state = quote! {
+                    let arcs = *unsafe { Self::TRANSITION_ARCS.get_unchecked(self.state) };
+                    let mut target = *unsafe { Self::DEFAULT_TRANSITIONS.get_unchecked(self.state) };
+                    for &(first, last, arc_target) in arcs {
+                        if byte < first {
+                            break;
+                        }
+                        if byte <= last {
+                            target = arc_target;
+                            break;
+                        }
+                    }
+                    self.state = target as usize;
+                };
+                (tr_table, next_state)
+            }
+            None if self.byte_classes => {
+                let lines: Vec<_> = lines.copied().collect();
+                let (class_of, classes_num) = Self::build_byte_classes(&lines);
+                let mut class_bytes = vec![0usize; classes_num];
+                for (byte, class) in class_of.iter().enumerate().rev() {
+                    class_bytes[*class as usize] = byte;
+                }
+                let mut tr_table_lines = Vec::new();
+                for line in &lines {
+                    let mut token_line = TokenStream::new();
+                    for byte in class_bytes.iter().copied() {
+                        // to remove suffix
+                        let stream = TokenStream::from_str(&format!("{},", line[byte])).unwrap();
+                        token_line.append_all(stream);
+                    }
+                    tr_table_lines.push(Group::new(Delimiter::Bracket, token_line))
+                }
+                let mut class_of_line = TokenStream::new();
+                for class in class_of {
+                    // to remove suffix
+                    let stream = TokenStream::from_str(&format!("{class},")).unwrap();
+                    class_of_line.append_all(stream);
+                }
+                let tr_table = quote! {
+                    const CLASSES_NUM: usize = #classes_num;
+
+                    const CLASS_OF: [u8; #bytes_num] = [#class_of_line];
+
+                    const TRANSITION_TABLE: [[#state_type; Self::CLASSES_NUM]; Self::STATES_NUM] = [
+                        #(#tr_table_lines),*
+                    ];
+                };
+                let next_state = quote! {
+                    let class = Self::CLASS_OF[byte as usize];
+                    self.state = *unsafe {
+                        Self::TRANSITION_TABLE
+                            .get_unchecked(self.state)
+                            .get_unchecked(class as usize)
+                    } as usize;
+                };
+                (tr_table, next_state)
+            }
+            None if self.flat_table => {
+                let mut tr_table_line = TokenStream::new();
+                for num in lines.flatten() {
+                    // to remove suffix
+                    let stream = TokenStream::from_str(&format!("{num},")).unwrap();
+                    tr_table_line.append_all(stream);
+                }
+                let tr_table = quote! {
+                    const STRIDE: usize = #bytes_num;
+
+                    const TRANSITION_TABLE: [#state_type; Self::STATES_NUM * Self::STRIDE] = [
+                        #tr_table_line
+                    ];
+                };
+                let next_state = quote! {
+                    self.state = *unsafe {
+                        Self::TRANSITION_TABLE.get_unchecked(self.state * Self::STRIDE + byte as usize)
+                    } as usize;
+                };
+                (tr_table, next_state)
+            }
+            None => {
+                let mut tr_table_lines = Vec::new();
+                for line in lines {
+                    let mut token_line = TokenStream::new();
+                    for num in line {
+                        // to remove suffix
+                        let stream = TokenStream::from_str(&format!("{num},")).unwrap();
+                        token_line.append_all(stream);
+                    }
+                    tr_table_lines.push(Group::new(Delimiter::Bracket, token_line))
+                }
+                let tr_table = quote! {
+                    const TRANSITION_TABLE: [[#state_type; #bytes_num]; Self::STATES_NUM] = [
+                        #(#tr_table_lines),*
+                    ];
+                };
+                let next_state = quote! {
+                    self.state = *unsafe {
+                        Self::TRANSITION_TABLE
+                            .get_unchecked(self.state)
+                            .get_unchecked(byte as usize)
+                    } as usize;
+                };
+                (tr_table, next_state)
+            }
+        };
+
+        let captures = self.gen_captures(sink_lines.len());
+        let (regs_ops, regs_consts, regs_code, regs_fields, regs_init, ops_before, ops_after) =
+            match captures {
+                Some((regs_ops, regs_consts, regs_code)) => (
+                    regs_ops,
+                    regs_consts,
+                    regs_code,
+                    quote! {
+                        pos: usize,
+                        regs: [Option<usize>; Self::REGS_NUM],
+                    },
+                    quote! {
+                        pos: 0,
+                        regs: Self::INITIAL_REGS,
+                    },
+                    quote! {
+                        let arcs = *unsafe { Self::TRANSITION_OPS.get_unchecked(self.state) };
+                        for &(first, last, ops) in arcs {
+                            if byte < first {
+                                break;
+                            }
+                            if byte <= last {
+                                Self::apply(&mut self.regs, self.pos, ops);
+                                break;
+                            }
+                        }
+                    },
+                    quote! {
+                        self.pos += 1;
+                    },
+                ),
+                None => Default::default(),
+            };
+        let patterns_code = self.gen_patterns(sink_lines.len());
+        let priorities_code = self.gen_priorities(sink_lines.len());
+        let vis = quote!(pub);
+
         quote! {
+            #regs_ops
+
+            /// The automaton of the regex driven one byte at a time. It doesn't
+            /// need the whole haystack, so it can be fed across buffer
+            /// boundaries.
             #[derive(Debug)]
-            struct StateMachine {
+            #vis struct StateMachine {
                 state: usize,
+                #regs_fields
             }
 
             impl StateMachine {
                 const START_STATE: usize = #start_state;
+                const TEXT_START_STATE: usize = #text_start_state;
                 const INVALID_STATE: usize = #invalid_state;
                 const FIRST_NON_FINAL_STATE: usize = #first_non_final_state;
                 const STATES_NUM: usize = #states_num;
 
-                const TRANSITION_TABLE: [[#state_type; #bytes_num]; Self::STATES_NUM] = [
-                    #(#tr_table_lines),*
+                #tr_table
+
+                const FINAL_AT_END_STATES: [bool; Self::STATES_NUM] = [
+                    #(#final_at_end_states),*
                 ];
 
+                #regs_consts
+
                 #[inline]
-                fn new() -> Self {
+                fn new(start: usize) -> Self {
                     Self {
-                        state: Self::START_STATE,
+                        state: if start == 0 {
+                            Self::TEXT_START_STATE
+                        } else {
+                            Self::START_STATE
+                        },
+                        #regs_init
                     }
                 }
 
+                /// Returns the state machine to the start of the text.
+                #[inline]
+                #vis fn reset(&mut self) {
+                    *self = Self::new(0);
+                }
+
+                /// Moves the state machine by the next byte of the haystack.
+                #[inline]
+                #vis fn feed(&mut self, byte: u8) {
+                    self.next(byte);
+                }
+
+                /// Checks if the bytes fed since the start form a match. The
+                /// end of text anchors are not taken into account.
                 #[inline]
-                fn is_final(&self) -> bool {
+                #vis fn is_final(&self) -> bool {
                     self.state < Self::FIRST_NON_FINAL_STATE
                 }
 
                 #[inline]
-                fn is_invalid(&self) -> bool {
+                fn is_final_at_end(&self) -> bool {
+                    !self.is_invalid() && Self::FINAL_AT_END_STATES[self.state]
+                }
+
+                /// Checks if no more bytes can lead to a match.
+                #[inline]
+                #vis fn is_invalid(&self) -> bool {
                     self.state == Self::INVALID_STATE
                 }
 
@@ -155,101 +990,1413 @@ impl<'a> CodeGen {
                 fn next(&mut self, byte: u8) {
                     ::core::debug_assert!(
                         self.state < Self::STATES_NUM,
-                        "transition from invalid state {} is not allowed",
+                        "state {} is out of the transition table",
                         self.state,
                     );
 
-                    self.state = *unsafe {
-                        Self::TRANSITION_TABLE
-                            .get_unchecked(self.state)
-                            .get_unchecked(byte as usize)
-                    } as usize;
+                    #ops_before
+                    #next_state
+                    #ops_after
                 }
+
+                #regs_code
+
+                #patterns_code
+
+                #priorities_code
             }
         }
     }
 
-    pub fn gen_match(&self) -> TokenStream {
-        let vis = quote!(pub);
-        quote! {
-            #[derive(Debug, PartialEq, Eq)]
-            #vis struct Match<'h> {
-                capture: &'h str,
-                start: usize,
-            }
-
-            impl<'h> Match<'h> {
-                #[inline]
-                #vis fn start(&self) -> usize {
-                    self.start
+    /// Generates the register operation type, the register constants, and the
+    /// methods of the state machine applying the operations, if the graph has
+    /// tag groups. `sink_len` is the number of sink states added after the
+    /// states of the graph.
+    fn gen_captures(&self, sink_len: usize) -> Option<(TokenStream, TokenStream, TokenStream)> {
+        let captures = self.captures.as_ref()?;
+        let gen_ops = |ops: &[RegOp]| {
+            let ops = ops.iter().map(|op| match op {
+                // to remove suffix
+                RegOp::Clear(reg) => {
+                    TokenStream::from_str(&format!("RegOp::Clear({reg})")).unwrap()
                 }
-
-                #[inline]
-                #vis fn end(&self) -> usize {
-                    self.start + self.capture.len()
+                RegOp::Write(reg) => {
+                    TokenStream::from_str(&format!("RegOp::Write({reg})")).unwrap()
                 }
+            });
+            quote! { &[#(#ops),*] }
+        };
+        let sink_ops = vec![quote!(&[]); sink_len];
 
-                #[inline]
-                #vis fn len(&self) -> usize {
-                    self.capture.len()
-                }
+        let regs_num = captures.initial_regs.len();
+        let initial_regs = captures.initial_regs.iter().map(|valid| {
+            if *valid {
+                quote!(Some(0))
+            } else {
+                quote!(None)
+            }
+        });
+        let tr_ops_lines = captures.tr_ops.iter().map(|arcs| {
+            let arcs = arcs.iter().map(|(first, last, ops)| {
+                // to remove suffix
+                let first = TokenStream::from_str(&first.to_string()).unwrap();
+                let last = TokenStream::from_str(&last.to_string()).unwrap();
+                let ops = gen_ops(ops);
+                quote! { (#first, #last, #ops) }
+            });
+            quote! { &[#(#arcs),*] }
+        });
+        let final_ops_lines = captures.final_ops.iter().map(|ops| gen_ops(ops));
+        let final_at_end_ops_lines = captures.final_at_end_ops.iter().map(|ops| gen_ops(ops));
 
-                #[inline]
-                #vis fn is_empty(&self) -> bool {
-                    self.capture.is_empty()
-                }
+        let regs_ops = quote! {
+            /// An operation on a register of the state machine.
+            #[derive(Debug, Clone, Copy)]
+            enum RegOp {
+                Clear(usize),
+                Write(usize),
+            }
+        };
+        let regs_consts = quote! {
+            const REGS_NUM: usize = #regs_num;
 
-                #[inline]
-                #vis fn range(&self) -> ::core::ops::Range<usize> {
-                    self.start..self.end()
-                }
+            const INITIAL_REGS: [Option<usize>; Self::REGS_NUM] = [
+                #(#initial_regs),*
+            ];
 
-                #[inline]
-                #vis fn as_str(&self) -> &'h str {
-                    self.capture
-                }
+            const TRANSITION_OPS: [&[(u8, u8, &[RegOp])]; Self::STATES_NUM] = [
+                #(#tr_ops_lines,)*
+                #(#sink_ops,)*
+            ];
 
-                #[inline]
-                #vis fn as_bytes(&self) -> &'h [u8] {
-                    self.as_str().as_bytes()
+            const FINAL_OPS: [&[RegOp]; Self::STATES_NUM] = [
+                #(#final_ops_lines,)*
+                #(#sink_ops,)*
+            ];
+
+            const FINAL_AT_END_OPS: [&[RegOp]; Self::STATES_NUM] = [
+                #(#final_at_end_ops_lines,)*
+                #(#sink_ops,)*
+            ];
+        };
+        let regs_code = quote! {
+            #[inline]
+            fn apply(regs: &mut [Option<usize>; Self::REGS_NUM], pos: usize, ops: &[RegOp]) {
+                for op in ops {
+                    match *op {
+                        RegOp::Clear(reg) => regs[reg] = None,
+                        RegOp::Write(reg) => regs[reg] = Some(pos),
+                    }
                 }
             }
-        }
-    }
 
-    pub fn gen_regex(&self) -> TokenStream {
-        let vis = quote!(pub);
+            /// Returns the registers as they are if the match ends in the
+            /// current state, or at the end of the haystack if `at_end` is
+            /// set.
+            #[inline]
+            fn final_regs(&self, at_end: bool) -> [Option<usize>; Self::REGS_NUM] {
+                let ops = if at_end {
+                    Self::FINAL_AT_END_OPS[self.state]
+                } else {
+                    Self::FINAL_OPS[self.state]
+                };
+                let mut regs = self.regs;
+                Self::apply(&mut regs, self.pos, ops);
+                regs
+            }
+        };
+        Some((regs_ops, regs_consts, regs_code))
+    }
+
+    /// Generates the backtracking resolver of the capture groups, if the
+    /// groups can't be resolved by the registers of the state machine.
+    fn gen_resolver(&self) -> TokenStream {
+        let Some(resolver) = &self.resolver else {
+            return TokenStream::new();
+        };
+        let vec = if self.no_std {
+            quote!(::alloc::vec::Vec)
+        } else {
+            quote!(Vec)
+        };
+        // to remove suffix
+        let usize_lit = |value: usize| TokenStream::from_str(&value.to_string()).unwrap();
+        let ops = resolver.ops.iter().map(|op| match op {
+            Op::Byte(byte) => {
+                let byte = usize_lit(*byte as usize);
+                quote!(ResolverOp::Byte(#byte))
+            }
+            Op::Class(ranges) => {
+                let ranges = ranges.iter().map(|(first, last)| {
+                    let first = usize_lit(*first as usize);
+                    let last = usize_lit(*last as usize);
+                    quote!((#first, #last))
+                });
+                quote!(ResolverOp::Class(&[#(#ranges),*]))
+            }
+            Op::Split(first, second) => {
+                let (first, second) = (usize_lit(*first), usize_lit(*second));
+                quote!(ResolverOp::Split(#first, #second))
+            }
+            Op::Jump(target) => {
+                let target = usize_lit(*target);
+                quote!(ResolverOp::Jump(#target))
+            }
+            Op::Save(slot) => {
+                let slot = usize_lit(*slot);
+                quote!(ResolverOp::Save(#slot))
+            }
+            Op::Clear(slot) => {
+                let slot = usize_lit(*slot);
+                quote!(ResolverOp::Clear(#slot))
+            }
+            Op::AssertStartText => quote!(ResolverOp::AssertStartText),
+            Op::AssertEndText => quote!(ResolverOp::AssertEndText),
+            Op::Match => quote!(ResolverOp::Match),
+        });
+        let ops_num = resolver.ops.len();
+        let entries = resolver.entries.iter().map(|entry| usize_lit(*entry));
+        let entries_num = resolver.entries.len();
+        let slots_num = resolver.slots_num();
         quote! {
+            /// An instruction of the capture resolver.
+            #[derive(Debug, Clone, Copy)]
+            enum ResolverOp {
+                Byte(u8),
+                Class(&'static [(u8, u8)]),
+                Split(usize, usize),
+                Jump(usize),
+                Save(usize),
+                Clear(usize),
+                AssertStartText,
+                AssertEndText,
+                Match,
+            }
+
+            /// A backtracking matcher of the patterns resolving the capture
+            /// groups of a match found by the state machine, whose registers
+            /// can't tell the groups of the patterns apart.
             #[derive(Debug)]
-            pub struct Regex;
+            struct Resolver;
 
-            impl Regex {
+            impl Resolver {
+                const SLOTS_NUM: usize = #slots_num;
+
+                const PROGRAM: [ResolverOp; #ops_num] = [
+                    #(#ops),*
+                ];
+
+                /// The first instruction of every pattern.
+                const ENTRIES: [usize; #entries_num] = [
+                    #(#entries),*
+                ];
+
+                /// Returns the open and close positions of the groups,
+                /// relative to the `start`, on the most preferred path of the
+                /// `pattern` matching exactly `haystack[start..end]`.
+                ///
+                /// Every pair of an instruction and a position is tried at
+                /// most once, so it takes `O(m * n)` time, where `m` is the
+                /// size of the program and `n` is the length of the match.
+                fn resolve(
+                    haystack: &[u8],
+                    start: usize,
+                    end: usize,
+                    pattern: usize,
+                ) -> [Option<usize>; Self::SLOTS_NUM] {
+                    enum Job {
+                        Run(usize, usize),
+                        Restore(usize, Option<usize>),
+                    }
+                    let width = end - start + 1;
+                    let mut visited = #vec::new();
+                    visited.resize((Self::PROGRAM.len() * width).div_ceil(64), 0u64);
+                    let mut slots = [None; Self::SLOTS_NUM];
+                    let mut jobs = #vec::new();
+                    jobs.push(Job::Run(Self::ENTRIES[pattern], start));
+                    while let Some(job) = jobs.pop() {
+                        let (mut pc, mut pos) = match job {
+                            Job::Run(pc, pos) => (pc, pos),
+                            Job::Restore(slot, value) => {
+                                slots[slot] = value;
+                                continue;
+                            }
+                        };
+                        loop {
+                            let key = pc * width + pos - start;
+                            if visited[key / 64] & (1 << (key % 64)) != 0 {
+                                break;
+                            }
+                            visited[key / 64] |= 1 << (key % 64);
+                            match Self::PROGRAM[pc] {
+                                ResolverOp::Byte(byte) => {
+                                    if pos == end || haystack[pos] != byte {
+                                        break;
+                                    }
+                                    pos += 1;
+                                }
+                                ResolverOp::Class(ranges) => {
+                                    if pos == end
+                                        || !ranges
+                                            .iter()
+                                            .any(|(first, last)| (*first..=*last).contains(&haystack[pos]))
+                                    {
+                                        break;
+                                    }
+                                    pos += 1;
+                                }
+                                ResolverOp::Split(first, second) => {
+                                    jobs.push(Job::Run(second, pos));
+                                    pc = first;
+                                    continue;
+                                }
+                                ResolverOp::Jump(target) => {
+                                    pc = target;
+                                    continue;
+                                }
+                                ResolverOp::Save(slot) => {
+                                    jobs.push(Job::Restore(slot, slots[slot]));
+                                    slots[slot] = Some(pos - start);
+                                }
+                                ResolverOp::Clear(slot) => {
+                                    jobs.push(Job::Restore(slot, slots[slot]));
+                                    slots[slot] = None;
+                                }
+                                ResolverOp::AssertStartText => {
+                                    if pos != 0 {
+                                        break;
+                                    }
+                                }
+                                ResolverOp::AssertEndText => {
+                                    if pos != haystack.len() {
+                                        break;
+                                    }
+                                }
+                                ResolverOp::Match => {
+                                    if pos == end {
+                                        return slots;
+                                    }
+                                    break;
+                                }
+                            }
+                            pc += 1;
+                        }
+                    }
+                    // the state machine has matched the span, so the program
+                    // matches it too, and this point isn't reached
+                    [None; Self::SLOTS_NUM]
+                }
+            }
+        }
+    }
+
+    /// Generates the pattern constants and the method of the state machine
+    /// returning the accepted pattern, if the graph has several patterns.
+    /// `sink_len` is the number of sink states added after the states of the
+    /// graph.
+    fn gen_patterns(&self, sink_len: usize) -> TokenStream {
+        let Some(patterns) = &self.patterns else {
+            return TokenStream::new();
+        };
+        let patterns_num = patterns.patterns_num;
+        let sink_patterns = vec![0u32; sink_len];
+        // to remove suffix
+        let unsuffixed = |pattern: &u32| TokenStream::from_str(&pattern.to_string()).unwrap();
+        let final_patterns = patterns
+            .final_patterns
+            .iter()
+            .chain(&sink_patterns)
+            .map(unsuffixed);
+        let final_at_end_patterns = patterns
+            .final_at_end_patterns
+            .iter()
+            .chain(&sink_patterns)
+            .map(unsuffixed);
+        quote! {
+            const PATTERNS_NUM: usize = #patterns_num;
+
+            const FINAL_PATTERNS: [u32; Self::STATES_NUM] = [
+                #(#final_patterns),*
+            ];
+
+            const FINAL_AT_END_PATTERNS: [u32; Self::STATES_NUM] = [
+                #(#final_at_end_patterns),*
+            ];
+
+            /// Returns the pattern accepted if the match ends in the current
+            /// state, or at the end of the haystack if `at_end` is set.
+            #[inline]
+            fn final_pattern(&self, at_end: bool) -> u32 {
+                if at_end {
+                    Self::FINAL_AT_END_PATTERNS[self.state]
+                } else {
+                    Self::FINAL_PATTERNS[self.state]
+                }
+            }
+        }
+    }
+
+    /// Generates the priority constants and the methods of the state machine
+    /// choosing the leftmost-first match, if the alternatives of the graph
+    /// have priorities. `sink_len` is the number of sink states added after
+    /// the states of the graph.
+    fn gen_priorities(&self, sink_len: usize) -> TokenStream {
+        let Some(priorities) = &self.priorities else {
+            return TokenStream::new();
+        };
+        let sink_priorities = vec![u32::MAX; sink_len];
+        // to remove suffix
+        let unsuffixed = |priority: &u32| TokenStream::from_str(&priority.to_string()).unwrap();
+        let final_priorities = priorities
+            .final_priorities
+            .iter()
+            .chain(&sink_priorities)
+            .map(unsuffixed);
+        let final_at_end_priorities = priorities
+            .final_at_end_priorities
+            .iter()
+            .chain(&sink_priorities)
+            .map(unsuffixed);
+        let live_priorities = priorities
+            .live_priorities
+            .iter()
+            .chain(&sink_priorities)
+            .map(unsuffixed);
+        quote! {
+            const FINAL_PRIORITIES: [u32; Self::STATES_NUM] = [
+                #(#final_priorities),*
+            ];
+
+            const FINAL_AT_END_PRIORITIES: [u32; Self::STATES_NUM] = [
+                #(#final_at_end_priorities),*
+            ];
+
+            const LIVE_PRIORITIES: [u32; Self::STATES_NUM] = [
+                #(#live_priorities),*
+            ];
+
+            /// Checks if the match ending in the current state, or at the end
+            /// of the haystack if `at_end` is set, is preferred to the one of
+            /// the `best` priority, and makes it the best one then. A match of
+            /// an earlier listed alternative is preferred, and a longer match
+            /// of the same alternative is.
+            #[inline]
+            fn prefer_final(&self, at_end: bool, best: &mut u32) -> bool {
+                let priority = if at_end {
+                    Self::FINAL_AT_END_PRIORITIES[self.state]
+                } else {
+                    Self::FINAL_PRIORITIES[self.state]
+                };
+                if priority <= *best {
+                    *best = priority;
+                    true
+                } else {
+                    false
+                }
+            }
+
+            /// Checks if no match preferred to the one of the `best` priority
+            /// is reachable from the current state.
+            #[inline]
+            fn is_outrun(&self, best: u32) -> bool {
+                Self::LIVE_PRIORITIES[self.state] > best
+            }
+        }
+    }
+
+    /// Generates the parts of a matching loop choosing the leftmost-first
+    /// match: the initialization, the conditions for the final states in the
+    /// middle and at the end of the haystack, and the check to stop early
+    /// with the `stop` statement. All of them are empty if the alternatives
+    /// have no priorities.
+    fn gen_priority_checks(
+        &self,
+        stop: TokenStream,
+    ) -> (TokenStream, TokenStream, TokenStream, TokenStream) {
+        if self.priorities.is_none() {
+            return Default::default();
+        }
+        (
+            quote!(let mut best_priority = u32::MAX;),
+            quote!(&&state_machine.prefer_final(false, &mut best_priority)),
+            quote!(&&state_machine.prefer_final(true, &mut best_priority)),
+            quote! {
+                if state_machine.is_outrun(best_priority) {
+                    #stop
+                }
+            },
+        )
+    }
+
+    pub fn gen_match(&self) -> TokenStream {
+        let bytes_match = self.gen_bytes_match();
+        if self.bytes_only {
+            return bytes_match;
+        }
+        let vis = quote!(pub);
+        // The generated code can't use the `renc` decoder, because the user's
+        // crate doesn't depend on it. But the haystack is a `str`, so it's
+        // already valid UTF-8, and `chars` decodes it exactly like
+        // `Utf8Encoder::decode_next` does, without any error to handle.
+        let (haystack_field, char_offsets) = if self.char_offsets {
+            (
+                quote!(haystack: &'h str,),
+                quote! {
+                    /// Returns the offset of the match start in chars.
+                    ///
+                    /// The chars are counted from the haystack start on every
+                    /// call, so it takes `O(start)` time.
+                    #[inline]
+                    #vis fn char_start(&self) -> usize {
+                        self.haystack[..self.start].chars().count()
+                    }
+
+                    /// Returns the offset of the match end in chars.
+                    ///
+                    /// The chars are counted from the haystack start on every
+                    /// call, so it takes `O(end)` time.
+                    #[inline]
+                    #vis fn char_end(&self) -> usize {
+                        self.char_start() + self.capture.chars().count()
+                    }
+                },
+            )
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        };
+        let gen_label = |label: &GroupLabel| match label {
+            GroupLabel::Index(index) => {
+                // to remove suffix
+                let index = TokenStream::from_str(&index.to_string()).unwrap();
+                quote!(GroupLabel::Index(#index))
+            }
+            GroupLabel::Name(name) => {
+                let name = name.as_ref();
+                quote!(GroupLabel::Name(#name))
+            }
+        };
+        let (regs_field, groups) = match (&self.captures, &self.resolver) {
+            (Some(captures), _) => {
+                let gen_tag_pos = |(reg, offset, guards): &TagPos| {
+                    // to remove suffix
+                    TokenStream::from_str(&format!("({reg}, {offset}, &{guards:?})")).unwrap()
+                };
+                let groups_num = captures.groups.len();
+                let groups = captures.groups.iter().map(|(label, open_pos, close_pos)| {
+                    let label = gen_label(label);
+                    let open_pos = gen_tag_pos(open_pos);
+                    let close_pos = gen_tag_pos(close_pos);
+                    quote! { (#label, #open_pos, #close_pos) }
+                });
+                (
+                    quote!(regs: [Option<usize>; StateMachine::REGS_NUM],),
+                    quote! {
+                        /// Labels of the groups with positions of their open
+                        /// and close tags. A tag position is the register with
+                        /// the base position, the offset from it, and the
+                        /// registers that must be valid for the tag.
+                        #[allow(clippy::type_complexity)]
+                        const GROUPS: [(GroupLabel<'static>, (usize, usize, &'static [usize]), (usize, usize, &'static [usize])); #groups_num] = [
+                            #(#groups),*
+                        ];
+
+                        /// Returns the part of the match captured by the group
+                        /// with the `label`, or `None` if the group didn't
+                        /// participate in the match or there is no such group.
+                        #vis fn group<'l>(&self, label: impl Into<GroupLabel<'l>>) -> Option<&'h str> {
+                            let label = label.into();
+                            let (_, open_pos, close_pos) =
+                                Self::GROUPS.iter().find(|(group_label, _, _)| *group_label == label)?;
+                            let start = self.tag_pos(*open_pos)?;
+                            let end = self.tag_pos(*close_pos)?;
+                            self.capture.get(start..end)
+                        }
+
+                        #[inline]
+                        fn tag_pos(&self, (reg, offset, guards): (usize, usize, &[usize])) -> Option<usize> {
+                            if guards.iter().any(|guard| self.regs[*guard].is_none()) {
+                                return None;
+                            }
+                            self.regs[reg].map(|pos| pos + offset)
+                        }
+                    },
+                )
+            }
+            (None, Some(resolver)) => {
+                let groups_num = resolver.groups.len();
+                let groups = resolver.groups.iter().map(gen_label);
+                (
+                    quote!(slots: [Option<usize>; Resolver::SLOTS_NUM],),
+                    quote! {
+                        /// Labels of the groups in the order of their slots.
+                        const GROUPS: [GroupLabel<'static>; #groups_num] = [
+                            #(#groups),*
+                        ];
+
+                        /// Returns the part of the match captured by the group
+                        /// with the `label`, or `None` if the group didn't
+                        /// participate in the match or there is no such group.
+                        #vis fn group<'l>(&self, label: impl Into<GroupLabel<'l>>) -> Option<&'h str> {
+                            let label = label.into();
+                            let index = Self::GROUPS.iter().position(|group_label| *group_label == label)?;
+                            let start = self.slots[index * 2]?;
+                            let end = self.slots[index * 2 + 1]?;
+                            self.capture.get(start..end)
+                        }
+                    },
+                )
+            }
+            (None, None) => Default::default(),
+        };
+        let group_label = if self.captures.is_some() || self.resolver.is_some() {
+            quote! {
+                /// A label of a capture group: a number or a name.
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                #vis enum GroupLabel<'l> {
+                    Index(u32),
+                    Name(&'l str),
+                }
+
+                impl From<u32> for GroupLabel<'_> {
+                    #[inline]
+                    fn from(index: u32) -> Self {
+                        Self::Index(index)
+                    }
+                }
+
+                impl<'l> From<&'l str> for GroupLabel<'l> {
+                    #[inline]
+                    fn from(name: &'l str) -> Self {
+                        Self::Name(name)
+                    }
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+        let resolver = self.gen_resolver();
+        let (pattern_field, pattern) = if self.patterns.is_some() {
+            (
+                quote!(pattern: u32,),
+                quote! {
+                    /// Returns the index of the pattern accepted by the match.
+                    #[inline]
+                    #vis fn pattern(&self) -> usize {
+                        self.pattern as usize
+                    }
+                },
+            )
+        } else {
+            Default::default()
+        };
+        quote! {
+            #group_label
+
+            #resolver
+
+            #[derive(Debug, PartialEq, Eq)]
+            #vis struct Match<'h> {
+                capture: &'h str,
+                start: usize,
+                #haystack_field
+                #regs_field
+                #pattern_field
+            }
+
+            impl<'h> Match<'h> {
                 #[inline]
-                #vis fn new() -> Self {
-                    Self
+                #vis fn start(&self) -> usize {
+                    self.start
+                }
+
+                #[inline]
+                #vis fn end(&self) -> usize {
+                    self.start + self.capture.len()
+                }
+
+                #[inline]
+                #vis fn len(&self) -> usize {
+                    self.capture.len()
+                }
+
+                #[inline]
+                #vis fn is_empty(&self) -> bool {
+                    self.capture.is_empty()
+                }
+
+                #[inline]
+                #vis fn range(&self) -> ::core::ops::Range<usize> {
+                    self.start..self.end()
+                }
+
+                #[inline]
+                #vis fn as_str(&self) -> &'h str {
+                    self.capture
+                }
+
+                #[inline]
+                #vis fn as_bytes(&self) -> &'h [u8] {
+                    self.as_str().as_bytes()
+                }
+
+                #char_offsets
+
+                #groups
+
+                #pattern
+            }
+
+            #bytes_match
+        }
+    }
+
+    /// Generates the `BytesMatch` struct returned by the methods matching a
+    /// byte slice.
+    fn gen_bytes_match(&self) -> TokenStream {
+        let vis = quote!(pub);
+        quote! {
+            /// A match in a byte slice, which isn't required to be valid
+            /// UTF-8. It is returned by the [`Regex::match_at_bytes`] method.
+            #[derive(Debug, PartialEq, Eq)]
+            #vis struct BytesMatch<'h> {
+                capture: &'h [u8],
+                start: usize,
+            }
+
+            impl<'h> BytesMatch<'h> {
+                #[inline]
+                #vis fn start(&self) -> usize {
+                    self.start
+                }
+
+                #[inline]
+                #vis fn end(&self) -> usize {
+                    self.start + self.capture.len()
+                }
+
+                #[inline]
+                #vis fn len(&self) -> usize {
+                    self.capture.len()
+                }
+
+                #[inline]
+                #vis fn is_empty(&self) -> bool {
+                    self.capture.is_empty()
+                }
+
+                #[inline]
+                #vis fn range(&self) -> ::core::ops::Range<usize> {
+                    self.start..self.end()
+                }
+
+                #[inline]
+                #vis fn as_bytes(&self) -> &'h [u8] {
+                    self.capture
+                }
+            }
+        }
+    }
+
+    pub fn gen_match_error(&self) -> TokenStream {
+        if !self.dead_state_errors {
+            return TokenStream::new();
+        }
+        let vis = quote!(pub);
+        quote! {
+            /// An error returned when the haystack can't be matched.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #vis struct MatchError {
+                offset: usize,
+                byte: Option<u8>,
+            }
+
+            impl MatchError {
+                /// Returns the offset of the byte that had no valid transition,
+                /// or the length of the haystack if it ended too early.
+                #[inline]
+                #vis fn offset(&self) -> usize {
+                    self.offset
+                }
+
+                /// Returns the value of the byte that had no valid transition,
+                /// or `None` if the haystack ended too early.
+                #[inline]
+                #vis fn byte(&self) -> Option<u8> {
+                    self.byte
+                }
+            }
+
+            impl ::core::fmt::Display for MatchError {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    match self.byte {
+                        Some(byte) => write!(f, "unexpected byte {byte:#04x} at offset {}", self.offset),
+                        None => write!(f, "unexpected end of haystack at offset {}", self.offset),
+                    }
                 }
+            }
+
+            impl ::core::error::Error for MatchError {}
+        }
+    }
 
-                #vis fn match_at<'h>(&mut self, haystack: &'h str, start: usize) -> Option<Match<'h>>{
-                    let mut state_machine = StateMachine::new();
+    fn gen_match_at(&self) -> TokenStream {
+        let vis = quote!(pub);
+        let haystack_field = if self.char_offsets {
+            quote!(haystack,)
+        } else {
+            TokenStream::new()
+        };
+        let (mut init_final, mut save_final, mut save_end_final, mut final_fields) =
+            if self.captures.is_some() {
+                (
+                    quote!(let mut final_regs = StateMachine::INITIAL_REGS;),
+                    quote!(final_regs = state_machine.final_regs(false);),
+                    quote!(final_regs = state_machine.final_regs(true);),
+                    quote!(regs: final_regs,),
+                )
+            } else {
+                Default::default()
+            };
+        let (init_priority, prefer_final, prefer_end_final, outrun_check) =
+            self.gen_priority_checks(quote!(break;));
+        init_final.extend(init_priority);
+        if self.patterns.is_some() {
+            init_final.extend(quote!(let mut final_pattern = 0;));
+            save_final.extend(quote!(final_pattern = state_machine.final_pattern(false);));
+            save_end_final.extend(quote!(final_pattern = state_machine.final_pattern(true);));
+            final_fields.extend(quote!(pattern: final_pattern,));
+        }
+        if self.resolver.is_some() {
+            let pattern = if self.patterns.is_some() {
+                quote!(final_pattern as usize)
+            } else {
+                quote!(0)
+            };
+            final_fields.extend(quote! {
+                slots: Resolver::resolve(haystack.as_bytes(), start, start + index, #pattern),
+            });
+        }
+        if let Some(match_len) = self.match_len
+            && !self.dead_state_errors
+            && self.captures.is_none()
+            && self.resolver.is_none()
+            && self.patterns.is_none()
+            && self.priorities.is_none()
+        {
+            return quote! {
+                /// Returns the match starting exactly at the `start` position
+                /// of the haystack. All the matches are `MATCH_LEN` bytes
+                /// long, so only these bytes are passed to the state machine.
+                ///
+                /// The `^` anchor matches only if `start` is `0`, and the `$`
+                /// anchor matches only at the end of the haystack.
+                #vis fn match_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>>{
+                    let bytes = haystack[start..].as_bytes();
+                    if bytes.len() < #match_len {
+                        return None;
+                    }
+                    let mut state_machine = StateMachine::new(start);
+                    for byte in &bytes[..#match_len] {
+                        state_machine.next(*byte);
+                        if state_machine.is_invalid() {
+                            return None;
+                        }
+                    }
+                    let is_final = state_machine.is_final()
+                        || bytes.len() == #match_len && state_machine.is_final_at_end();
+                    is_final.then(|| Match {
+                        capture: &haystack[start..start + #match_len],
+                        start,
+                        #haystack_field
+                    })
+                }
+            };
+        }
+        if !self.dead_state_errors {
+            let (invalid_check, sink_doc) = if self.sink_state {
+                let doc = quote! {
+                    ///
+                    /// The state machine reads the whole rest of the haystack,
+                    /// so searching by calling this method at every position
+                    /// takes quadratic time.
+                };
+                (TokenStream::new(), doc)
+            } else {
+                let check = quote! {
+                    if state_machine.is_invalid() {
+                        break;
+                    }
+                };
+                (check, TokenStream::new())
+            };
+            return quote! {
+                /// Returns the longest match starting exactly at the `start`
+                /// position of the haystack.
+                ///
+                /// The `^` anchor matches only if `start` is `0`, and the `$`
+                /// anchor matches only at the end of the haystack.
+                #sink_doc
+                #vis fn match_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>>{
+                    let mut state_machine = StateMachine::new(start);
                     let mut final_index = None;
-                    if state_machine.is_final() {
+                    #init_final
+                    if state_machine.is_final() #prefer_final {
                         final_index = Some(0);
+                        #save_final
                     }
-                    for (i, byte) in haystack[start..].as_bytes().iter().enumerate() {
+                    let bytes = haystack[start..].as_bytes();
+                    for (i, byte) in bytes.iter().enumerate() {
                         state_machine.next(*byte);
-                        if state_machine.is_final() {
+                        if state_machine.is_final() #prefer_final {
                             final_index = Some(i + 1);
+                            #save_final
                         }
-                        if state_machine.is_invalid() {
-                            break;
-                        }
+                        #invalid_check
+                        #outrun_check
+                    }
+                    if state_machine.is_final_at_end() #prefer_end_final {
+                        final_index = Some(bytes.len());
+                        #save_end_final
                     }
                     final_index.map(|index| Match {
                         capture: &haystack[start..start + index],
                         start,
+                        #haystack_field
+                        #final_fields
                     })
                 }
+            };
+        }
+        quote! {
+            /// Returns the longest match starting exactly at the `start`
+            /// position of the haystack.
+            ///
+            /// If there is no match, returns an error naming the byte that
+            /// had no valid transition.
+            ///
+            /// The `^` anchor matches only if `start` is `0`, and the `$`
+            /// anchor matches only at the end of the haystack.
+            #vis fn match_at<'h>(&self, haystack: &'h str, start: usize) -> Result<Match<'h>, MatchError>{
+                let mut state_machine = StateMachine::new(start);
+                let mut final_index = None;
+                let mut dead_index = None;
+                #init_final
+                if state_machine.is_final() #prefer_final {
+                    final_index = Some(0);
+                    #save_final
+                }
+                let bytes = haystack[start..].as_bytes();
+                for (i, byte) in bytes.iter().enumerate() {
+                    state_machine.next(*byte);
+                    if state_machine.is_final() #prefer_final {
+                        final_index = Some(i + 1);
+                        #save_final
+                    }
+                    if state_machine.is_invalid() {
+                        dead_index = Some(i);
+                        break;
+                    }
+                    #outrun_check
+                }
+                if state_machine.is_final_at_end() #prefer_end_final {
+                    final_index = Some(bytes.len());
+                    #save_end_final
+                }
+                match final_index {
+                    Some(index) => Ok(Match {
+                        capture: &haystack[start..start + index],
+                        start,
+                        #haystack_field
+                        #final_fields
+                    }),
+                    None => Err(MatchError {
+                        offset: start + dead_index.unwrap_or(bytes.len()),
+                        byte: dead_index.map(|i| bytes[i]),
+                    }),
+                }
+            }
+        }
+    }
+
+    fn gen_match_segments(&self) -> TokenStream {
+        let vis = quote!(pub);
+        let (init_priority, prefer_final, prefer_end_final, outrun_check) =
+            self.gen_priority_checks(quote!(break 'segments;));
+        quote! {
+            /// Returns the global range of the longest match starting exactly
+            /// at the `start` position of the haystack that is the
+            /// concatenation of the `segments`. The state machine runs across
+            /// the segment boundaries, so the segments are never copied.
+            ///
+            /// The `^` anchor matches only if `start` is `0`, and the `$`
+            /// anchor matches only at the end of the last segment.
+            #vis fn match_segments<'h>(&self, segments: &[&'h [u8]], start: usize) -> Option<(usize, usize)> {
+                let mut state_machine = StateMachine::new(start);
+                #init_priority
+                let mut final_index = if state_machine.is_final() #prefer_final {
+                    Some(0)
+                } else {
+                    None
+                };
+                let mut offset = 0;
+                let mut index = 0;
+                'segments: for segment in segments {
+                    let skip = start.saturating_sub(offset).min(segment.len());
+                    offset += segment.len();
+                    for byte in &segment[skip..] {
+                        state_machine.next(*byte);
+                        index += 1;
+                        if state_machine.is_final() #prefer_final {
+                            final_index = Some(index);
+                        }
+                        if state_machine.is_invalid() {
+                            break 'segments;
+                        }
+                        #outrun_check
+                    }
+                }
+                if offset < start {
+                    return None;
+                }
+                if state_machine.is_final_at_end() #prefer_end_final {
+                    final_index = Some(index);
+                }
+                final_index.map(|index| (start, start + index))
+            }
+        }
+    }
+
+    fn gen_match_at_bytes(&self) -> TokenStream {
+        let vis = quote!(pub);
+        let (init_priority, prefer_final, prefer_end_final, outrun_check) =
+            self.gen_priority_checks(quote!(break;));
+        let utf8_doc = if self.bytes_only {
+            TokenStream::new()
+        } else {
+            quote! {
+                /// Unlike `match_at`, the haystack isn't required to be valid
+                /// UTF-8, and the `start` isn't required to be at a char
+                /// boundary.
+            }
+        };
+        quote! {
+            /// Returns the longest match starting exactly at the `start`
+            /// position of the byte haystack.
+            #utf8_doc
+            ///
+            /// The `^` anchor matches only if `start` is `0`, and the `$`
+            /// anchor matches only at the end of the haystack.
+            #vis fn match_at_bytes<'h>(&self, haystack: &'h [u8], start: usize) -> Option<BytesMatch<'h>> {
+                let mut state_machine = StateMachine::new(start);
+                #init_priority
+                let mut final_index = if state_machine.is_final() #prefer_final {
+                    Some(0)
+                } else {
+                    None
+                };
+                let bytes = haystack.get(start..)?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    state_machine.next(*byte);
+                    if state_machine.is_final() #prefer_final {
+                        final_index = Some(i + 1);
+                    }
+                    if state_machine.is_invalid() {
+                        break;
+                    }
+                    #outrun_check
+                }
+                if state_machine.is_final_at_end() #prefer_end_final {
+                    final_index = Some(bytes.len());
+                }
+                final_index.map(|index| BytesMatch {
+                    capture: &bytes[..index],
+                    start,
+                })
             }
         }
     }
+
+    pub fn gen_regex(&self) -> TokenStream {
+        let vis = quote!(pub);
+        let min_len = self.min_len;
+        let match_len = match self.match_len {
+            Some(len) => quote!(Some(#len)),
+            None => quote!(None),
+        };
+        let match_at = self.gen_match_at();
+        let match_at_bytes = self.gen_match_at_bytes();
+        let match_segments = self.gen_match_segments();
+        if self.bytes_only {
+            return quote! {
+                #[derive(Debug)]
+                pub struct Regex;
+
+                impl Regex {
+                    #[inline]
+                    #vis fn new() -> Self {
+                        Self
+                    }
+
+                    /// Returns the state machine at the start of the text to
+                    /// be fed byte by byte.
+                    #[inline]
+                    #vis fn state_machine(&self) -> StateMachine {
+                        StateMachine::new(0)
+                    }
+
+                    #match_at_bytes
+
+                    #match_segments
+                }
+            };
+        }
+        let (vec, string) = if self.no_std {
+            (quote!(::alloc::vec::Vec), quote!(::alloc::string::String))
+        } else {
+            (quote!(Vec), quote!(String))
+        };
+        let find_iter = self.gen_find_iter();
+        let prefix_iter = self.gen_prefix_iter();
+        let to_option = if self.dead_state_errors {
+            quote!(.ok())
+        } else {
+            TokenStream::new()
+        };
+        quote! {
+            #[derive(Debug)]
+            pub struct Regex;
+
+            impl Regex {
+                /// Minimal length of a match in bytes.
+                const MIN_LEN: usize = #min_len;
+
+                /// Exact length of every match in bytes, if all the matches
+                /// have the same length.
+                const MATCH_LEN: Option<usize> = #match_len;
+
+                #[inline]
+                #vis fn new() -> Self {
+                    Self
+                }
+
+                /// Returns the state machine at the start of the text to be
+                /// fed byte by byte.
+                #[inline]
+                #vis fn state_machine(&self) -> StateMachine {
+                    StateMachine::new(0)
+                }
+
+                #match_at
+
+                #match_at_bytes
+
+                #match_segments
+
+                /// Returns the leftmost match in the haystack, trying every
+                /// char boundary in order as the start of the match. A pattern
+                /// matching the empty string matches at position `0`.
+                #[inline]
+                #vis fn find<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+                    self.find_iter(haystack).next()
+                }
+
+                /// Returns an iterator over all non-overlapping leftmost-longest
+                /// matches in the haystack.
+                #[inline]
+                #vis fn find_iter<'h>(&self, haystack: &'h str) -> Matches<'h> {
+                    Matches {
+                        haystack,
+                        start: 0,
+                        last_end: None,
+                    }
+                }
+
+                /// Returns an iterator over the matches of all the lengths
+                /// starting exactly at the `start` position, from the shortest
+                /// to the longest, unlike `match_at` returning only the
+                /// longest one.
+                #[inline]
+                #vis fn prefixes<'h>(&self, haystack: &'h str, start: usize) -> Prefixes<'h> {
+                    Prefixes {
+                        haystack,
+                        start,
+                        len: 0,
+                        state_machine: StateMachine::new(start),
+                        started: false,
+                        ended: false,
+                    }
+                }
+
+                /// Returns the length of the longest match starting at every
+                /// position of the haystack, or `None` if there is no match
+                /// there. Positions inside a multi-byte char never match.
+                ///
+                /// The state machine is run from every position separately, so
+                /// the complexity is `O(n * m)`, where `n` is the length of the
+                /// haystack and `m` is the longest distance the state machine
+                /// runs before it gets into the invalid state.
+                #vis fn match_lengths(&self, haystack: &str) -> #vec<Option<usize>> {
+                    (0..haystack.len())
+                        .map(|start| {
+                            if haystack.is_char_boundary(start) {
+                                self.match_at(haystack, start)#to_option.map(|m| m.len())
+                            } else {
+                                None
+                            }
+                        })
+                        .collect()
+                }
+
+                /// Returns all non-overlapping leftmost-longest matches in the
+                /// haystack.
+                #vis fn find_all<'h>(&self, haystack: &'h str) -> #vec<Match<'h>> {
+                    // there are at most `len / MIN_LEN + 1` matches, but the
+                    // haystack may have none, so the hint is bounded
+                    let capacity = (haystack.len() / Self::MIN_LEN.max(1) + 1).min(16);
+                    let mut matches = #vec::with_capacity(capacity);
+                    matches.extend(self.find_iter(haystack));
+                    matches
+                }
+
+                /// Replaces at most `limit` non-overlapping leftmost-longest
+                /// matches in the haystack with the `replacement`, or all of
+                /// them if `limit` is `0`. The rest of the haystack is copied
+                /// as is.
+                #vis fn replacen(&self, haystack: &str, replacement: &str, limit: usize) -> #string {
+                    let limit = if limit == 0 { usize::MAX } else { limit };
+                    let mut result = #string::with_capacity(haystack.len());
+                    let mut last_end = 0;
+                    for m in self.find_iter(haystack).take(limit) {
+                        result.push_str(&haystack[last_end..m.start()]);
+                        result.push_str(replacement);
+                        last_end = m.end();
+                    }
+                    result.push_str(&haystack[last_end..]);
+                    result
+                }
+            }
+
+            #find_iter
+
+            #prefix_iter
+        }
+    }
+
+    /// Generates the `Lexer` struct keeping a value for every pattern, if the
+    /// graph has several patterns and the `Regex` isn't bytes-only.
+    pub fn gen_lexer(&self) -> TokenStream {
+        if self.patterns.is_none() || self.bytes_only {
+            return TokenStream::new();
+        }
+        let vis = quote!(pub);
+        let result = if self.dead_state_errors {
+            quote!(Result<(Match<'h>, &T), MatchError>)
+        } else {
+            quote!(Option<(Match<'h>, &T)>)
+        };
+        quote! {
+            /// A matcher of several patterns, each of them with an associated
+            /// value.
+            #[derive(Debug)]
+            #vis struct Lexer<T> {
+                values: [T; StateMachine::PATTERNS_NUM],
+            }
+
+            impl<T> Lexer<T> {
+                /// Creates a lexer with the values of the patterns in the
+                /// order of the patterns.
+                #[inline]
+                #vis fn new(values: [T; StateMachine::PATTERNS_NUM]) -> Self {
+                    Self { values }
+                }
+
+                /// Returns the longest match starting exactly at the `start`
+                /// position of the haystack, and the value of its pattern. If
+                /// several patterns match the same longest string, the first
+                /// of them wins.
+                #vis fn match_at<'h>(&self, haystack: &'h str, start: usize) -> #result {
+                    Regex.match_at(haystack, start).map(|m| {
+                        let value = &self.values[m.pattern()];
+                        (m, value)
+                    })
+                }
+
+                /// Returns the regex matching any of the patterns.
+                #[inline]
+                #vis fn regex(&self) -> &Regex {
+                    &Regex
+                }
+            }
+        }
+    }
+
+    /// Generates the `Prefixes` iterator yielding a match every time the
+    /// state machine gets into a final state while it consumes the haystack
+    /// from a fixed start. It stops at the invalid state or at the end of the
+    /// haystack.
+    pub fn gen_prefix_iter(&self) -> TokenStream {
+        let vis = quote!(pub);
+        let mut final_fields = TokenStream::new();
+        if self.char_offsets {
+            final_fields.extend(quote!(haystack: self.haystack,));
+        }
+        if self.captures.is_some() {
+            final_fields.extend(quote!(regs: self.state_machine.final_regs(at_end),));
+        }
+        if self.patterns.is_some() {
+            final_fields.extend(quote!(pattern: self.state_machine.final_pattern(at_end),));
+        }
+        if self.resolver.is_some() {
+            let pattern = if self.patterns.is_some() {
+                quote!(self.state_machine.final_pattern(at_end) as usize)
+            } else {
+                quote!(0)
+            };
+            final_fields.extend(quote! {
+                slots: Resolver::resolve(
+                    self.haystack.as_bytes(),
+                    self.start,
+                    self.start + self.len,
+                    #pattern,
+                ),
+            });
+        }
+        quote! {
+            /// An iterator over the matches of all the lengths starting at a
+            /// fixed position of a haystack, i.e. over all its accepted
+            /// prefixes.
+            ///
+            /// It is created by the [`Regex::prefixes`] method.
+            #[derive(Debug)]
+            #vis struct Prefixes<'h> {
+                haystack: &'h str,
+                start: usize,
+                len: usize,
+                state_machine: StateMachine,
+                started: bool,
+                ended: bool,
+            }
+
+            impl<'h> Prefixes<'h> {
+                #[inline]
+                fn new_match(&self, #[allow(unused_variables)] at_end: bool) -> Match<'h> {
+                    Match {
+                        capture: &self.haystack[self.start..self.start + self.len],
+                        start: self.start,
+                        #final_fields
+                    }
+                }
+            }
+
+            impl<'h> Iterator for Prefixes<'h> {
+                type Item = Match<'h>;
+
+                fn next(&mut self) -> Option<Match<'h>> {
+                    if !self.started {
+                        self.started = true;
+                        if self.state_machine.is_final() {
+                            return Some(self.new_match(false));
+                        }
+                    }
+                    let bytes = self.haystack.as_bytes();
+                    while self.start + self.len < bytes.len() && !self.state_machine.is_invalid() {
+                        self.state_machine.next(bytes[self.start + self.len]);
+                        self.len += 1;
+                        if self.state_machine.is_final() {
+                            return Some(self.new_match(false));
+                        }
+                    }
+                    if !self.ended {
+                        self.ended = true;
+                        // the match of this length is already yielded if the
+                        // state is final regardless of the end
+                        if self.start + self.len == bytes.len()
+                            && self.state_machine.is_final_at_end()
+                            && !self.state_machine.is_final()
+                        {
+                            return Some(self.new_match(true));
+                        }
+                    }
+                    None
+                }
+            }
+
+            impl ::core::iter::FusedIterator for Prefixes<'_> {}
+        }
+    }
+
+    pub fn gen_find_iter(&self) -> TokenStream {
+        let vis = quote!(pub);
+        let found = if self.dead_state_errors {
+            quote!(Ok(m))
+        } else {
+            quote!(Some(m))
+        };
+        let (doc, empty_start, miss) = if self.anchored_start {
+            (
+                quote! {
+                    ///
+                    /// The iteration stops at the first position without a
+                    /// match, after an empty match, or at a skipped empty
+                    /// match, so the matches are contiguous and start at the
+                    /// beginning of the haystack.
+                },
+                quote!(self.haystack.len() + 1),
+                quote! {
+                    self.start = self.haystack.len() + 1;
+                    return None;
+                },
+            )
+        } else {
+            (
+                TokenStream::new(),
+                quote!(next_char_start),
+                quote!(self.start = next_char_start;),
+            )
+        };
+        quote! {
+            /// An iterator over all non-overlapping leftmost-longest matches
+            /// in a haystack.
+            ///
+            /// After an empty match, the search goes on from the next char.
+            /// An empty match ending where the previous match ends is
+            /// skipped, like in the `regex` crate, e.g. `a*` over `"bab"`
+            /// yields `0..0`, `1..2` and `3..3`, but not `2..2`.
+            #doc
+            ///
+            /// It is created by the [`Regex::find_iter`] method.
+            #[derive(Debug)]
+            #vis struct Matches<'h> {
+                haystack: &'h str,
+                start: usize,
+                last_end: Option<usize>,
+            }
+
+            impl<'h> Iterator for Matches<'h> {
+                type Item = Match<'h>;
+
+                fn next(&mut self) -> Option<Match<'h>> {
+                    while self.start <= self.haystack.len() {
+                        let start = self.start;
+                        let next_char_start = start
+                            + self.haystack[start..]
+                                .chars()
+                                .next()
+                                .map_or(1, char::len_utf8);
+                        if let #found = Regex.match_at(self.haystack, start)
+                            && !(m.is_empty() && self.last_end == Some(m.end()))
+                        {
+                            self.start = if m.is_empty() { #empty_start } else { m.end() };
+                            self.last_end = Some(m.end());
+                            return Some(m);
+                        }
+                        #miss
+                    }
+                    None
+                }
+            }
+
+            impl ::core::iter::FusedIterator for Matches<'_> {}
+        }
+    }
 }
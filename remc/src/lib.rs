@@ -1,5 +1,6 @@
 mod codegen;
 mod regex;
+mod resolver;
 
 #[cfg(test)]
 mod utest;
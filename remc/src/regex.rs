@@ -1,42 +1,233 @@
 use crate::codegen::CodeGen;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
-use regr::{Arena, Graph, Translator};
-use resy::{Parser, enc::Utf8Encoder};
-use syn::{LitStr, Result};
+use regr::{Arena, Graph, Inst, Translator};
+use resy::enc::{Latin1Encoder, Utf8Encoder};
+use resy::{Hir, Parser};
+use std::ops::Range;
+use syn::parse::{Parse, ParseStream};
+use syn::{Expr, Ident, LitStr, Result, Token};
 
-pub(crate) fn re_impl(input: TokenStream2) -> Result<TokenStream2> {
-    let lit = syn::parse2::<LitStr>(input)?;
-    if !lit.suffix().is_empty() {
-        let v = lit.token().to_string();
-        let loc = v.len() - lit.suffix().len()..v.len();
-        let span = lit.token().subspan(loc).unwrap_or_else(|| lit.span());
-        return Err(syn::Error::new(
-            span,
-            "suffixes for string literals are not allowed",
-        ));
+/// Input of the `re!` macro: a pattern literal, or comma-separated patterns
+/// with values in the `"pattern" => value` form, optionally followed by
+/// comma-separated flags.
+struct ReInput {
+    patterns: Vec<(LitStr, Option<Expr>)>,
+    strict: bool,
+    case_insensitive: bool,
+    sink: bool,
+    sparse: bool,
+    classes: bool,
+    flat: bool,
+    anchored_start: bool,
+    leftmost_first: bool,
+    no_std: bool,
+    latin1: bool,
+}
+
+impl Parse for ReInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lit = input.parse()?;
+        let with_values = input.peek(Token![=>]);
+        let mut patterns = vec![(lit, Self::parse_value(input, with_values)?)];
+        let mut strict = false;
+        let mut case_insensitive = false;
+        let mut sink = false;
+        let mut sparse = false;
+        let mut classes = false;
+        let mut flat = false;
+        let mut anchored_start = false;
+        let mut leftmost_first = false;
+        let mut no_std = false;
+        let mut latin1 = false;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            if with_values && input.peek(LitStr) {
+                let lit = input.parse()?;
+                patterns.push((lit, Self::parse_value(input, with_values)?));
+                continue;
+            }
+            let flag = input.parse::<Ident>()?;
+            match flag.to_string().as_str() {
+                "strict" => strict = true,
+                "i" => case_insensitive = true,
+                "sink" => sink = true,
+                "sparse" => sparse = true,
+                "classes" => classes = true,
+                "flat" => flat = true,
+                "anchored_start" => anchored_start = true,
+                "first" => leftmost_first = true,
+                "no_std" => no_std = true,
+                "latin1" if with_values => {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        "flag `latin1` can't be used with values",
+                    ));
+                }
+                "latin1" => latin1 = true,
+                _ => {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        format!("unknown flag `{flag}`"),
+                    ));
+                }
+            }
+        }
+        Ok(ReInput {
+            patterns,
+            strict,
+            case_insensitive,
+            sink,
+            sparse,
+            classes,
+            flat,
+            anchored_start,
+            leftmost_first,
+            no_std,
+            latin1,
+        })
     }
+}
+
+impl ReInput {
+    /// Parses the `=> value` part of a pattern if the patterns have values.
+    fn parse_value(input: ParseStream, with_values: bool) -> Result<Option<Expr>> {
+        if !with_values {
+            return Ok(None);
+        }
+        input.parse::<Token![=>]>()?;
+        Ok(Some(input.parse()?))
+    }
+}
+
+pub(crate) fn re_impl(input: TokenStream2) -> Result<TokenStream2> {
+    let ReInput {
+        patterns,
+        strict,
+        case_insensitive,
+        sink,
+        sparse,
+        classes,
+        flat,
+        anchored_start,
+        leftmost_first,
+        no_std,
+        latin1,
+    } = syn::parse2::<ReInput>(input)?;
 
     let mut nfa_arena = Arena::new();
     let nfa = Graph::new_in(&mut nfa_arena);
     let start_node = nfa.start_node();
-    let end_node = nfa.node();
+    let end_node = nfa.node().finalize();
+
+    // the Latin-1 patterns get the byte slice methods only, because their
+    // matches may end inside a UTF-8 char of a `&str` haystack
+    let parse = |pattern: &str| -> resy::Result<Hir> {
+        if latin1 {
+            Parser::new(Latin1Encoder)
+                .with_case_insensitive(case_insensitive)
+                .parse(pattern)
+        } else {
+            Parser::new(Utf8Encoder)
+                .with_case_insensitive(case_insensitive)
+                .parse(pattern)
+        }
+    };
+    // with values, the patterns themselves are the prioritized alternatives
+    let with_values = patterns.iter().any(|(_, value)| value.is_some());
+    let mut translator = Translator::new(&nfa).with_leftmost_first(leftmost_first && !with_values);
+    let mut values = Vec::new();
+    let mut hirs = Vec::new();
+    let mut match_len = None;
+    let mut literals = None;
+    for (index, (lit, value)) in patterns.iter().enumerate() {
+        if !lit.suffix().is_empty() {
+            let v = lit.token().to_string();
+            let loc = v.len() - lit.suffix().len()..v.len();
+            let span = lit.token().subspan(loc).unwrap_or_else(|| lit.span());
+            return Err(syn::Error::new(
+                span,
+                "suffixes for string literals are not allowed",
+            ));
+        }
 
-    let parser = Parser::new(Utf8Encoder);
-    let hir = parser
-        .parse(&lit.value())
-        .map_err(|err| syn::Error::new(lit.span(), err))?;
+        let hir = parse(&lit.value())
+            .map_err(|err| syn::Error::new(value_span(lit, err.error_span()), err))?;
+        // the matches have the exact length only if all the patterns have the
+        // same one
+        match_len = if index == 0 {
+            hir.exact_len()
+        } else {
+            match_len.filter(|len| hir.exact_len() == Some(*len))
+        };
 
-    let mut translator = Translator::new(&nfa);
-    translator.translate(&hir, start_node, end_node);
+        // a set of literals is built into a DFA directly from its trie
+        if patterns.len() == 1
+            && value.is_none()
+            && !leftmost_first
+            && let Some(hir_literals) = hir.literals()
+        {
+            literals = Some(
+                hir_literals
+                    .into_iter()
+                    .map(<[u8]>::to_vec)
+                    .collect::<Vec<_>>(),
+            );
+            continue;
+        }
+
+        let translated = match value {
+            // every pattern gets its own end node, and the transition from it
+            // to the common final node tells which pattern is accepted
+            Some(value) => {
+                let first_node = nfa.node();
+                let last_node = nfa.node();
+                start_node.connect_epsilon(first_node);
+                let tr = last_node.connect_epsilon(end_node);
+                tr.merge_instruct(Inst::Accept(index as u32), None);
+                if leftmost_first {
+                    tr.merge_instruct(Inst::Priority(index as u32), None);
+                }
+                values.push(value);
+                translator.translate(&hir, first_node, last_node)
+            }
+            None => translator.translate(&hir, start_node, end_node),
+        };
+        translated.map_err(|err| syn::Error::new(lit.span(), err))?;
+        hirs.push(hir);
+    }
 
     let mut dfa_arena = Arena::new();
-    let dfa = nfa.determinize_in(&mut dfa_arena);
+    let dfa = match &literals {
+        Some(literals) => Graph::from_literals_in(literals, &mut dfa_arena),
+        None => nfa.determinize_with_tags_in(&mut dfa_arena),
+    };
 
-    let cogen = CodeGen::new(&dfa);
+    let cogen = CodeGen::new(&dfa)
+        .with_dead_state_errors(strict)
+        .with_sink_state(sink)
+        .with_sparse_table(sparse)
+        .with_byte_classes(classes)
+        .with_flat_table(flat)
+        .with_match_len(match_len)
+        .with_anchored_start(anchored_start)
+        .with_no_std(no_std)
+        .with_bytes_only(latin1)
+        .with_char_offsets(cfg!(feature = "char-offsets"))
+        .with_capture_resolver(&hirs);
     let state_machine_code = cogen.gen_state_machine();
     let match_code = cogen.gen_match();
+    let match_error_code = cogen.gen_match_error();
     let regex_code = cogen.gen_regex();
+    let lexer_code = cogen.gen_lexer();
+    let constructor = if values.is_empty() {
+        quote!(adhoc::Regex::new())
+    } else {
+        quote!(adhoc::Lexer::new([#(#values),*]))
+    };
 
     Ok(quote!(
         {
@@ -45,10 +236,76 @@ pub(crate) fn re_impl(input: TokenStream2) -> Result<TokenStream2> {
 
                 #match_code
 
+                #match_error_code
+
                 #regex_code
+
+                #lexer_code
             }
 
-            adhoc::Regex::new()
+            #constructor
         }
     ))
 }
+
+/// Returns the span of the `range` of bytes in the value of the literal, or
+/// the span of the whole literal if the compiler can't point inside it.
+fn value_span(lit: &LitStr, range: Range<usize>) -> Span {
+    source_range(&lit.token().to_string(), range)
+        .and_then(|range| lit.token().subspan(range))
+        .unwrap_or_else(|| lit.span())
+}
+
+/// Maps the `range` of bytes in the value of the string literal onto the range
+/// of bytes in its source `token`, taking into account the quotes, the raw
+/// string hashes, and the escape sequences.
+///
+/// Returns `None` if the token is not a string literal or the range is out of
+/// the value.
+pub(crate) fn source_range(token: &str, range: Range<usize>) -> Option<Range<usize>> {
+    if let Some(raw) = token.strip_prefix('r') {
+        let prefix_len = raw.len() - raw.trim_start_matches('#').len() + 2;
+        let value_len = token[prefix_len..].rfind('"')?;
+        if range.end > value_len {
+            return None;
+        }
+        return Some(range.start + prefix_len..range.end + prefix_len);
+    }
+
+    // the source offset of every byte of the value, and of the closing quote
+    let mut offsets = Vec::new();
+    let mut chars = token
+        .char_indices()
+        .skip_while(|(_, c)| *c != '"')
+        .skip(1)
+        .peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' => {
+                offsets.push(offset);
+                break;
+            }
+            '\\' => match chars.next()?.1 {
+                'u' => {
+                    let mut code = String::new();
+                    for (_, c) in chars.by_ref().skip(1) {
+                        if c == '}' {
+                            break;
+                        }
+                        code.push(c);
+                    }
+                    let c = char::from_u32(u32::from_str_radix(&code.replace('_', ""), 16).ok()?)?;
+                    offsets.extend(std::iter::repeat_n(offset, c.len_utf8()));
+                }
+                'x' => {
+                    chars.nth(1)?;
+                    offsets.push(offset);
+                }
+                '\n' | '\r' => while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {},
+                _ => offsets.push(offset),
+            },
+            _ => offsets.extend(offset..offset + c.len_utf8()),
+        }
+    }
+    Some(*offsets.get(range.start)?..*offsets.get(range.end)?)
+}
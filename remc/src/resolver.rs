@@ -0,0 +1,192 @@
+use redt::SetU8;
+use resy::{AnchorKind, DisjunctHir, GroupHir, GroupLabel, Hir, HirVisitor, RepeatHir};
+
+/// An instruction of the capture resolver's program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Op {
+    /// Consumes the byte.
+    Byte(u8),
+    /// Consumes a byte of the ranges.
+    Class(Vec<(u8, u8)>),
+    /// Goes on at the first target, and backtracks to the second one if the
+    /// first one fails.
+    Split(usize, usize),
+    Jump(usize),
+    /// Stores the current position to the slot.
+    Save(usize),
+    /// Clears the slot.
+    Clear(usize),
+    AssertStartText,
+    AssertEndText,
+    /// Succeeds if the whole match is consumed.
+    Match,
+}
+
+/// A backtracking program of the patterns, which finds the most preferred
+/// path matching a span already found by the state machine, if the registers
+/// of the DFA can't tell the groups apart, see
+/// [`regr::Graph::has_tag_conflicts`].
+///
+/// The earlier alternative of a disjunction is preferred over the later ones,
+/// and a repetition prefers more iterations, or fewer ones if it's not
+/// greedy. Taking an alternative clears the groups of the other alternatives,
+/// the same way the translator invalidates their tags.
+pub(crate) struct Resolver {
+    pub(crate) ops: Vec<Op>,
+    /// The first instruction of every pattern.
+    pub(crate) entries: Vec<usize>,
+    /// The sorted labels of the groups. The group with index `i` stores its
+    /// open and close positions in the slots `2 * i` and `2 * i + 1`.
+    pub(crate) groups: Vec<GroupLabel>,
+}
+
+impl Resolver {
+    pub(crate) fn new(patterns: &[Hir]) -> Self {
+        let mut groups = Vec::new();
+        for hir in patterns {
+            groups.extend(Self::labels(hir));
+        }
+        groups.sort();
+        groups.dedup();
+
+        let mut resolver = Resolver {
+            ops: Vec::new(),
+            entries: Vec::new(),
+            groups,
+        };
+        for hir in patterns {
+            resolver.entries.push(resolver.ops.len());
+            hir.visit(&mut resolver);
+            resolver.ops.push(Op::Match);
+        }
+        resolver
+    }
+
+    /// Returns the number of slots the program writes.
+    pub(crate) fn slots_num(&self) -> usize {
+        self.groups.len() * 2
+    }
+
+    /// Returns the labels of the groups in the `hir`.
+    fn labels(hir: &Hir) -> Vec<GroupLabel> {
+        struct Labels(Vec<GroupLabel>);
+        impl HirVisitor for Labels {
+            fn visit_group(&mut self, group: &GroupHir) {
+                self.0.push(group.label().clone());
+                self.visit_hir(group.inner());
+            }
+        }
+        let mut labels = Labels(Vec::new());
+        hir.visit(&mut labels);
+        labels.0
+    }
+
+    fn group_index(&self, label: &GroupLabel) -> usize {
+        self.groups
+            .binary_search(label)
+            .unwrap_or_else(|_| panic!("unknown group {label:?}"))
+    }
+
+    /// Pushes a placeholder of a `Split` or a `Jump` to be patched later.
+    fn push_placeholder(&mut self) -> usize {
+        self.ops.push(Op::Jump(usize::MAX));
+        self.ops.len() - 1
+    }
+}
+
+impl HirVisitor for Resolver {
+    fn visit_disjunct(&mut self, disjunct: &DisjunctHir) {
+        let alternatives = disjunct.alternatives();
+        if alternatives.is_empty() {
+            self.ops.push(Op::Class(Vec::new()));
+            return;
+        }
+        let labels: Vec<_> = alternatives.iter().map(Self::labels).collect();
+        let mut jumps = Vec::new();
+        for (i, hir) in alternatives.iter().enumerate() {
+            let is_last = i + 1 == alternatives.len();
+            let split = (!is_last).then(|| self.push_placeholder());
+            let others = labels
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .flat_map(|(_, labels)| labels)
+                .filter(|label| !labels[i].contains(label));
+            let mut cleared: Vec<_> = others.map(|label| self.group_index(label)).collect();
+            cleared.sort_unstable();
+            cleared.dedup();
+            for index in cleared {
+                self.ops.push(Op::Clear(index * 2));
+                self.ops.push(Op::Clear(index * 2 + 1));
+            }
+            self.visit_hir(hir);
+            if let Some(split) = split {
+                jumps.push(self.push_placeholder());
+                self.ops[split] = Op::Split(split + 1, self.ops.len());
+            }
+        }
+        for jump in jumps {
+            self.ops[jump] = Op::Jump(self.ops.len());
+        }
+    }
+
+    fn visit_repeat(&mut self, repeat: &RepeatHir) {
+        let (lower, upper) = repeat.iter_hint();
+        for _ in 0..lower {
+            self.visit_hir(repeat.inner());
+        }
+        let split = |split: usize, exit: usize| {
+            if repeat.is_greedy() {
+                Op::Split(split + 1, exit)
+            } else {
+                Op::Split(exit, split + 1)
+            }
+        };
+        match upper {
+            None => {
+                let loop_split = self.push_placeholder();
+                self.visit_hir(repeat.inner());
+                self.ops.push(Op::Jump(loop_split));
+                self.ops[loop_split] = split(loop_split, self.ops.len());
+            }
+            Some(upper) => {
+                let splits: Vec<_> = (lower..upper)
+                    .map(|_| {
+                        let split = self.push_placeholder();
+                        self.visit_hir(repeat.inner());
+                        split
+                    })
+                    .collect();
+                for pc in splits {
+                    self.ops[pc] = split(pc, self.ops.len());
+                }
+            }
+        }
+    }
+
+    fn visit_group(&mut self, group: &GroupHir) {
+        let index = self.group_index(group.label());
+        self.ops.push(Op::Save(index * 2));
+        self.visit_hir(group.inner());
+        self.ops.push(Op::Save(index * 2 + 1));
+    }
+
+    fn visit_class(&mut self, set: &SetU8) {
+        let ranges = set
+            .ranges()
+            .map(|range| (range.start(), range.last()))
+            .collect();
+        self.ops.push(Op::Class(ranges));
+    }
+
+    fn visit_literal(&mut self, bytes: &[u8]) {
+        self.ops.extend(bytes.iter().copied().map(Op::Byte));
+    }
+
+    fn visit_anchor(&mut self, kind: AnchorKind) {
+        self.ops.push(match kind {
+            AnchorKind::StartText => Op::AssertStartText,
+            AnchorKind::EndText => Op::AssertEndText,
+        });
+    }
+}
@@ -1,8 +1,9 @@
 use crate::codegen::CodeGen;
 use pretty_assertions::assert_eq;
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::quote;
-use regr::{Arena, Graph};
+use regr::{Arena, Graph, Translator};
+use resy::{Parser, enc::Utf8Encoder};
 
 fn pretty(tok_stream: TokenStream) -> String {
     prettyplease::unparse(&syn::parse2::<syn::File>(tok_stream).unwrap())
@@ -24,6 +25,26 @@ fn codgen_produce_for_empty_graph() {
     let _ = CodeGen::new(&gr);
 }
 
+#[test]
+fn codegen_empty_pattern_and_language() {
+    // the empty pattern: the start state is final
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let _ = gr.node().finalize();
+    let code = pretty(CodeGen::new(&gr).gen_state_machine());
+    assert!(code.contains("const START_STATE: usize = 0usize;"));
+    assert!(code.contains("const FIRST_NON_FINAL_STATE: usize = 1usize;"));
+    let code = pretty(CodeGen::new(&gr).gen_regex());
+    assert!(code.contains("const MIN_LEN: usize = 0usize;"));
+
+    // the empty language: there are no final states at all
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let _ = gr.node();
+    let code = pretty(CodeGen::new(&gr).gen_state_machine());
+    assert!(code.contains("const FIRST_NON_FINAL_STATE: usize = 0usize;"));
+}
+
 #[test]
 fn codgen_produce() {
     let mut ar = Arena::new();
@@ -34,18 +55,40 @@ fn codgen_produce() {
     assert_eq!(
         pretty(cd.gen_state_machine()),
         pretty(quote! {
+            /// The automaton of the regex driven one byte at a time. It doesn't
+            /// need the whole haystack, so it can be fed across buffer
+            /// boundaries.
             #[derive(Debug)]
-            struct StateMachine {
+            pub struct StateMachine {
                 state: usize,
             }
 
             impl StateMachine {
                 const START_STATE: usize = 0usize;
+                const TEXT_START_STATE: usize = 0usize;
                 const INVALID_STATE: usize = 1usize;
                 const FIRST_NON_FINAL_STATE: usize = 0usize;
-                const STATES_NUM: usize = 1usize;
+                const STATES_NUM: usize = 2usize;
 
                 const TRANSITION_TABLE: [[u8; 256usize]; Self::STATES_NUM] = [
+                    [
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+                    ],
                     [
                         1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                         1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
@@ -66,20 +109,46 @@ fn codgen_produce() {
                     ]
                 ];
 
+                const FINAL_AT_END_STATES: [bool; Self::STATES_NUM] = [false, false];
+
                 #[inline]
-                fn new() -> Self {
+                fn new(start: usize) -> Self {
                     Self {
-                        state: Self::START_STATE,
+                        state: if start == 0 {
+                            Self::TEXT_START_STATE
+                        } else {
+                            Self::START_STATE
+                        },
                     }
                 }
 
+                /// Returns the state machine to the start of the text.
+                #[inline]
+                pub fn reset(&mut self) {
+                    *self = Self::new(0);
+                }
+
+                /// Moves the state machine by the next byte of the haystack.
                 #[inline]
-                fn is_final(&self) -> bool {
+                pub fn feed(&mut self, byte: u8) {
+                    self.next(byte);
+                }
+
+                /// Checks if the bytes fed since the start form a match. The
+                /// end of text anchors are not taken into account.
+                #[inline]
+                pub fn is_final(&self) -> bool {
                     self.state < Self::FIRST_NON_FINAL_STATE
                 }
 
                 #[inline]
-                fn is_invalid(&self) -> bool {
+                fn is_final_at_end(&self) -> bool {
+                    !self.is_invalid() && Self::FINAL_AT_END_STATES[self.state]
+                }
+
+                /// Checks if no more bytes can lead to a match.
+                #[inline]
+                pub fn is_invalid(&self) -> bool {
                     self.state == Self::INVALID_STATE
                 }
 
@@ -87,7 +156,7 @@ fn codgen_produce() {
                 fn next(&mut self, byte: u8) {
                     ::core::debug_assert!(
                         self.state < Self::STATES_NUM,
-                        "transition from invalid state {} is not allowed",
+                        "state {} is out of the transition table",
                         self.state,
                     );
                     self.state = *unsafe {
@@ -100,3 +169,357 @@ fn codgen_produce() {
         })
     );
 }
+
+#[test]
+fn codegen_produce_match_len() {
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let a = gr.node();
+    let b = gr.node();
+    let c = gr.node().finalize();
+    a.connect(b).merge(b'a');
+    b.connect(c).merge(b'b');
+
+    let code = pretty(CodeGen::new(&gr).gen_regex());
+    assert!(code.contains("const MATCH_LEN: Option<usize> = None;"));
+    assert!(!code.contains("for byte in &bytes[..2usize]"));
+
+    let code = pretty(CodeGen::new(&gr).with_match_len(Some(2)).gen_regex());
+    assert!(code.contains("const MATCH_LEN: Option<usize> = Some(2usize);"));
+    assert!(code.contains("if bytes.len() < 2usize {"));
+    assert!(code.contains("for byte in &bytes[..2usize]"));
+
+    // dead state errors need the general loop
+    let code = pretty(
+        CodeGen::new(&gr)
+            .with_match_len(Some(2))
+            .with_dead_state_errors(true)
+            .gen_regex(),
+    );
+    assert!(!code.contains("for byte in &bytes[..2usize]"));
+}
+
+#[test]
+fn codgen_produce_sparse() {
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let a = gr.node();
+    let b = gr.node().finalize();
+    for byte in b'0'..=b'9' {
+        a.connect(b).merge(byte);
+        b.connect(b).merge(byte);
+    }
+    let cd = CodeGen::new(&gr).with_sparse_table(true);
+
+    assert_eq!(
+        pretty(cd.gen_state_machine()),
+        pretty(quote! {
+            /// The automaton of the regex driven one byte at a time. It doesn't
+            /// need the whole haystack, so it can be fed across buffer
+            /// boundaries.
+            #[derive(Debug)]
+            pub struct StateMachine {
+                state: usize,
+            }
+
+            impl StateMachine {
+                const START_STATE: usize = 1usize;
+                const TEXT_START_STATE: usize = 1usize;
+                const INVALID_STATE: usize = 2usize;
+                const FIRST_NON_FINAL_STATE: usize = 1usize;
+                const STATES_NUM: usize = 3usize;
+
+                const DEFAULT_TRANSITIONS: [u8; Self::STATES_NUM] = [2, 2, 2];
+
+                const TRANSITION_ARCS: [&[(u8, u8, u8)]; Self::STATES_NUM] = [
+                    &[(48, 57, 0)],
+                    &[(48, 57, 0)],
+                    &[]
+                ];
+
+                const FINAL_AT_END_STATES: [bool; Self::STATES_NUM] = [false, false, false];
+
+                #[inline]
+                fn new(start: usize) -> Self {
+                    Self {
+                        state: if start == 0 {
+                            Self::TEXT_START_STATE
+                        } else {
+                            Self::START_STATE
+                        },
+                    }
+                }
+
+                /// Returns the state machine to the start of the text.
+                #[inline]
+                pub fn reset(&mut self) {
+                    *self = Self::new(0);
+                }
+
+                /// Moves the state machine by the next byte of the haystack.
+                #[inline]
+                pub fn feed(&mut self, byte: u8) {
+                    self.next(byte);
+                }
+
+                /// Checks if the bytes fed since the start form a match. The
+                /// end of text anchors are not taken into account.
+                #[inline]
+                pub fn is_final(&self) -> bool {
+                    self.state < Self::FIRST_NON_FINAL_STATE
+                }
+
+                #[inline]
+                fn is_final_at_end(&self) -> bool {
+                    !self.is_invalid() && Self::FINAL_AT_END_STATES[self.state]
+                }
+
+                /// Checks if no more bytes can lead to a match.
+                #[inline]
+                pub fn is_invalid(&self) -> bool {
+                    self.state == Self::INVALID_STATE
+                }
+
+                #[inline]
+                fn next(&mut self, byte: u8) {
+                    ::core::debug_assert!(
+                        self.state < Self::STATES_NUM,
+                        "state {} is out of the transition table",
+                        self.state,
+                    );
+                    let arcs = *unsafe { Self::TRANSITION_ARCS.get_unchecked(self.state) };
+                    let mut target = *unsafe { Self::DEFAULT_TRANSITIONS.get_unchecked(self.state) };
+                    for &(first, last, arc_target) in arcs {
+                        if byte < first {
+                            break;
+                        }
+                        if byte <= last {
+                            target = arc_target;
+                            break;
+                        }
+                    }
+                    self.state = target as usize;
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn codgen_produce_sparse_fallback() {
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let a = gr.node();
+    let b = gr.node().finalize();
+    for byte in (0..=u8::MAX).step_by(2) {
+        a.connect(b).merge(byte);
+    }
+
+    // too many arcs for the sparse table
+    let code = pretty(
+        CodeGen::new(&gr)
+            .with_sparse_table(true)
+            .gen_state_machine(),
+    );
+    assert!(code.contains("TRANSITION_TABLE"));
+    assert!(!code.contains("TRANSITION_ARCS"));
+
+    let code = pretty(CodeGen::new(&gr).gen_state_machine());
+    assert!(code.contains("TRANSITION_TABLE"));
+    assert!(!code.contains("TRANSITION_ARCS"));
+}
+
+#[test]
+fn codgen_produce_flat_table() {
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let a = gr.node();
+    let b = gr.node().finalize();
+    a.connect(b).merge(b'a');
+    b.connect(b).merge(b'a');
+    let code = pretty(CodeGen::new(&gr).with_flat_table(true).gen_state_machine());
+
+    // the last line is of the invalid state
+    let mut table = vec![2; 3 * 256];
+    table[b'a' as usize] = 0;
+    table[256 + b'a' as usize] = 0;
+    let table = table.iter().map(|target| Literal::u8_unsuffixed(*target));
+    let expected = pretty(quote! {
+        impl StateMachine {
+            const STRIDE: usize = 256usize;
+            const TRANSITION_TABLE: [u8; Self::STATES_NUM * Self::STRIDE] = [#(#table,)*];
+        }
+    });
+    let expected = expected
+        .lines()
+        .filter(|line| line.starts_with("    "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(code.contains(&expected), "{code}");
+    assert!(code.contains(".get_unchecked(self.state * Self::STRIDE + byte as usize)"));
+}
+
+#[test]
+fn codgen_produce_byte_classes() {
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let a = gr.node();
+    let b = gr.node().finalize();
+    let c = gr.node().finalize();
+    for byte in b'0'..=b'9' {
+        a.connect(b).merge(byte);
+        b.connect(b).merge(byte);
+    }
+    a.connect(c).merge(b'-');
+    a.connect(c).merge(b'+');
+    let cd = CodeGen::new(&gr).with_byte_classes(true);
+
+    let class_of = (0..=u8::MAX).map(|byte| {
+        Literal::u8_unsuffixed(match byte {
+            b'+' | b'-' => 1,
+            b'0'..=b'9' => 2,
+            _ => 0,
+        })
+    });
+    assert_eq!(
+        pretty(cd.gen_state_machine()),
+        pretty(quote! {
+            /// The automaton of the regex driven one byte at a time. It doesn't
+            /// need the whole haystack, so it can be fed across buffer
+            /// boundaries.
+            #[derive(Debug)]
+            pub struct StateMachine {
+                state: usize,
+            }
+
+            impl StateMachine {
+                const START_STATE: usize = 2usize;
+                const TEXT_START_STATE: usize = 2usize;
+                const INVALID_STATE: usize = 3usize;
+                const FIRST_NON_FINAL_STATE: usize = 2usize;
+                const STATES_NUM: usize = 4usize;
+
+                const CLASSES_NUM: usize = 3usize;
+
+                const CLASS_OF: [u8; 256usize] = [#(#class_of,)*];
+
+                const TRANSITION_TABLE: [[u8; Self::CLASSES_NUM]; Self::STATES_NUM] = [
+                    [3, 3, 0,],
+                    [3, 3, 3,],
+                    [3, 1, 0,],
+                    [3, 3, 3,]
+                ];
+
+                const FINAL_AT_END_STATES: [bool; Self::STATES_NUM] = [false, false, false, false];
+
+                #[inline]
+                fn new(start: usize) -> Self {
+                    Self {
+                        state: if start == 0 {
+                            Self::TEXT_START_STATE
+                        } else {
+                            Self::START_STATE
+                        },
+                    }
+                }
+
+                /// Returns the state machine to the start of the text.
+                #[inline]
+                pub fn reset(&mut self) {
+                    *self = Self::new(0);
+                }
+
+                /// Moves the state machine by the next byte of the haystack.
+                #[inline]
+                pub fn feed(&mut self, byte: u8) {
+                    self.next(byte);
+                }
+
+                /// Checks if the bytes fed since the start form a match. The
+                /// end of text anchors are not taken into account.
+                #[inline]
+                pub fn is_final(&self) -> bool {
+                    self.state < Self::FIRST_NON_FINAL_STATE
+                }
+
+                #[inline]
+                fn is_final_at_end(&self) -> bool {
+                    !self.is_invalid() && Self::FINAL_AT_END_STATES[self.state]
+                }
+
+                /// Checks if no more bytes can lead to a match.
+                #[inline]
+                pub fn is_invalid(&self) -> bool {
+                    self.state == Self::INVALID_STATE
+                }
+
+                #[inline]
+                fn next(&mut self, byte: u8) {
+                    ::core::debug_assert!(
+                        self.state < Self::STATES_NUM,
+                        "state {} is out of the transition table",
+                        self.state,
+                    );
+                    let class = Self::CLASS_OF[byte as usize];
+                    self.state = *unsafe {
+                        Self::TRANSITION_TABLE
+                            .get_unchecked(self.state)
+                            .get_unchecked(class as usize)
+                    } as usize;
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn codegen_produce_char_offsets() {
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let _ = gr.node();
+
+    let code = pretty(CodeGen::new(&gr).with_char_offsets(false).gen_match());
+    assert!(!code.contains("haystack"));
+    assert!(!code.contains("fn char_start"));
+
+    let cd = CodeGen::new(&gr).with_char_offsets(true);
+    let code = pretty(cd.gen_match());
+    assert!(code.contains("haystack: &'h str,"));
+    assert!(code.contains("pub fn char_start(&self) -> usize"));
+    assert!(code.contains("pub fn char_end(&self) -> usize"));
+    assert!(pretty(cd.gen_regex()).contains("haystack,"));
+}
+
+#[test]
+fn codegen_produce_captures() {
+    let mut ar = Arena::new();
+    let gr = Graph::new_in(&mut ar);
+    let _ = gr.node();
+    let code = pretty(CodeGen::new(&gr).gen_match());
+    assert!(!code.contains("regs"));
+    assert!(!code.contains("fn group"));
+
+    let mut nfa_ar = Arena::new();
+    let nfa = Graph::new_in(&mut nfa_ar);
+    let hir = Parser::new(Utf8Encoder).parse("(?<1>a)(?<2>b)?").unwrap();
+    let start_node = nfa.start_node();
+    let end_node = nfa.node().finalize();
+    Translator::new(&nfa)
+        .translate(&hir, start_node, end_node)
+        .unwrap();
+    let mut ar = Arena::new();
+    let gr = nfa.determinize_with_tags_in(&mut ar);
+
+    let cd = CodeGen::new(&gr);
+    let code = pretty(cd.gen_state_machine());
+    assert!(code.contains("regs: [Option<usize>; Self::REGS_NUM],"));
+    assert!(code.contains("const REGS_NUM: usize = 2usize;"));
+    assert!(code.contains("const INITIAL_REGS: [Option<usize>; Self::REGS_NUM] = [None, None];"));
+    assert!(code.contains("&[(97, 97, &[RegOp::Write(0)])]"));
+    assert!(code.contains("&[(98, 98, &[RegOp::Write(1)])]"));
+    let code = pretty(cd.gen_match());
+    assert!(code.contains("regs: [Option<usize>; StateMachine::REGS_NUM],"));
+    assert!(code.contains("(GroupLabel::Index(1), (0, 0, &[]), (0, 1, &[]))"));
+    assert!(code.contains("(GroupLabel::Index(2), (1, 0, &[]), (1, 1, &[]))"));
+    assert!(code.contains("pub fn group<'l>"));
+}
@@ -1 +1,3 @@
 mod codegen;
+mod regex;
+mod resolver;
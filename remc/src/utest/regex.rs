@@ -0,0 +1,66 @@
+use crate::regex::{re_impl, source_range};
+use quote::quote;
+use resy::{Parser, enc::Utf8Encoder};
+
+#[test]
+fn re_impl_flags() {
+    assert!(re_impl(quote!("abc")).is_ok());
+    assert!(re_impl(quote!("abc",)).is_ok());
+    assert!(re_impl(quote!("abc", strict)).is_ok());
+    assert!(re_impl(quote!("a|ab", first)).is_ok());
+    assert!(re_impl(quote!("abc", strict, no_std)).is_ok());
+    assert!(re_impl(quote!("café", latin1)).is_ok());
+
+    let error = re_impl(quote!("abc", lazy)).unwrap_err();
+    assert_eq!(error.to_string(), "unknown flag `lazy`");
+    let error = re_impl(quote!("abc"; strict)).unwrap_err();
+    assert_eq!(error.to_string(), "expected `,`");
+}
+
+#[test]
+fn re_impl_pattern_values() {
+    assert!(re_impl(quote!("if" => 1)).is_ok());
+    assert!(re_impl(quote!("if" => Action::If, "[a-z]+" => Action::Ident,)).is_ok());
+    assert!(re_impl(quote!("if" => 1, "[a-z]+" => 2, strict)).is_ok());
+
+    let error = re_impl(quote!("if" => 1, "[a-z]+")).unwrap_err();
+    assert_eq!(error.to_string(), "expected `=>`");
+    let error = re_impl(quote!("if", "[a-z]+" => 2)).unwrap_err();
+    assert_eq!(error.to_string(), "expected identifier");
+    let error = re_impl(quote!("if" => 1, "é" => 2, latin1)).unwrap_err();
+    assert_eq!(error.to_string(), "flag `latin1` can't be used with values");
+}
+
+#[test]
+fn re_impl_error_span() {
+    let error = re_impl(quote!("a{3,1}")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "repetition expression `{n,m}` expects that `n <= m`"
+    );
+
+    // the error points at `{3,1}` inside the literal
+    let error = Parser::new(Utf8Encoder).parse("a{3,1}").unwrap_err();
+    assert_eq!(error.error_span(), 1..6);
+    assert_eq!(source_range(r#""a{3,1}""#, error.error_span()), Some(2..7));
+}
+
+#[test]
+fn literal_source_range() {
+    assert_eq!(source_range(r#""a{3,1}""#, 1..6), Some(2..7));
+    assert_eq!(source_range(r#""a{3,1}""#, 6..6), Some(7..7));
+    assert_eq!(source_range(r#""a{3,1}""#, 1..7), None);
+    assert_eq!(source_range(r#"r"a{3,1}""#, 1..6), Some(3..8));
+    assert_eq!(source_range(r###"r##"a{3,1}"##"###, 1..6), Some(5..10));
+    assert_eq!(source_range(r#"r"a""#, 0..2), None);
+
+    // escape sequences take more bytes in the source than in the value
+    assert_eq!(source_range(r#""\\d\t{3,1}""#, 0..2), Some(1..4));
+    assert_eq!(source_range(r#""\\d\t{3,1}""#, 3..8), Some(6..11));
+    assert_eq!(source_range(r#""\x41{3,1}""#, 1..6), Some(5..10));
+    assert_eq!(source_range(r#""\u{44f}{3,1}""#, 2..7), Some(8..13));
+    assert_eq!(source_range(r#""ў{3,1}""#, 2..7), Some(3..8));
+    assert_eq!(source_range("\"a\\\n    {3,1}\"", 1..6), Some(8..13));
+    assert_eq!(source_range(r#""a{3,1}"suffix"#, 1..6), Some(2..7));
+    assert_eq!(source_range("abc", 0..1), None);
+}
@@ -0,0 +1,52 @@
+use crate::resolver::{Op, Resolver};
+use resy::{GroupLabel, Parser, enc::Utf8Encoder};
+
+#[test]
+fn resolver_program() {
+    let parser = Parser::new(Utf8Encoder);
+    let patterns = [
+        parser.parse("(?<1>a|(?<2>b))c*?").unwrap(),
+        parser.parse("^(?<3>x){1,2}").unwrap(),
+    ];
+    let resolver = Resolver::new(&patterns);
+    assert_eq!(
+        resolver.groups,
+        [
+            GroupLabel::Index(1),
+            GroupLabel::Index(2),
+            GroupLabel::Index(3)
+        ]
+    );
+    assert_eq!(resolver.slots_num(), 6);
+    assert_eq!(resolver.entries, [0, 14]);
+    assert_eq!(
+        resolver.ops,
+        [
+            Op::Save(0),
+            // the first alternative clears the group of the second one
+            Op::Split(2, 6),
+            Op::Clear(2),
+            Op::Clear(3),
+            Op::Byte(b'a'),
+            Op::Jump(9),
+            Op::Save(2),
+            Op::Byte(b'b'),
+            Op::Save(3),
+            Op::Save(1),
+            // the lazy repetition prefers the exit
+            Op::Split(13, 11),
+            Op::Byte(b'c'),
+            Op::Jump(10),
+            Op::Match,
+            Op::AssertStartText,
+            Op::Save(4),
+            Op::Byte(b'x'),
+            Op::Save(5),
+            Op::Split(19, 22),
+            Op::Save(4),
+            Op::Byte(b'x'),
+            Op::Save(5),
+            Op::Match,
+        ]
+    );
+}
@@ -1,9 +1,32 @@
 use remc::re;
 
+/// Compiles the pattern with `re!` and asserts that matching the haystack from
+/// its very beginning captures the expected substring, or nothing at all.
+macro_rules! assert_re_match {
+    ($pattern:literal, $haystack:literal => $expected:expr) => {
+        let expected: Option<&str> = $expected;
+        assert_eq!(
+            re!($pattern).match_at($haystack, 0).map(|m| m.as_str()),
+            expected,
+            "pattern {:?} against {:?}",
+            $pattern,
+            $haystack,
+        );
+    };
+}
+
+#[test]
+fn assert_re_match() {
+    assert_re_match!("hello", "hello world" => Some("hello"));
+    assert_re_match!("[0-9]+", "2024-10" => Some("2024"));
+    assert_re_match!("x*", "abc" => Some(""));
+    assert_re_match!("hello", "world" => None);
+    assert_re_match!("[0-9]+", "a1" => None);
+}
+
 #[test]
-#[ignore]
 fn simple_regex() {
-    let mut regex = re!("hello");
+    let regex = re!("hello");
     let m = regex.match_at("hello", 0).unwrap();
     assert_eq!(m.as_str(), "hello");
 
@@ -15,9 +38,8 @@ fn simple_regex() {
 }
 
 #[test]
-#[ignore]
 fn klenee_start_regex() {
-    let mut regex = re!("hello**");
+    let regex = re!("hello**");
     let m = regex.match_at("hello", 0).unwrap();
     assert_eq!(m.as_str(), "hello");
 
@@ -27,7 +49,7 @@ fn klenee_start_regex() {
     let m = regex.match_at("hhelloooO", 1).unwrap();
     assert_eq!(m.as_str(), "hellooo");
 
-    let mut regex = re!("hell[oa]**");
+    let regex = re!("hell[oa]**");
     let m = regex.match_at("hella", 0).unwrap();
     assert_eq!(m.as_str(), "hella");
 
@@ -40,9 +62,8 @@ fn klenee_start_regex() {
 }
 
 #[test]
-#[ignore]
 fn another_test() {
-    let mut regex = re!("[ab]*a");
+    let regex = re!("[ab]*a");
     let m = regex.match_at("ba", 0).unwrap();
     assert_eq!(m.as_str(), "ba");
     let m = regex.match_at("baaaaaa", 0).unwrap();
@@ -52,3 +73,823 @@ fn another_test() {
     let m = regex.match_at("bbb", 0);
     assert_eq!(m, None);
 }
+
+#[test]
+fn find_all() {
+    let regex = re!("[0-9]+");
+    let matches = regex.find_all("a1 b22 c333");
+    assert_eq!(
+        matches.iter().map(|m| m.range()).collect::<Vec<_>>(),
+        [1..2, 4..6, 8..11]
+    );
+    assert_eq!(
+        matches.iter().map(|m| m.as_str()).collect::<Vec<_>>(),
+        ["1", "22", "333"]
+    );
+    assert_eq!(regex.find_all("abc"), []);
+    // a long haystack without matches doesn't reserve a slot per byte
+    assert!(regex.find_all(&"x".repeat(1 << 20)).capacity() <= 16);
+
+    let regex = re!("ab");
+    let matches = regex.find_all("ababxab");
+    assert_eq!(
+        matches.iter().map(|m| m.range()).collect::<Vec<_>>(),
+        [0..2, 2..4, 5..7]
+    );
+}
+
+#[test]
+fn find_iter() {
+    let regex = re!("ab");
+    let mut matches = regex.find_iter("ababX ab");
+    let m = matches.next().unwrap();
+    assert_eq!((m.start(), m.end()), (0, 2));
+    let m = matches.next().unwrap();
+    assert_eq!((m.start(), m.end()), (2, 4));
+    let m = matches.next().unwrap();
+    assert_eq!((m.start(), m.end()), (6, 8));
+    assert_eq!(matches.next(), None);
+    assert_eq!(matches.next(), None);
+
+    assert_eq!(regex.find_iter("").next(), None);
+    assert_eq!(regex.find_iter("xyz").next(), None);
+
+    let regex = re!("x*");
+    assert_eq!(
+        regex
+            .find_iter("axxб")
+            .map(|m| m.range())
+            .collect::<Vec<_>>(),
+        [0..0, 1..3, 5..5]
+    );
+}
+
+#[test]
+fn find_iter_empty_matches() {
+    let regex = re!("a*");
+    let ranges = |haystack| {
+        regex
+            .find_iter(haystack)
+            .map(|m| (m.start(), m.end()))
+            .collect::<Vec<_>>()
+    };
+    // an empty match right after the previous match is skipped
+    assert_eq!(ranges("bab"), [(0, 0), (1, 2), (3, 3)]);
+    assert_eq!(ranges(""), [(0, 0)]);
+    assert_eq!(ranges("aa"), [(0, 2)]);
+    assert_eq!(ranges("bb"), [(0, 0), (1, 1), (2, 2)]);
+    assert_eq!(ranges("яa"), [(0, 0), (2, 3)]);
+    assert_eq!(regex.replacen("bab", "-", 0), "-b-b-");
+}
+
+#[test]
+fn match_lengths() {
+    let regex = re!("a+");
+    assert_eq!(
+        regex.match_lengths("aXaa"),
+        [Some(1), None, Some(2), Some(1)]
+    );
+    assert_eq!(regex.match_lengths(""), []);
+
+    let regex = re!("x*");
+    assert_eq!(
+        regex.match_lengths("xбx"),
+        [Some(1), Some(0), None, Some(1)]
+    );
+
+    let regex = re!("^a|b$", strict);
+    assert_eq!(regex.match_lengths("aab"), [Some(1), None, Some(1)]);
+}
+
+#[test]
+fn anchored_start() {
+    let unanchored = re!("abc");
+    let anchored = re!("abc", anchored_start);
+
+    macro_rules! ranges {
+        ($matches:expr) => {
+            $matches
+                .iter()
+                .map(|m| (m.start(), m.end()))
+                .collect::<Vec<_>>()
+        };
+    }
+    assert_eq!(ranges!(anchored.find_all("abcabc")), [(0, 3), (3, 6)]);
+    assert_eq!(ranges!(anchored.find_all("abcabcxabc")), [(0, 3), (3, 6)]);
+    assert_eq!(ranges!(anchored.find_all("xabc")), []);
+    assert_eq!(ranges!(anchored.find_all("ab")), []);
+    assert_eq!(
+        ranges!(unanchored.find_all("abcabcxabc")),
+        [(0, 3), (3, 6), (7, 10)]
+    );
+    assert_eq!(ranges!(unanchored.find_all("xabc")), [(1, 4)]);
+
+    // the current position of the stream is the `start` of `match_at`
+    assert_eq!(anchored.match_at("xabcd", 1).unwrap().range(), 1..4);
+    assert_eq!(anchored.match_at("xabcd", 0), None);
+    assert_eq!(anchored.match_at("xabcd", 2), None);
+
+    let mut matches = anchored.find_iter("abcx");
+    assert_eq!(matches.next().unwrap().range(), 0..3);
+    assert_eq!(matches.next(), None);
+    assert_eq!(matches.next(), None);
+
+    let anchored = re!("x*", anchored_start);
+    assert_eq!(ranges!(anchored.find_all("xxyx")), [(0, 2)]);
+    assert_eq!(ranges!(anchored.find_all("xxx")), [(0, 3)]);
+    assert_eq!(ranges!(anchored.find_all("yx")), [(0, 0)]);
+}
+
+#[cfg(feature = "char-offsets")]
+#[test]
+fn char_offsets() {
+    let regex = re!("é+");
+    let m = regex.find_all("xéé").pop().unwrap();
+    assert_eq!(m.range(), 1..5);
+    assert_eq!(m.char_start()..m.char_end(), 1..3);
+
+    let m = regex.match_at("ўxéy", 3).unwrap();
+    assert_eq!((m.char_start(), m.char_end()), (2, 3));
+    assert_eq!(regex.match_at("é", 0).unwrap().char_start(), 0);
+}
+
+#[test]
+fn empty_pattern() {
+    let regex = re!("");
+    let m = regex.match_at("", 0).unwrap();
+    assert_eq!(m.range(), 0..0);
+    assert!(m.is_empty());
+    assert_eq!(regex.match_at("abc", 0).unwrap().range(), 0..0);
+    assert_eq!(regex.match_at("abc", 2).unwrap().range(), 2..2);
+    assert_eq!(regex.match_at("abc", 3).unwrap().range(), 3..3);
+    assert_eq!(
+        regex
+            .find_all("ab")
+            .iter()
+            .map(|m| m.start())
+            .collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    assert_eq!(regex.match_lengths("ab"), [Some(0), Some(0)]);
+
+    let regex = re!("", strict);
+    assert_eq!(regex.match_at("abc", 1).unwrap().range(), 1..1);
+}
+
+#[test]
+fn anchors() {
+    let regex = re!("^ab");
+    assert_eq!(regex.match_at("abab", 0).unwrap().range(), 0..2);
+    assert_eq!(regex.match_at("abab", 2), None);
+    let matches = regex.find_all("abab");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].range(), 0..2);
+
+    let regex = re!("ab$");
+    assert_eq!(regex.match_at("abab", 0), None);
+    assert_eq!(regex.match_at("abab", 2).unwrap().range(), 2..4);
+
+    let regex = re!("a+$");
+    assert_eq!(regex.match_at("aaa", 0).unwrap().as_str(), "aaa");
+    assert_eq!(regex.match_at("aab", 0), None);
+
+    let regex = re!("^$");
+    assert_eq!(regex.match_at("", 0).unwrap().range(), 0..0);
+    assert_eq!(regex.match_at("a", 0), None);
+
+    let regex = re!("^a|b");
+    assert_eq!(regex.match_at("aa", 0).unwrap().as_str(), "a");
+    assert_eq!(regex.match_at("aa", 1), None);
+    assert_eq!(regex.match_at("ab", 1).unwrap().as_str(), "b");
+
+    let regex = re!("a\\$");
+    assert_eq!(regex.match_at("a$", 0).unwrap().as_str(), "a$");
+}
+
+#[test]
+fn class_escapes() {
+    let regex = re!("[\\w]+");
+    assert_eq!(
+        regex.match_at("snake_case9 x", 0).unwrap().as_str(),
+        "snake_case9"
+    );
+    assert_eq!(regex.match_at("_tmp-1", 0).unwrap().as_str(), "_tmp");
+    assert_eq!(regex.match_at("-", 0), None);
+
+    let regex = re!("\\D");
+    assert_eq!(regex.match_at("a", 0).unwrap().as_str(), "a");
+    assert_eq!(regex.match_at("ў", 0).unwrap().as_str(), "ў");
+    assert_eq!(regex.match_at("7", 0), None);
+
+    let regex = re!("\\d+\\s*[\\d.]+");
+    assert_eq!(regex.match_at("12 \t3.5", 0).unwrap().as_str(), "12 \t3.5");
+}
+
+#[test]
+fn dead_state_errors() {
+    let regex = re!("abc", strict);
+    assert_eq!(regex.match_at("abc", 0).unwrap().as_str(), "abc");
+
+    let error = regex.match_at("abx", 0).unwrap_err();
+    assert_eq!(error.offset(), 2);
+    assert_eq!(error.byte(), Some(b'x'));
+    assert_eq!(error.to_string(), "unexpected byte 0x78 at offset 2");
+
+    let error = regex.match_at("xab", 1).unwrap_err();
+    assert_eq!(error.offset(), 3);
+    assert_eq!(error.byte(), None);
+    assert_eq!(error.to_string(), "unexpected end of haystack at offset 3");
+
+    let matches = regex.find_all("abcxabc");
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[1].range(), 4..7);
+}
+
+#[test]
+fn case_insensitive() {
+    let regex = re!("ABC", i);
+    assert_eq!(regex.match_at("abc", 0).unwrap().as_str(), "abc");
+    assert_eq!(regex.match_at("AbC", 0).unwrap().as_str(), "AbC");
+    assert_eq!(regex.match_at("ABD", 0), None);
+
+    let regex = re!("[a-c]+[^x]", i);
+    assert_eq!(regex.match_at("aBcd", 0).unwrap().as_str(), "aBcd");
+    assert_eq!(regex.match_at("aBcX", 0).unwrap().as_str(), "aBc");
+
+    let regex = re!("ABC");
+    assert_eq!(regex.match_at("abc", 0), None);
+
+    let regex = re!("Ñandú", i);
+    assert_eq!(regex.match_at("ñANDÚ", 0).unwrap().as_str(), "ñANDÚ");
+    assert_eq!(regex.match_at("Ñandu", 0), None);
+
+    let regex = re!("[а-я]+", i);
+    assert_eq!(regex.match_at("ПрИвЕт", 0).unwrap().as_str(), "ПрИвЕт");
+
+    // a folded letter takes a quantifier like any other item
+    let regex = re!("ab+c?", i);
+    assert_eq!(regex.match_at("AbBBc", 0).unwrap().as_str(), "AbBBc");
+    assert_eq!(regex.match_at("aBx", 0).unwrap().as_str(), "aB");
+}
+
+#[test]
+fn sink_state() {
+    macro_rules! assert_same {
+        ($pattern:literal, $($haystack:literal),+) => {
+            let branching = re!($pattern);
+            let branch_free = re!($pattern, sink);
+            for haystack in [$($haystack),+] {
+                for start in 0..=haystack.len() {
+                    assert_eq!(
+                        branching.match_at(haystack, start).map(|m| m.range()),
+                        branch_free.match_at(haystack, start).map(|m| m.range()),
+                        "pattern {:?} at {start} of {haystack:?}",
+                        $pattern,
+                    );
+                }
+                assert_eq!(
+                    branching.find_all(haystack).iter().map(|m| m.range()).collect::<Vec<_>>(),
+                    branch_free.find_all(haystack).iter().map(|m| m.range()).collect::<Vec<_>>(),
+                );
+            }
+        };
+    }
+    assert_same!("hello", "hello", "hhelloo", "", "hell");
+    assert_same!("[ab]*a", "ba", "baaab", "bbb", "xaba");
+    assert_same!("[0-9]+", "a1 b22 c333", "abc");
+    assert_same!("^ab|b$", "abab", "bab", "b");
+    assert_same!("x*", "", "xxy", "yx");
+}
+
+#[test]
+fn invalid_state_transitions() {
+    // the sink state machines keep going after the first byte without a
+    // valid transition, so every table layout needs a line for the invalid
+    // state
+    let dense = re!("ab", sink);
+    let sparse = re!("ab", sparse, sink);
+    let classes = re!("ab", classes, sink);
+    let flat = re!("ab", flat, sink);
+    for haystack in ["xy", "axb", "b\u{0}\u{ff}", "aab"] {
+        assert_eq!(dense.match_at(haystack, 0), None);
+        assert_eq!(sparse.match_at(haystack, 0), None);
+        assert_eq!(classes.match_at(haystack, 0), None);
+        assert_eq!(flat.match_at(haystack, 0), None);
+    }
+    assert_eq!(dense.find("aab").unwrap().range(), 1..3);
+    assert_eq!(flat.find("aab").unwrap().range(), 1..3);
+
+    // a state machine fed by hand past a dead byte steps from the invalid
+    // state, so it has to stay there without leaving the table
+    macro_rules! assert_stays_invalid {
+        ($regex:expr) => {
+            let mut state_machine = $regex.state_machine();
+            state_machine.feed(b'a');
+            state_machine.feed(b'x');
+            assert!(state_machine.is_invalid());
+            for byte in [b'a', b'b', 0x00, 0x7f, 0x80, 0xff] {
+                state_machine.feed(byte);
+                assert!(state_machine.is_invalid());
+                assert!(!state_machine.is_final());
+            }
+        };
+    }
+    assert_stays_invalid!(re!("ab"));
+    assert_stays_invalid!(re!("ab", sparse));
+    assert_stays_invalid!(re!("ab", classes));
+    assert_stays_invalid!(re!("ab", flat));
+}
+
+#[test]
+fn exact_match_len() {
+    let regex = re!("[0-9]{4}");
+    assert_eq!(regex.match_at("123", 0), None);
+    assert_eq!(regex.match_at("12345", 2), None);
+    assert_eq!(regex.match_at("12a45", 0), None);
+    assert_eq!(regex.match_at("12345", 1).unwrap().as_str(), "2345");
+    assert_eq!(regex.find("x 2024-10").unwrap().range(), 2..6);
+
+    let regex = re!("^ab|cd$");
+    assert_eq!(regex.match_at("ab", 0).unwrap().as_str(), "ab");
+    assert_eq!(regex.match_at("xab", 1), None);
+    assert_eq!(regex.match_at("cd", 0).unwrap().as_str(), "cd");
+    assert_eq!(regex.match_at("cdx", 0), None);
+
+    let regex = re!("");
+    assert_eq!(regex.match_at("", 0).unwrap().as_str(), "");
+    assert_eq!(regex.match_at("abc", 3).unwrap().range(), 3..3);
+}
+
+#[test]
+fn state_machine_feed() {
+    let regex = re!("ab");
+    let mut state_machine = regex.state_machine();
+    assert!(!state_machine.is_final());
+    for byte in b"a" {
+        state_machine.feed(*byte);
+    }
+    assert!(!state_machine.is_final());
+    for byte in b"b" {
+        state_machine.feed(*byte);
+    }
+    assert!(state_machine.is_final());
+    state_machine.feed(b'c');
+    assert!(!state_machine.is_final());
+    assert!(state_machine.is_invalid());
+
+    state_machine.reset();
+    assert!(!state_machine.is_invalid());
+    state_machine.feed(b'a');
+    state_machine.feed(b'b');
+    assert!(state_machine.is_final());
+}
+
+#[test]
+fn literal_set() {
+    let regex = re!("foo|bar|ba|baz|");
+    assert_eq!(regex.match_at("foo", 0).unwrap().as_str(), "foo");
+    assert_eq!(regex.match_at("bazz", 0).unwrap().as_str(), "baz");
+    assert_eq!(regex.match_at("bat", 0).unwrap().as_str(), "ba");
+    assert_eq!(regex.match_at("fo", 0).unwrap().as_str(), "");
+    assert_eq!(regex.find("x bar").unwrap().range(), 0..0);
+
+    let regex = re!("foo|bar");
+    assert_eq!(regex.find("x bar foo").unwrap().range(), 2..5);
+    assert_eq!(regex.find_iter("foobarfo").count(), 2);
+    assert_eq!(regex.match_at("fo", 0), None);
+}
+
+#[test]
+fn sparse_table() {
+    macro_rules! assert_same {
+        ($pattern:literal, $($haystack:literal),+) => {
+            let dense = re!($pattern);
+            let sparse = re!($pattern, sparse);
+            for haystack in [$($haystack),+] {
+                for start in (0..=haystack.len()).filter(|&i| haystack.is_char_boundary(i)) {
+                    assert_eq!(
+                        dense.match_at(haystack, start).map(|m| m.range()),
+                        sparse.match_at(haystack, start).map(|m| m.range()),
+                        "pattern {:?} at {start} of {haystack:?}",
+                        $pattern,
+                    );
+                }
+            }
+        };
+    }
+    assert_same!("[0-9]+", "a1 b22 c333", "abc", "");
+    assert_same!("hello|help", "hello", "help", "hhelp", "hel");
+    assert_same!("[a-z]+[0-9]*", "abc123", "Abc1", "x9y");
+    assert_same!("^ab|b$", "abab", "bab", "b");
+    assert_same!("[^a]б", "бб", "aб", "ббa");
+
+    let regex = re!("[0-9]+", sparse, sink);
+    assert_eq!(regex.match_at("12a3", 0).unwrap().range(), 0..2);
+}
+
+#[test]
+fn flat_table() {
+    macro_rules! assert_same {
+        ($pattern:literal, $($haystack:literal),+) => {
+            let nested = re!($pattern);
+            let flat = re!($pattern, flat);
+            for haystack in [$($haystack),+] {
+                for start in (0..=haystack.len()).filter(|&i| haystack.is_char_boundary(i)) {
+                    assert_eq!(
+                        nested.match_at(haystack, start).map(|m| m.range()),
+                        flat.match_at(haystack, start).map(|m| m.range()),
+                        "pattern {:?} at {start} of {haystack:?}",
+                        $pattern,
+                    );
+                }
+            }
+        };
+    }
+    assert_same!("[0-9]+", "a1 b22 c333", "abc", "");
+    assert_same!("hello|help", "hello", "help", "hhelp", "hel");
+    assert_same!(".*foo.*", "xfoox", "f\noo", "бfooб");
+    assert_same!("^ab|b$", "abab", "bab", "b");
+    assert_same!("[^a]б", "бб", "aб", "ббa");
+
+    let regex = re!("[0-9]+", flat, sink, strict);
+    assert_eq!(regex.match_at("12a3", 0).unwrap().range(), 0..2);
+    assert_eq!(regex.match_at("a12", 0).unwrap_err().offset(), 0);
+}
+
+#[test]
+fn byte_classes() {
+    let dense = re!(".*foo.*");
+    let compressed = re!(".*foo.*", classes);
+    for haystack in [
+        "",
+        "foo",
+        "fo",
+        "xfoox",
+        "ffoo",
+        "fofoo",
+        "f\noo",
+        "foo\nbar",
+        "бfooб",
+        "фуу",
+        "\u{10FFFF}foo",
+    ] {
+        for start in (0..=haystack.len()).filter(|&i| haystack.is_char_boundary(i)) {
+            assert_eq!(
+                dense.match_at(haystack, start).map(|m| m.range()),
+                compressed.match_at(haystack, start).map(|m| m.range()),
+                "{haystack:?} at {start}",
+            );
+        }
+    }
+    // all the two-byte haystacks around the pattern's bytes
+    for first in ['f', 'o', 'x', '\n', 'я'] {
+        for second in ['f', 'o', 'x', '\n', 'я'] {
+            let haystack = format!("{first}{second}foo{first}{second}");
+            assert_eq!(
+                dense.match_at(&haystack, 0).map(|m| m.range()),
+                compressed.match_at(&haystack, 0).map(|m| m.range()),
+            );
+        }
+    }
+
+    let regex = re!("[0-9]+", classes, strict);
+    assert_eq!(regex.match_at("12a", 0).unwrap().range(), 0..2);
+    assert_eq!(regex.match_at("a12", 0).unwrap_err().offset(), 0);
+}
+
+#[test]
+fn non_greedy_repeat() {
+    // the DFA matcher always returns the leftmost-longest match
+    let regex = re!("a+?");
+    assert_eq!(regex.match_at("aaa", 0).unwrap().as_str(), "aaa");
+    let regex = re!("<.*?>");
+    assert_eq!(regex.match_at("<a><b>", 0).unwrap().as_str(), "<a><b>");
+}
+
+#[test]
+fn replacen() {
+    let regex = re!(",");
+    assert_eq!(regex.replacen("a,b,c,d", ";", 2), "a;b;c,d");
+    assert_eq!(regex.replacen("a,b,c,d", ";", 0), "a;b;c;d");
+    assert_eq!(regex.replacen("a,b,c,d", ";", 10), "a;b;c;d");
+    assert_eq!(regex.replacen("abcd", ";", 1), "abcd");
+    assert_eq!(regex.replacen("", ";", 1), "");
+
+    let regex = re!("[0-9]+");
+    assert_eq!(regex.replacen("a1 b22 c333", "<ў>", 2), "a<ў> b<ў> c333");
+
+    let regex = re!("x*");
+    assert_eq!(regex.replacen("ab", "-", 0), "-a-b-");
+}
+
+#[test]
+fn capture_groups() {
+    let regex = re!("(?<1>a)(?<2>b)?");
+    let m = regex.match_at("a", 0).unwrap();
+    assert_eq!(m.group(1), Some("a"));
+    assert_eq!(m.group(2), None);
+    assert_eq!(m.group(3), None);
+    let m = regex.match_at("ab", 0).unwrap();
+    assert_eq!(m.group(1), Some("a"));
+    assert_eq!(m.group(2), Some("b"));
+
+    // the positions are relative to the match, but the groups are slices of
+    // the haystack
+    let regex = re!("(?<key>[a-z]+)=(?<value>[0-9]*)");
+    let m = regex.find_iter("x; key=42;").next().unwrap();
+    assert_eq!(m.group("key"), Some("key"));
+    assert_eq!(m.group("value"), Some("42"));
+    assert_eq!(m.group(1), None);
+
+    // the group of the alternative that is not taken is invalidated
+    let regex = re!("(?<1>a+)|(?<2>b)");
+    let m = regex.match_at("aa", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("aa"), None));
+    let m = regex.match_at("b", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (None, Some("b")));
+
+    // the last iteration of a repeated group wins
+    let regex = re!("((?<1>a)|b)+");
+    assert_eq!(regex.match_at("ba", 0).unwrap().group(1), Some("a"));
+    assert_eq!(regex.match_at("ab", 0).unwrap().group(1), None);
+
+    // the groups after an alternation of different lengths
+    let regex = re!("(?<1>a|bb)(?<2>c)");
+    let m = regex.match_at("ac", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("a"), Some("c")));
+    let m = regex.match_at("bbc", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("bb"), Some("c")));
+    let regex = re!("(?<1>x(a|b+)y)(?<2>z)");
+    let m = regex.match_at("xbbbyz", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("xbbby"), Some("z")));
+
+    let regex = re!("(?<1>x)(?<2>y|z)$", strict);
+    assert_eq!(regex.match_at("xz", 0).unwrap().group(2), Some("z"));
+}
+
+#[test]
+fn pattern_values() {
+    #[derive(Debug, PartialEq)]
+    enum Action {
+        If,
+        Ident,
+        Number(u32),
+    }
+
+    let lexer = re! {
+        "if" => Action::If,
+        "[a-z]+" => Action::Ident,
+        "[0-9]+" => Action::Number(10),
+    };
+    let (m, action) = lexer.match_at("if", 0).unwrap();
+    assert_eq!((m.as_str(), action), ("if", &Action::If));
+    let (m, action) = lexer.match_at("foo", 0).unwrap();
+    assert_eq!((m.as_str(), action), ("foo", &Action::Ident));
+    // the longest match wins over the first pattern
+    let (m, action) = lexer.match_at("iffy", 0).unwrap();
+    assert_eq!((m.as_str(), action), ("iffy", &Action::Ident));
+    let (m, action) = lexer.match_at("x = 42;", 4).unwrap();
+    assert_eq!((m.as_str(), action), ("42", &Action::Number(10)));
+    assert_eq!(m.pattern(), 2);
+    assert!(lexer.match_at("-", 0).is_none());
+    assert_eq!(lexer.regex().find_all("if x1").len(), 3);
+
+    let lexer = re! { "a$" => 'e', "ab" => 'b', strict };
+    assert_eq!(lexer.match_at("a", 0).unwrap().1, &'e');
+    assert_eq!(lexer.match_at("ab", 0).unwrap().1, &'b');
+    assert_eq!(lexer.match_at("b", 0).unwrap_err().offset(), 0);
+}
+
+#[test]
+fn match_segments() {
+    let regex = re!("abc");
+    assert_eq!(regex.match_segments(&[b"ab", b"cX"], 0), Some((0, 3)));
+    assert_eq!(
+        regex.match_segments(&[b"a", b"", b"b", b"c"], 0),
+        Some((0, 3))
+    );
+    assert_eq!(regex.match_segments(&[b"xa", b"bc"], 1), Some((1, 4)));
+    assert_eq!(regex.match_segments(&[b"xa", b"bc"], 0), None);
+    assert_eq!(regex.match_segments(&[b"ab"], 0), None);
+    assert_eq!(regex.match_segments(&[b"ab"], 3), None);
+
+    let regex = re!("[0-9]+$");
+    assert_eq!(regex.match_segments(&[b"12", b"34"], 0), Some((0, 4)));
+    assert_eq!(regex.match_segments(&[b"12", b"3x"], 0), None);
+
+    let regex = re!("^a*");
+    assert_eq!(regex.match_segments(&[b"aa", b"ab"], 0), Some((0, 3)));
+    assert_eq!(regex.match_segments(&[b"aa", b"ab"], 1), None);
+}
+
+#[test]
+fn match_at_bytes() {
+    let regex = re!("ab+c");
+    let haystack = b"\xFFabbc\xC3";
+    let m = regex.match_at_bytes(haystack, 1).unwrap();
+    assert_eq!(m.as_bytes(), b"abbc");
+    assert_eq!(m.range(), 1..5);
+    assert_eq!(m.len(), 4);
+    assert_eq!(regex.match_at_bytes(haystack, 0), None);
+    assert_eq!(regex.match_at_bytes(haystack, 7), None);
+
+    // the pattern matches only valid UTF-8, but the haystack can be invalid
+    let regex = re!("[^a]+");
+    let m = regex.match_at_bytes("яa".as_bytes(), 0).unwrap();
+    assert_eq!(m.as_bytes(), "я".as_bytes());
+    assert_eq!(regex.match_at_bytes("яa".as_bytes(), 1), None);
+    assert_eq!(regex.match_at_bytes(b"\x80\xFFa", 0), None);
+
+    let regex = re!("^x*$");
+    assert_eq!(regex.match_at_bytes(b"xx", 0).unwrap().range(), 0..2);
+    assert_eq!(regex.match_at_bytes(b"xx", 1), None);
+    assert_eq!(regex.match_at_bytes(b"", 0).unwrap().range(), 0..0);
+}
+
+#[test]
+fn latin1() {
+    let regex = re!("café", latin1);
+    let m = regex.match_at_bytes(b"caf\xE9", 0).unwrap();
+    assert_eq!(m.as_bytes(), b"caf\xE9");
+    assert_eq!(m.len(), 4);
+    assert_eq!(regex.match_at_bytes("café".as_bytes(), 0), None);
+    assert_eq!(regex.match_segments(&[b"xca", b"f\xE9"], 1), Some((1, 5)));
+
+    // a dot is a single byte of any value
+    let regex = re!("^a.+(b)$", latin1, i);
+    assert_eq!(regex.match_at_bytes(b"A\xFF\x80B", 0).unwrap().len(), 4);
+    assert_eq!(regex.match_at_bytes(b"A\xFFB\xFF", 0), None);
+
+    let regex = re!("[^é]", latin1);
+    assert_eq!(
+        regex.match_at_bytes(b"\xE9\xC3", 1).unwrap().as_bytes(),
+        b"\xC3"
+    );
+    assert_eq!(regex.match_at_bytes(b"\xE9\xC3", 0), None);
+}
+
+#[test]
+fn leftmost_first() {
+    let longest = re!("a|ab");
+    let first = re!("a|ab", first);
+    assert_eq!(longest.match_at("ab", 0).unwrap().as_str(), "ab");
+    assert_eq!(first.match_at("ab", 0).unwrap().as_str(), "a");
+
+    // a later alternative is taken only if the earlier ones don't match
+    let first = re!("ab|a|abc", first);
+    assert_eq!(first.match_at("abc", 0).unwrap().as_str(), "ab");
+    assert_eq!(first.match_at("ac", 0).unwrap().as_str(), "a");
+    let first = re!("x|[a-z]+", first, strict);
+    assert_eq!(first.match_at("xyz", 0).unwrap().as_str(), "x");
+    assert_eq!(first.match_at("yz", 0).unwrap().as_str(), "yz");
+    assert_eq!(first.match_at("1", 0).unwrap_err().offset(), 0);
+
+    // an alternative keeps the longest match of its own
+    let first = re!("a+|a+b", first, sink);
+    assert_eq!(first.match_at("aaab", 0).unwrap().as_str(), "aaa");
+    assert_eq!(
+        first
+            .find_all("aab ab")
+            .iter()
+            .map(|m| m.as_str())
+            .collect::<Vec<_>>(),
+        ["aa", "a"]
+    );
+    assert_eq!(first.match_segments(&[b"a", b"ab"], 0), Some((0, 2)));
+
+    let first = re!("a$|ab", first);
+    assert_eq!(first.match_at("a", 0).unwrap().as_str(), "a");
+    assert_eq!(first.match_at("ab", 0).unwrap().as_str(), "ab");
+
+    let lexer = re! { "if" => 0, "[a-z]+" => 1, first };
+    let (m, value) = lexer.match_at("iffy", 0).unwrap();
+    assert_eq!((m.as_str(), value), ("if", &0));
+    let (m, value) = lexer.match_at("foo", 0).unwrap();
+    assert_eq!((m.as_str(), value), ("foo", &1));
+}
+
+#[test]
+fn find() {
+    let regex = re!("ll");
+    let m = regex.find("hello").unwrap();
+    assert_eq!((m.start(), m.as_str()), (2, "ll"));
+    assert_eq!(regex.find("help"), None);
+    assert_eq!(regex.find(""), None);
+
+    let m = re!("x*").find("abc").unwrap();
+    assert_eq!((m.start(), m.as_str()), (0, ""));
+    let m = re!("x*").find("").unwrap();
+    assert_eq!((m.start(), m.as_str()), (0, ""));
+    let m = re!("$").find("ab").unwrap();
+    assert_eq!(m.start(), 2);
+
+    let m = re!("[0-9]+", strict).find("ўa42").unwrap();
+    assert_eq!((m.start(), m.as_str()), (3, "42"));
+    assert_eq!(re!("a", anchored_start).find("ba"), None);
+}
+
+#[test]
+fn prefixes() {
+    let regex = re!("a+");
+    let lens = |haystack, start| {
+        regex
+            .prefixes(haystack, start)
+            .map(|m| m.len())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(lens("aaa", 0), [1, 2, 3]);
+    assert_eq!(lens("aaba", 0), [1, 2]);
+    assert_eq!(lens("aaba", 3), [1]);
+    assert_eq!(lens("baa", 0), Vec::<usize>::new());
+    assert_eq!(lens("aaa", 3), Vec::<usize>::new());
+
+    let regex = re!("(ab)*", sink);
+    let m = regex.prefixes("ababx", 0).collect::<Vec<_>>();
+    assert_eq!(
+        m.iter().map(|m| m.as_str()).collect::<Vec<_>>(),
+        ["", "ab", "abab"]
+    );
+
+    let regex = re!("a|ab$|abc");
+    let m = regex
+        .prefixes("abc", 0)
+        .map(|m| m.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(m, ["a", "abc"]);
+    let m = regex
+        .prefixes("ab", 0)
+        .map(|m| m.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(m, ["a", "ab"]);
+
+    let regex = re!("(?<1>a+)b?", strict);
+    let groups = regex
+        .prefixes("aab", 0)
+        .map(|m| (m.as_str(), m.group(1)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        groups,
+        [("a", Some("a")), ("aa", Some("aa")), ("aab", Some("aa"))]
+    );
+
+    let lexer = re! { "if" => 0, "[a-z]+" => 1 };
+    let patterns = lexer
+        .regex()
+        .prefixes("iff", 0)
+        .map(|m| m.pattern())
+        .collect::<Vec<_>>();
+    assert_eq!(patterns, [1, 0, 1]);
+}
+
+#[test]
+fn capture_groups_with_conflicts() {
+    // the registers of the DFA would mix `ab` of the first path and `cd` of
+    // the second one, so the groups are resolved by the earlier alternative
+    let regex = re!("(?<1>a|ab)(?<2>c|bcd)");
+    let m = regex.match_at("abcd", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("a"), Some("bcd")));
+    let m = regex.match_at("abc", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("ab"), Some("c")));
+
+    // a greedy repetition takes as much as it can, and a lazy one leaves it
+    let regex = re!("(?<1>a*)(?<2>a*)");
+    let m = regex.match_at("aaa", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("aaa"), Some("")));
+    let regex = re!("(?<1>a*?)(?<2>a*)");
+    let m = regex.match_at("aaa", 0).unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some(""), Some("aaa")));
+
+    // the groups are resolved over the haystack, so anchors hold
+    let regex = re!("x(?<1>a|ab)(?<2>c|bcd)$");
+    let m = regex.find("zzxabcd").unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("a"), Some("bcd")));
+    assert_eq!(regex.find("zzxabcdz"), None);
+    let m = regex.prefixes("xabcd", 0).last().unwrap();
+    assert_eq!((m.group(1), m.group(2)), (Some("a"), Some("bcd")));
+
+    let regex = re!("(?<1>a)|ab");
+    assert_eq!(regex.match_at("a", 0).unwrap().group(1), Some("a"));
+    assert_eq!(regex.match_at("ab", 0).unwrap().group(1), None);
+
+    // every pattern is resolved by its own program
+    let lexer = re! { "(?<1>a|ab)(?<2>c|bcd)" => 'a', "(?<1>x*)(?<2>x*)" => 'x', strict };
+    let (m, value) = lexer.match_at("abcd", 0).unwrap();
+    assert_eq!(
+        (m.group(1), m.group(2), *value),
+        (Some("a"), Some("bcd"), 'a')
+    );
+    let (m, value) = lexer.match_at("xx", 0).unwrap();
+    assert_eq!(
+        (m.group(1), m.group(2), *value),
+        (Some("xx"), Some(""), 'x')
+    );
+}
+
+/// Only nightly compilers can point the errors into a literal, and the stable
+/// ones underline the whole literal.
+#[rustversion::attr(not(nightly), ignore)]
+#[test]
+fn ui() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,5 @@
+use remc::re;
+
+fn main() {
+    let _ = re!("a{3,1}");
+}
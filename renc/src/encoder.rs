@@ -1,10 +1,15 @@
 use crate::encoding::Encoding;
-use crate::error::Result;
-use redt::Range;
+use crate::error::{Error, Result};
+use redt::{Range, RangeU8};
 
 /// This trait helps convert unicode code points into byte sequences
 /// corresponding to the encoding way chosen by the user.
 pub trait Encoder {
+    /// The maximum length of a byte sequence of one encoded code point, so a
+    /// buffer of this size is always enough for [`Encoder::encode_ucp`] and
+    /// [`Encoder::encode_char`].
+    const MAX_BYTES_PER_CODEPOINT: usize;
+
     fn encoding(&self) -> Encoding;
 
     /// Encode unicode code point into a byte sequence
@@ -24,6 +29,27 @@ pub trait Encoder {
     where
         F: FnMut(&[Range<u8>]);
 
+    /// Encode range of unicode code points into a vector of byte range
+    /// sequences.
+    ///
+    /// It collects the same sequences as [`Encoder::encode_range`] passes to
+    /// its handler, but fails if any bound of the range is out of the
+    /// encoding's code point range.
+    fn encode_range_to_vec(&self, start_ucp: u32, last_ucp: u32) -> Result<Vec<Vec<RangeU8>>> {
+        let encoding = self.encoding();
+        for codepoint in [start_ucp, last_ucp] {
+            if codepoint < encoding.min_codepoint() || encoding.max_codepoint() < codepoint {
+                return Err(Error::InvalidCodePoint {
+                    codepoint,
+                    encoding,
+                });
+            }
+        }
+        let mut sequences = Vec::new();
+        self.encode_range(start_ucp, last_ucp, |seq| sequences.push(seq.to_vec()));
+        Ok(sequences)
+    }
+
     /// Encode the entire range of code points allowed by this coder into array
     /// of byte sequences.
     fn encode_entire_range<F>(&self, handler: F)
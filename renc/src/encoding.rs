@@ -3,6 +3,7 @@ use redt::Range;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Encoding {
     Ascii,
+    Latin1,
     Utf8,
 }
 
@@ -11,6 +12,7 @@ impl Encoding {
     pub const fn name(&self) -> &'static str {
         match self {
             Encoding::Ascii => "ASCII",
+            Encoding::Latin1 => "ISO-8859-1",
             Encoding::Utf8 => "UTF-8",
         }
     }
@@ -19,6 +21,7 @@ impl Encoding {
     pub const fn allows_surrogates(&self) -> bool {
         match self {
             Encoding::Ascii => false,
+            Encoding::Latin1 => false,
             Encoding::Utf8 => false,
         }
     }
@@ -27,6 +30,7 @@ impl Encoding {
     pub const fn min_codepoint(&self) -> u32 {
         match self {
             Encoding::Ascii => 0,
+            Encoding::Latin1 => 0,
             Encoding::Utf8 => 0,
         }
     }
@@ -35,13 +39,25 @@ impl Encoding {
     pub const fn max_codepoint(&self) -> u32 {
         match self {
             Encoding::Ascii => 0x7F,
+            Encoding::Latin1 => 0xFF,
             Encoding::Utf8 => 0x10FFFF,
         }
     }
 
+    /// Returns the maximum number of bytes one code point is encoded with.
+    #[inline]
+    pub const fn max_bytes_per_codepoint(&self) -> usize {
+        match self {
+            Encoding::Ascii => 1,
+            Encoding::Latin1 => 1,
+            Encoding::Utf8 => 4,
+        }
+    }
+
     #[inline]
     pub fn codepoint_ranges(&self) -> &'static [Range<u32>] {
         static ASCII_RANGES: &[Range<u32>] = &[Range::new_unchecked_const(0, 0x7F)];
+        static LATIN1_RANGES: &[Range<u32>] = &[Range::new_unchecked_const(0, 0xFF)];
         static UTF_RANGES: &[Range<u32>] = &[
             Range::new_unchecked_const(0, 0xD7FF),
             Range::new_unchecked_const(0xE000, 0x10FFFF),
@@ -49,7 +65,27 @@ impl Encoding {
 
         match self {
             Encoding::Ascii => ASCII_RANGES,
+            Encoding::Latin1 => LATIN1_RANGES,
             Encoding::Utf8 => UTF_RANGES,
         }
     }
+
+    /// Returns `true` if the `codepoint` can be encoded, i.e. it's within one
+    /// of the [`Encoding::codepoint_ranges`].
+    #[inline]
+    pub fn is_valid_codepoint(&self, codepoint: u32) -> bool {
+        self.codepoint_ranges()
+            .iter()
+            .any(|range| range.start() <= codepoint && codepoint <= range.last())
+    }
+
+    /// Returns the number of code points that can be encoded, e.g. surrogates
+    /// aren't counted for UTF-8.
+    #[inline]
+    pub fn codepoint_count(&self) -> u64 {
+        self.codepoint_ranges()
+            .iter()
+            .map(|range| (range.last() - range.start()) as u64 + 1)
+            .sum()
+    }
 }
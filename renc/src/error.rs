@@ -20,6 +20,10 @@ pub enum Error {
 
     /// Error returned when the provided output buffer is too small to hold the
     /// encoded byte sequence.
+    ///
+    /// It's never returned for one code point if the buffer has at least
+    /// [`Encoder::MAX_BYTES_PER_CODEPOINT`](crate::Encoder::MAX_BYTES_PER_CODEPOINT)
+    /// bytes.
     #[error("output buffer for the encoded byte sequence is too small")]
     SmallBuffer,
 
@@ -36,4 +40,43 @@ pub enum Error {
     /// - `0`: The invalid code point value.
     #[error("invalid unicode code point '\\x{codepoint:X}' for {} encoding", encoding.name())]
     InvalidCodePoint { codepoint: u32, encoding: Encoding },
+
+    /// Error returned when a decoded byte sequence starts with a byte that
+    /// can't be the first byte of an encoded code point.
+    ///
+    /// # Parameters
+    ///
+    /// - `byte`: The invalid byte.
+    /// - `offset`: The offset of the byte in the decoded sequence.
+    #[error("invalid leading byte {byte:02X}h at offset {offset}")]
+    InvalidLeadingByte { byte: u8, offset: usize },
+
+    /// Error returned when a byte of a multi-byte sequence isn't a valid
+    /// continuation byte.
+    ///
+    /// # Parameters
+    ///
+    /// - `byte`: The invalid byte.
+    /// - `offset`: The offset of the byte in the decoded sequence.
+    #[error("invalid continuation byte {byte:02X}h at offset {offset}")]
+    InvalidContinuation { byte: u8, offset: usize },
+
+    /// Error returned when a code point is encoded with more bytes than it
+    /// needs.
+    ///
+    /// # Parameters
+    ///
+    /// - `codepoint`: The encoded code point.
+    /// - `offset`: The offset of the sequence in the decoded bytes.
+    #[error("overlong encoding of code point {codepoint:X}h at offset {offset}")]
+    OverlongEncoding { codepoint: u32, offset: usize },
+
+    /// Error returned when a decoded byte sequence ends in the middle of an
+    /// encoded code point.
+    ///
+    /// # Parameters
+    ///
+    /// - `offset`: The offset where the next byte is expected.
+    #[error("unexpected end of the byte sequence at offset {offset}")]
+    UnexpectedEnd { offset: usize },
 }
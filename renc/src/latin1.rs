@@ -0,0 +1,79 @@
+use crate::encoder::Encoder;
+use crate::encoding::Encoding;
+use crate::error::{Error::*, Result};
+use redt::Range;
+
+const ENCODING: Encoding = Encoding::Latin1;
+
+/// Encoder for ISO-8859-1 (Latin-1), where every code point from `0` to `FFh`
+/// is encoded as a single byte of the same value.
+pub struct Latin1Encoder;
+
+impl Latin1Encoder {
+    #[inline]
+    pub fn new() -> Self {
+        Latin1Encoder
+    }
+}
+
+impl Default for Latin1Encoder {
+    #[inline]
+    fn default() -> Self {
+        Latin1Encoder::new()
+    }
+}
+
+impl Encoder for Latin1Encoder {
+    const MAX_BYTES_PER_CODEPOINT: usize = ENCODING.max_bytes_per_codepoint();
+
+    #[inline]
+    fn encoding(&self) -> Encoding {
+        ENCODING
+    }
+
+    fn encode_char(&self, c: char, buffer: &mut [u8]) -> Result<usize> {
+        self.encode_ucp(c as u32, buffer)
+    }
+
+    fn encode_ucp(&self, codepoint: u32, buffer: &mut [u8]) -> Result<usize> {
+        let byte = byte_try_from(codepoint)?;
+        let Some(first) = buffer.first_mut() else {
+            return Err(SmallBuffer);
+        };
+        *first = byte;
+        Ok(1)
+    }
+
+    fn encode_str(&self, s: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut len = 0;
+        for c in s.chars() {
+            len += self.encode_char(c, &mut buffer[len..])?;
+        }
+        Ok(len)
+    }
+
+    fn encode_range<F>(&self, start_ucp: u32, end_ucp: u32, handler: F)
+    where
+        F: FnMut(&[Range<u8>]),
+    {
+        let mut handler = handler;
+        let end_ucp = end_ucp.min(ENCODING.max_codepoint());
+        if start_ucp <= end_ucp {
+            handler(&[Range::new(start_ucp as u8, end_ucp as u8)]);
+        }
+    }
+
+    fn encode_entire_range<F>(&self, handler: F)
+    where
+        F: FnMut(&[Range<u8>]),
+    {
+        self.encode_range(ENCODING.min_codepoint(), ENCODING.max_codepoint(), handler);
+    }
+}
+
+fn byte_try_from(codepoint: u32) -> Result<u8> {
+    u8::try_from(codepoint).map_err(|_| InvalidCodePoint {
+        codepoint,
+        encoding: ENCODING,
+    })
+}
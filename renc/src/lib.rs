@@ -7,5 +7,8 @@ pub use error::{Error, Result};
 mod encoding;
 pub use encoding::Encoding;
 
+mod latin1;
+pub use latin1::Latin1Encoder;
+
 mod utf8;
 pub use utf8::Utf8Encoder;
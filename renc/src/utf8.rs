@@ -15,6 +15,51 @@ impl Utf8Encoder {
     }
 }
 
+/// Decoding API
+impl Utf8Encoder {
+    /// Decodes the first char of the UTF-8 byte sequence, and returns it
+    /// together with the number of bytes it takes.
+    pub fn decode_next(&self, bytes: &[u8]) -> Result<(char, usize)> {
+        decode_at(bytes, 0)
+    }
+
+    /// Checks that the whole byte sequence is a valid UTF-8 string, and
+    /// returns it as a string slice.
+    pub fn decode_str<'b>(&self, bytes: &'b [u8]) -> Result<&'b str> {
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let (_, len) = decode_at(bytes, offset)?;
+            offset += len;
+        }
+        // SAFETY: every char of the sequence has been validated above
+        Ok(unsafe { str::from_utf8_unchecked(bytes) })
+    }
+
+    /// Returns `true` if the byte sequence is a valid UTF-8 string, or a valid
+    /// beginning of it cut in the middle of the last char, i.e. more bytes may
+    /// make it valid. Returns `false` if no bytes can make it valid.
+    pub fn is_valid_prefix(&self, bytes: &[u8]) -> bool {
+        let mut offset = 0;
+        while offset < bytes.len() {
+            match decode_at(bytes, offset) {
+                Ok((_, len)) => offset += len,
+                Err(UnexpectedEnd { .. }) => {
+                    // continuation bytes are already validated, but overlong
+                    // encodings, surrogates and too big code points can be
+                    // caught by the first two bytes only
+                    let tail = &bytes[offset..];
+                    let Some(second_bytes) = second_byte_range(tail[0]) else {
+                        return false;
+                    };
+                    return tail.get(1).is_none_or(|byte| second_bytes.contains(byte));
+                }
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
 impl Default for Utf8Encoder {
     #[inline]
     fn default() -> Self {
@@ -23,6 +68,8 @@ impl Default for Utf8Encoder {
 }
 
 impl Encoder for Utf8Encoder {
+    const MAX_BYTES_PER_CODEPOINT: usize = ENCODING.max_bytes_per_codepoint();
+
     #[inline]
     fn encoding(&self) -> Encoding {
         ENCODING
@@ -59,6 +106,15 @@ impl Encoder for Utf8Encoder {
     {
         let range = Range::new(start_ucp, end_ucp);
         let mut handler = handler;
+        // ASCII ranges are the most common ones in classes, and they are
+        // encoded with one sequence of one byte
+        if range.last() <= 0x7F {
+            handler(&[Range::new_unchecked(
+                range.start() as u8,
+                range.last() as u8,
+            )]);
+            return;
+        }
         encode_range(range, &mut handler);
     }
 
@@ -205,6 +261,54 @@ fn run_handler<const N: usize>(start: u32, end: u32, handler: &mut impl FnMut(&[
     handler(&buffer);
 }
 
+/// Decodes the char starting at the `offset` of the `bytes`. Offsets in errors
+/// are relative to the beginning of the `bytes`.
+fn decode_at(bytes: &[u8], offset: usize) -> Result<(char, usize)> {
+    let Some(&lead) = bytes.get(offset) else {
+        return Err(UnexpectedEnd { offset });
+    };
+    let (len, mut codepoint) = match lead {
+        0x00..=0x7F => return Ok((lead as char, 1)),
+        0xC0..=0xDF => (2, (lead & 0x1F) as u32),
+        0xE0..=0xEF => (3, (lead & 0x0F) as u32),
+        0xF0..=0xF7 => (4, (lead & 0x07) as u32),
+        _ => {
+            return Err(InvalidLeadingByte { byte: lead, offset });
+        }
+    };
+    for i in offset + 1..offset + len {
+        let Some(&byte) = bytes.get(i) else {
+            return Err(UnexpectedEnd { offset: i });
+        };
+        if byte & 0xC0 != 0x80 {
+            return Err(InvalidContinuation { byte, offset: i });
+        }
+        codepoint = (codepoint << 6) | (byte & 0x3F) as u32;
+    }
+    let min_codepoint = match len {
+        2 => 0x80,
+        3 => 0x800,
+        _ => 0x10000,
+    };
+    if codepoint < min_codepoint {
+        return Err(OverlongEncoding { codepoint, offset });
+    }
+    Ok((char_try_from(codepoint)?, len))
+}
+
+/// Returns the range of valid second bytes of a multibyte sequence with the
+/// `lead` byte, or `None` if the `lead` byte can't start a multibyte sequence.
+fn second_byte_range(lead: u8) -> Option<std::ops::RangeInclusive<u8>> {
+    match lead {
+        0xC2..=0xDF | 0xE1..=0xEC | 0xEE..=0xEF | 0xF1..=0xF3 => Some(0x80..=0xBF),
+        0xE0 => Some(0xA0..=0xBF),
+        0xED => Some(0x80..=0x9F),
+        0xF0 => Some(0x90..=0xBF),
+        0xF4 => Some(0x80..=0x8F),
+        _ => None,
+    }
+}
+
 fn char_try_from(codepoint: u32) -> Result<char> {
     if let Ok(c) = char::try_from(codepoint) {
         Ok(c)
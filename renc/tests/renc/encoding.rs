@@ -9,9 +9,21 @@ fn encoding_ascii() {
     assert_eq!(encoding.allows_surrogates(), false);
     assert_eq!(encoding.min_codepoint(), 0);
     assert_eq!(encoding.max_codepoint(), 0x7F);
+    assert_eq!(encoding.max_bytes_per_codepoint(), 1);
     assert_eq!(encoding.codepoint_ranges(), &[Range::new(0, 0x7f)]);
 }
 
+#[test]
+fn encoding_latin1() {
+    let encoding = Encoding::Latin1;
+    assert_eq!(encoding.name(), "ISO-8859-1");
+    assert_eq!(encoding.allows_surrogates(), false);
+    assert_eq!(encoding.min_codepoint(), 0);
+    assert_eq!(encoding.max_codepoint(), 0xFF);
+    assert_eq!(encoding.max_bytes_per_codepoint(), 1);
+    assert_eq!(encoding.codepoint_ranges(), &[Range::new(0, 0xFF)]);
+}
+
 #[test]
 fn encoding_utf8() {
     let encoding = Encoding::Utf8;
@@ -19,8 +31,33 @@ fn encoding_utf8() {
     assert_eq!(encoding.allows_surrogates(), false);
     assert_eq!(encoding.min_codepoint(), 0);
     assert_eq!(encoding.max_codepoint(), 0x10FFFF);
+    assert_eq!(encoding.max_bytes_per_codepoint(), 4);
     assert_eq!(
         encoding.codepoint_ranges(),
         &[Range::new(0, 0xD7FF), Range::new(0xE000, 0x10FFFF)]
     );
 }
+
+#[test]
+fn encoding_codepoint_validation() {
+    let utf8 = Encoding::Utf8;
+    assert!(utf8.is_valid_codepoint(0));
+    assert!(utf8.is_valid_codepoint(0xD7FF));
+    assert!(!utf8.is_valid_codepoint(0xD800));
+    assert!(!utf8.is_valid_codepoint(0xDFFF));
+    assert!(utf8.is_valid_codepoint(0xE000));
+    assert!(utf8.is_valid_codepoint(0x10FFFF));
+    assert!(!utf8.is_valid_codepoint(0x110000));
+    assert!(!utf8.is_valid_codepoint(u32::MAX));
+    assert_eq!(utf8.codepoint_count(), 0x110000 - 0x800);
+
+    let latin1 = Encoding::Latin1;
+    assert!(latin1.is_valid_codepoint(0xFF));
+    assert!(!latin1.is_valid_codepoint(0x100));
+    assert_eq!(latin1.codepoint_count(), 0x100);
+
+    let ascii = Encoding::Ascii;
+    assert!(ascii.is_valid_codepoint(0x7F));
+    assert!(!ascii.is_valid_codepoint(0x80));
+    assert_eq!(ascii.codepoint_count(), 0x80);
+}
@@ -0,0 +1,94 @@
+use pretty_assertions::assert_eq;
+use redt::Range;
+use renc::{Encoder, Encoding, Error, Latin1Encoder};
+
+static CODER: Latin1Encoder = Latin1Encoder;
+
+fn encode_range(start: u32, end: u32) -> Vec<Vec<Range<u8>>> {
+    let mut sequences = Vec::new();
+    CODER.encode_range(start, end, |seq| sequences.push(seq.to_vec()));
+    sequences
+}
+
+#[test]
+fn latin1_encoding() {
+    assert_eq!(CODER.encoding(), Encoding::Latin1);
+}
+
+#[test]
+fn latin1_encode_ucp() {
+    let mut buffer = [0u8; 4];
+    assert_eq!(CODER.encode_ucp(0, &mut buffer), Ok(1));
+    assert_eq!(buffer[0], 0);
+    assert_eq!(CODER.encode_ucp(b'a' as u32, &mut buffer), Ok(1));
+    assert_eq!(buffer[0], b'a');
+    assert_eq!(CODER.encode_ucp(0xE9, &mut buffer), Ok(1));
+    assert_eq!(buffer[0], 0xE9);
+    assert_eq!(CODER.encode_ucp(0xFF, &mut buffer), Ok(1));
+    assert_eq!(buffer[0], 0xFF);
+
+    assert_eq!(
+        CODER.encode_ucp(0x100, &mut buffer),
+        Err(Error::InvalidCodePoint {
+            codepoint: 0x100,
+            encoding: Encoding::Latin1
+        })
+    );
+    assert_eq!(
+        CODER.encode_ucp(b'a' as u32, &mut []),
+        Err(Error::SmallBuffer)
+    );
+}
+
+#[test]
+fn latin1_encode_str() {
+    let mut buffer = [0u8; 8];
+    assert_eq!(CODER.encode_char('é', &mut buffer), Ok(1));
+    assert_eq!(buffer[0], 0xE9);
+
+    assert_eq!(CODER.encode_str("café", &mut buffer), Ok(4));
+    assert_eq!(&buffer[..4], b"caf\xE9");
+    assert_eq!(CODER.encode_str("", &mut buffer), Ok(0));
+    assert_eq!(
+        CODER.encode_str("café", &mut buffer[..3]),
+        Err(Error::SmallBuffer)
+    );
+    assert_eq!(
+        CODER.encode_str("caў", &mut buffer),
+        Err(Error::InvalidCodePoint {
+            codepoint: 'ў' as u32,
+            encoding: Encoding::Latin1
+        })
+    );
+}
+
+#[test]
+fn latin1_encode_range() {
+    assert_eq!(encode_range(0x41, 0x5A), [[Range::new(0x41, 0x5A)]]);
+    assert_eq!(encode_range(0xE9, 0xE9), [[Range::new(0xE9, 0xE9)]]);
+    // invalid code points are ignored
+    assert_eq!(encode_range(0xF0, 0x10FFFF), [[Range::new(0xF0, 0xFF)]]);
+    assert_eq!(encode_range(0x100, 0x200), Vec::<Vec<_>>::new());
+
+    let mut sequences = Vec::new();
+    CODER.encode_entire_range(|seq| sequences.push(seq.to_vec()));
+    assert_eq!(sequences, [[Range::new(0, 0xFF)]]);
+
+    assert_eq!(
+        CODER.encode_range_to_vec(0x61, 0x100),
+        Err(Error::InvalidCodePoint {
+            codepoint: 0x100,
+            encoding: Encoding::Latin1
+        })
+    );
+}
+
+#[test]
+fn latin1_max_bytes_per_codepoint() {
+    assert_eq!(Latin1Encoder::MAX_BYTES_PER_CODEPOINT, 1);
+    let mut buffer = [0u8; Latin1Encoder::MAX_BYTES_PER_CODEPOINT];
+    for codepoint in 0..=Encoding::Latin1.max_codepoint() {
+        assert_eq!(CODER.encode_ucp(codepoint, &mut buffer), Ok(1));
+        assert_eq!(buffer[0] as u32, codepoint);
+    }
+}
@@ -1,2 +1,3 @@
 mod encoding;
+mod latin1;
 mod utf8;
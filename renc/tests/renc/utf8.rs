@@ -3,7 +3,7 @@ use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
 use redt::{Range, range};
 use regex_syntax::utf8::{Utf8Sequence, Utf8Sequences};
-use renc::{Encoder, Error, Result, Utf8Encoder};
+use renc::{Encoder, Encoding, Error, Result, Utf8Encoder};
 use std::ops::RangeInclusive;
 
 static CODER: Utf8Encoder = Utf8Encoder;
@@ -165,6 +165,133 @@ fn encode_str_fails() {
     );
 }
 
+#[test]
+fn decode_next() {
+    assert_eq!(CODER.decode_next(b"abc"), Ok(('a', 1)));
+    assert_eq!(CODER.decode_next(&[0xD1, 0x9E, b'a']), Ok(('ў', 2)));
+    assert_eq!(CODER.decode_next(&[0xE2, 0xB2, 0x80]), Ok(('Ⲁ', 3)));
+    assert_eq!(CODER.decode_next(&[0xF0, 0x90, 0x8C, 0xB0]), Ok(('𐌰', 4)));
+    assert_eq!(
+        CODER.decode_next("\u{10FFFF}".as_bytes()),
+        Ok(('\u{10FFFF}', 4))
+    );
+}
+
+#[test]
+fn decode_next_fails() {
+    assert_eq!(
+        CODER.decode_next(&[]),
+        Err(Error::UnexpectedEnd { offset: 0 })
+    );
+    assert_eq!(
+        CODER.decode_next(&[0xF0, 0x90, 0x8C]),
+        Err(Error::UnexpectedEnd { offset: 3 })
+    );
+    assert_eq!(
+        CODER.decode_next(&[0x9E]),
+        Err(Error::InvalidLeadingByte {
+            byte: 0x9E,
+            offset: 0
+        })
+    );
+    assert_eq!(
+        CODER.decode_next(&[0xF8, 0x80, 0x80, 0x80]),
+        Err(Error::InvalidLeadingByte {
+            byte: 0xF8,
+            offset: 0
+        })
+    );
+    assert_eq!(
+        CODER.decode_next(&[0xE2, 0xB2, b'a']),
+        Err(Error::InvalidContinuation {
+            byte: b'a',
+            offset: 2
+        })
+    );
+    assert_eq!(
+        CODER.decode_next(&[0xD1, 0xD1, 0x9E]),
+        Err(Error::InvalidContinuation {
+            byte: 0xD1,
+            offset: 1
+        })
+    );
+
+    // overlong encodings of '/'
+    assert_eq!(
+        CODER.decode_next(&[0xC0, 0xAF]),
+        Err(Error::OverlongEncoding {
+            codepoint: 0x2F,
+            offset: 0
+        })
+    );
+    assert_eq!(
+        CODER.decode_next(&[0xE0, 0x80, 0xAF]),
+        Err(Error::OverlongEncoding {
+            codepoint: 0x2F,
+            offset: 0
+        })
+    );
+    assert_eq!(
+        CODER.decode_next(&[0xF0, 0x80, 0x80, 0xAF]),
+        Err(Error::OverlongEncoding {
+            codepoint: 0x2F,
+            offset: 0
+        })
+    );
+
+    assert_matches!(
+        CODER.decode_next(&[0xED, 0xA0, 0x80]),
+        Err(Error::SurrogateUnsupported {
+            codepoint: 0xD800,
+            ..
+        })
+    );
+    assert_matches!(
+        CODER.decode_next(&[0xF4, 0x90, 0x80, 0x80]),
+        Err(Error::InvalidCodePoint {
+            codepoint: 0x110000,
+            ..
+        })
+    );
+}
+
+#[test]
+fn decode_str() {
+    assert_eq!(CODER.decode_str(b""), Ok(""));
+    assert_eq!(CODER.decode_str(b"abc"), Ok("abc"));
+    assert_eq!(
+        CODER.decode_str(&[0xD1, 0x9E, 0xE2, 0xB2, 0x80, 0xF0, 0x90, 0x8C, 0xB0]),
+        Ok("ўⲀ𐌰")
+    );
+
+    assert_eq!(
+        CODER.decode_str(&[b'a', 0xD1, 0x9E, 0xE2, 0xB2]),
+        Err(Error::UnexpectedEnd { offset: 5 })
+    );
+    assert_eq!(
+        CODER.decode_str(&[0xD1, 0x9E, 0xE2, b'b', 0x80]),
+        Err(Error::InvalidContinuation {
+            byte: b'b',
+            offset: 3
+        })
+    );
+    assert_eq!(
+        CODER.decode_str(&[b'a', b'b', 0xC1, 0xBF]),
+        Err(Error::OverlongEncoding {
+            codepoint: 0x7F,
+            offset: 2
+        })
+    );
+    assert_eq!(
+        Error::OverlongEncoding {
+            codepoint: 0x7F,
+            offset: 2
+        }
+        .to_string(),
+        "overlong encoding of code point 7Fh at offset 2"
+    );
+}
+
 #[test]
 fn encode_one_byte_ranges() {
     assert_eq!(encode_range(0..=0), Ok(arr(&[arr(&[range(0, 0)])])));
@@ -176,6 +303,18 @@ fn encode_one_byte_ranges() {
         assert_eq!(encode_range(23..=0), Ok(arr(&[arr(&[range(0, 23)])])));
         assert_eq!(encode_range(0x7F..=0), Ok(arr(&[arr(&[range(0, 0x7F)])])));
     }
+
+    // the ASCII ranges take a shortcut, so they are compared exhaustively
+    for start in 0..=0x7F {
+        for end in start..=0x7F {
+            assert_eq!(encode_range(start..=end), expect_range(start..=end));
+        }
+    }
+    assert_eq!(
+        encode_range(b'a' as u32..=b'z' as u32),
+        Ok(arr(&[arr(&[range(b'a', b'z')])]))
+    );
+    assert_eq!(encode_range(0x7F..=0x80), expect_range(0x7F..=0x80));
 }
 
 #[test]
@@ -266,6 +405,37 @@ fn encode_entire_range() {
     assert_eq!(Ok(seq), encode_range(0x0..=0x10FFFF));
 }
 
+#[test]
+fn encode_range_to_vec() {
+    let mut seq = Vec::new();
+    CODER.encode_range(0x83, 0x10FFFF, |ranges| seq.push(ranges.to_vec()));
+    assert_eq!(CODER.encode_range_to_vec(0x83, 0x10FFFF), Ok(seq));
+    assert_eq!(
+        CODER.encode_range_to_vec(0x734, 0x83),
+        Ok(vec![
+            vec![
+                range(0b110_00010, 0b110_00010),
+                range(0b10_000011, 0b10_111111)
+            ],
+            vec![
+                range(0b110_00011, 0b110_11011),
+                range(0b10_000000, 0b10_111111)
+            ],
+            vec![
+                range(0b110_11100, 0b110_11100),
+                range(0b10_000000, 0b10_110100)
+            ],
+        ])
+    );
+    assert_matches!(
+        CODER.encode_range_to_vec(0x800, 0x110000),
+        Err(Error::InvalidCodePoint {
+            codepoint: 0x110000,
+            ..
+        })
+    );
+}
+
 mod prop {
     use super::*;
     use pretty_assertions::assert_eq;
@@ -291,6 +461,13 @@ mod prop {
         fn encode_ranges((start, end) in gen_range()) {
             assert_eq!(encode_range(start..=end), expect_range(start..=end));
         }
+
+        // `gen_range` almost never generates ranges of ASCII chars only
+        #[test]
+        fn encode_ascii_ranges(start in 0..=0x7Fu32, len in 0..=0x100u32) {
+            let end = start + len;
+            assert_eq!(encode_range(start..=end), expect_range(start..=end));
+        }
     }
 }
 
@@ -342,3 +519,38 @@ mod bruteforce {
         }
     }
 }
+
+#[test]
+fn is_valid_prefix() {
+    assert!(CODER.is_valid_prefix(b""));
+    assert!(CODER.is_valid_prefix(b"abc"));
+    assert!(CODER.is_valid_prefix("aўⲀ𐌰".as_bytes()));
+
+    // valid, but incomplete
+    assert!(CODER.is_valid_prefix(&[0xD1]));
+    assert!(CODER.is_valid_prefix(&[b'a', 0xE2, 0xB2]));
+    assert!(CODER.is_valid_prefix(&[0xF0, 0x90, 0x8C]));
+    assert!(CODER.is_valid_prefix(&[0xF4, 0x8F]));
+
+    // invalid
+    assert!(!CODER.is_valid_prefix(&[0x9E]));
+    assert!(!CODER.is_valid_prefix(&[0xD1, b'a']));
+    assert!(!CODER.is_valid_prefix(&[0xE2, 0xB2, b'a', 0xD1]));
+    assert!(!CODER.is_valid_prefix(&[0xF8]));
+    assert!(!CODER.is_valid_prefix(&[0xC0]));
+    assert!(!CODER.is_valid_prefix(&[0xE0, 0x80]));
+    assert!(!CODER.is_valid_prefix(&[0xED, 0xA0]));
+    assert!(!CODER.is_valid_prefix(&[0xF4, 0x90]));
+    assert!(!CODER.is_valid_prefix(&[0xC1, 0xBF]));
+}
+
+#[test]
+fn utf8_max_bytes_per_codepoint() {
+    assert_eq!(Utf8Encoder::MAX_BYTES_PER_CODEPOINT, 4);
+    let mut buffer = [0u8; Utf8Encoder::MAX_BYTES_PER_CODEPOINT];
+    for range in Encoding::Utf8.codepoint_ranges() {
+        for codepoint in range.start()..=range.last() {
+            assert!(CODER.encode_ucp(codepoint, &mut buffer).is_ok());
+        }
+    }
+}
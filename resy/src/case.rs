@@ -0,0 +1,117 @@
+//! Simple case folding of Unicode code points.
+//!
+//! Every code point is folded with the [`CASE_PAIRS`] table of simple 1:1
+//! case pairs, and the [`CASE_ORBITS`] table of characters with several
+//! simple variants, like `k`, `K` and the Kelvin sign U+212A.
+
+use redt::{Range, RangeList};
+
+/// Returns the `codepoint` followed by its simple case variants, if any, with
+/// no duplicates. For example, `a` gives `a` and `A`, `k` gives `k`, `K` and
+/// the Kelvin sign U+212A, and a code point that isn't a letter gives only
+/// itself.
+pub fn fold_case(codepoint: u32) -> impl Iterator<Item = u32> {
+    let mut folded = vec![codepoint];
+    for_each_counterpart(Range::new(codepoint, codepoint), |variant| {
+        let variant = variant.start();
+        if !folded.contains(&variant) {
+            folded.push(variant);
+        }
+    });
+    folded.into_iter()
+}
+
+/// Simple 1:1 case pairs as `(first, last, delta, step)`: every `step`-th
+/// code point in `first..=last` is an uppercase letter, and its lowercase
+/// counterpart is the code point `delta` away from it.
+///
+/// Only the ASCII, Latin-1, Latin Extended-A, Greek, Cyrillic, Armenian and
+/// fullwidth Latin letters are covered. Full case folding, where a character
+/// maps to several ones (e.g. `ß` to `ss`), is out of scope.
+const CASE_PAIRS: &[(u32, u32, i32, u32)] = &[
+    (0x0041, 0x005A, 32, 1),
+    (0x00C0, 0x00D6, 32, 1),
+    (0x00D8, 0x00DE, 32, 1),
+    (0x0100, 0x012E, 1, 2),
+    (0x0132, 0x0136, 1, 2),
+    (0x0139, 0x0147, 1, 2),
+    (0x014A, 0x0176, 1, 2),
+    (0x0178, 0x0178, -121, 1),
+    (0x0179, 0x017D, 1, 2),
+    (0x0386, 0x0386, 38, 1),
+    (0x0388, 0x038A, 37, 1),
+    (0x038C, 0x038C, 64, 1),
+    (0x038E, 0x038F, 63, 1),
+    (0x0391, 0x03A1, 32, 1),
+    (0x03A3, 0x03AB, 32, 1),
+    (0x0400, 0x040F, 80, 1),
+    (0x0410, 0x042F, 32, 1),
+    (0x0460, 0x0480, 1, 2),
+    (0x048A, 0x04BE, 1, 2),
+    (0x04C0, 0x04C0, 15, 1),
+    (0x04C1, 0x04CD, 1, 2),
+    (0x04D0, 0x052E, 1, 2),
+    (0x0531, 0x0556, 48, 1),
+    (0xFF21, 0xFF3A, 32, 1),
+];
+
+/// Case orbits of the characters with more than one simple case variant.
+/// Every code point of an orbit folds to all the other ones.
+const CASE_ORBITS: &[&[u32]] = &[
+    // K, k, Kelvin sign
+    &[0x004B, 0x006B, 0x212A],
+    // S, s, long s
+    &[0x0053, 0x0073, 0x017F],
+    // Σ, ς, σ
+    &[0x03A3, 0x03C2, 0x03C3],
+];
+
+/// Extends the code point ranges with the case counterparts of the letters
+/// in them according to [`CASE_PAIRS`] and [`CASE_ORBITS`].
+pub(crate) fn fold_case_ranges(mut ranges: RangeList<u32>) -> RangeList<u32> {
+    let mut counterparts = Vec::new();
+    for range in ranges.ranges() {
+        for_each_counterpart(*range, |counterpart| counterparts.push(counterpart));
+    }
+    for range in counterparts {
+        ranges.merge(range);
+    }
+    ranges
+}
+
+/// Walks [`CASE_PAIRS`] in both directions and [`CASE_ORBITS`], and calls `f`
+/// with the ranges of the case counterparts of the letters in `range`.
+fn for_each_counterpart(range: Range<u32>, mut f: impl FnMut(Range<u32>)) {
+    for &(first, last, delta, step) in CASE_PAIRS {
+        let lower_first = first.wrapping_add_signed(delta);
+        let lower_last = last.wrapping_add_signed(delta);
+        for (first, last, delta) in [(first, last, delta), (lower_first, lower_last, -delta)] {
+            let start = range.start().max(first);
+            let last = range.last().min(last);
+            if start > last {
+                continue;
+            }
+            if step == 1 {
+                f(Range::new(
+                    start.wrapping_add_signed(delta),
+                    last.wrapping_add_signed(delta),
+                ));
+            } else {
+                for c in (start..=last).filter(|c| (c - first).is_multiple_of(step)) {
+                    let c = c.wrapping_add_signed(delta);
+                    f(Range::new(c, c));
+                }
+            }
+        }
+    }
+    for orbit in CASE_ORBITS {
+        if orbit
+            .iter()
+            .any(|&c| range.start() <= c && c <= range.last())
+        {
+            for &c in orbit.iter() {
+                f(Range::new(c, c));
+            }
+        }
+    }
+}
@@ -40,6 +40,25 @@ pub enum Error {
 
     #[error("repetition expression `{{n,m}}` expects that `n <= m`")]
     InvalidRepetition { span: Range<usize> },
+
+    #[error("`{sequence}` is out of the allowed byte alphabet")]
+    OutOfAlphabet {
+        sequence: Box<str>,
+        span: Range<usize>,
+    },
+
+    #[error("group name `{name}` is already used")]
+    DuplicateGroupName { name: Box<str>, span: Range<usize> },
+
+    #[error("pattern length {len} exceeds the maximum of {max} bytes")]
+    PatternTooLong {
+        len: usize,
+        max: usize,
+        span: Range<usize>,
+    },
+
+    #[error("nesting depth of groups and repetitions exceeds the maximum of {max}")]
+    TooDeep { max: usize, span: Range<usize> },
 }
 
 impl Error {
@@ -53,6 +72,10 @@ impl Error {
             UnsupportedEscape { span, .. } => span.clone(),
             ZeroRepetition { span } => span.clone(),
             InvalidRepetition { span } => span.clone(),
+            OutOfAlphabet { span, .. } => span.clone(),
+            DuplicateGroupName { span, .. } => span.clone(),
+            PatternTooLong { span, .. } => span.clone(),
+            TooDeep { span, .. } => span.clone(),
         }
     }
 }
@@ -110,4 +133,38 @@ pub(crate) mod err {
     pub(crate) fn invalid_repetition<T>(span: Range<usize>) -> Result<T> {
         Err(Box::new(Error::InvalidRepetition { span }))
     }
+
+    pub(crate) fn out_of_alphabet<T, S>(sequence: S, span: Range<usize>) -> Result<T>
+    where
+        S: Into<Box<str>>,
+    {
+        Err(Box::new(Error::OutOfAlphabet {
+            sequence: sequence.into(),
+            span,
+        }))
+    }
+
+    pub(crate) fn duplicate_group_name<T>(
+        name: impl Into<Box<str>>,
+        span: Range<usize>,
+    ) -> Result<T> {
+        Err(Box::new(Error::DuplicateGroupName {
+            name: name.into(),
+            span,
+        }))
+    }
+
+    /// The span starts at the last char boundary not exceeding `max`, so that
+    /// it can be used to slice the `pattern`.
+    pub(crate) fn pattern_too_long<T>(pattern: &str, max: usize) -> Result<T> {
+        Err(Box::new(Error::PatternTooLong {
+            len: pattern.len(),
+            max,
+            span: pattern.floor_char_boundary(max)..pattern.len(),
+        }))
+    }
+
+    pub(crate) fn too_deep<T>(max: usize, span: Range<usize>) -> Result<T> {
+        Err(Box::new(Error::TooDeep { max, span }))
+    }
 }
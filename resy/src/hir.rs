@@ -1,10 +1,22 @@
-use redt::{Legible, SetU8};
+use redt::{Legible, RangeList, SetU8, ops::*};
+use renc::Encoder;
+use std::collections::HashSet;
 use std::fmt::Write;
 
 /// Hir represents a high-level intermediate representation of a regular
 /// expression, that contains bytes already encoded from unicode code points,
 /// and can be used to build a graph of the corresponding finite automaton.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// With the `serde` feature, a deserialized hir is built with the same
+/// constructors as a parsed one, e.g. [`Hir::disjunct`], so its length hints
+/// are recalculated, and invalid input, like an empty disjunction or a
+/// repetition with `lower > upper`, is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(try_from = "serde_repr::HirRepr")
+)]
 pub enum Hir {
     Disjunct(DisjunctHir),
     Concat(ConcatHir),
@@ -12,17 +24,55 @@ pub enum Hir {
     Group(GroupHir),
     Class(SetU8),
     Literal(Vec<u8>),
+    Anchor(AnchorKind),
+}
+
+/// Kind of a zero-width assertion about the position within a haystack.
+///
+/// Matching always starts at some position of the haystack, so
+/// [`AnchorKind::StartText`] is satisfied only if this position is `0`. For
+/// example, `match_at(haystack, start)` with `start > 0` never matches a
+/// pattern that requires `^` to be passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnchorKind {
+    /// `^`, i.e. the beginning of the haystack.
+    StartText,
+
+    /// `$`, i.e. the end of the haystack.
+    EndText,
 }
 
 impl Hir {
-    /// Creates a new disjunciton hir instance. If there is only one item, it
+    /// Creates a new disjunciton hir instance. Structurally equal alternatives
+    /// are removed except the first one. If there is only one item left, it
     /// returns that item.
+    ///
+    /// An alternative followed by an empty one, like in `(a+|)`, becomes an
+    /// optional repetition `(a+)?`, which is translated into fewer nodes. It
+    /// isn't done for a literal, so `(a|)` is kept as is, since alternatives
+    /// of literals are translated into a trie where the empty one costs just a
+    /// single epsilon transition. It isn't done for an alternative containing
+    /// groups either, as a skipped disjunction branch invalidates their tags.
+    /// A leading empty alternative, like in `(|a)`, is kept as well, because
+    /// it prefers the empty match.
     pub fn disjunct(alternatives: impl Into<Vec<Hir>>) -> Hir {
-        let alters = alternatives.into();
+        let mut alters = alternatives.into();
         assert!(!alters.is_empty(), "empty disjunction is not allowed");
+        let mut seen = HashSet::with_capacity(alters.len());
+        let is_first: Vec<_> = alters.iter().map(|alter| seen.insert(alter)).collect();
+        let mut is_first = is_first.into_iter();
+        alters.retain(|_| is_first.next().unwrap());
         if alters.len() == 1 {
             return alters.into_iter().next().unwrap();
         }
+        if let [alter, empty] = &alters[..]
+            && *empty == Hir::empty()
+            && !alter.is_literal()
+            && !alter.has_groups()
+        {
+            return Hir::repeat(alters.swap_remove(0), 0, Some(1));
+        }
         let mut min_len = usize::MAX;
         let mut max_len = Some(0);
         for alter in &alters {
@@ -76,6 +126,18 @@ impl Hir {
 
     /// Creates a new repeat hir instance.
     pub fn repeat(item: Hir, lower: usize, upper: Option<usize>) -> Hir {
+        Self::repeat_impl(item, lower, upper, true)
+    }
+
+    /// Creates a new non-greedy repeat hir instance.
+    ///
+    /// The translated automaton is the same as for a greedy one, because a DFA
+    /// always matches the longest string.
+    pub fn non_greedy_repeat(item: Hir, lower: usize, upper: Option<usize>) -> Hir {
+        Self::repeat_impl(item, lower, upper, false)
+    }
+
+    fn repeat_impl(item: Hir, lower: usize, upper: Option<usize>, greedy: bool) -> Hir {
         if let Some(upper) = upper {
             assert!(
                 lower <= upper,
@@ -85,13 +147,15 @@ impl Hir {
         Hir::Repeat(RepeatHir {
             lower,
             upper,
+            greedy,
             item: Box::new(item),
         })
     }
 
-    pub fn group(label: u32, item: Hir) -> Hir {
+    /// Creates a new group hir instance labeled with a number or a name.
+    pub fn group(label: impl Into<GroupLabel>, item: Hir) -> Hir {
         Hir::Group(GroupHir {
-            label,
+            label: label.into(),
             item: Box::new(item),
         })
     }
@@ -108,6 +172,71 @@ impl Hir {
         Hir::Literal(bytes.into())
     }
 
+    /// Creates a new anchor hir instance, i.e. a zero-width assertion.
+    #[inline]
+    pub fn anchor(kind: AnchorKind) -> Hir {
+        Hir::Anchor(kind)
+    }
+
+    /// Creates a hir matching any code point of the `ranges` encoded with the
+    /// `encoder`. It's a disjunction of the encoded byte sequences, where a
+    /// single-byte sequence is a class, and a multibyte one is a concatenation
+    /// of classes, i.e. the same hir the parser builds for a character class.
+    ///
+    /// Empty `ranges` give an empty hir.
+    pub fn from_codepoint_ranges(
+        ranges: &RangeList<u32>,
+        encoder: &impl Encoder,
+    ) -> renc::Result<Hir> {
+        let alphabet = !SetU8::empty();
+        let mut alternatives = Vec::with_capacity(ranges.len());
+        for range in ranges.ranges() {
+            let hir = Self::from_codepoint_range(range.start(), range.last(), encoder, &alphabet)?;
+            alternatives.extend(hir);
+        }
+        if alternatives.is_empty() {
+            Ok(Hir::empty())
+        } else {
+            Ok(Hir::disjunct(alternatives))
+        }
+    }
+
+    /// Encodes the code points from `first_codepoint` to `last_codepoint` into
+    /// a hir like [`Hir::from_codepoint_ranges`] does, skipping the byte
+    /// sequences out of the `alphabet`. Returns `None` if all of them are
+    /// skipped.
+    pub(crate) fn from_codepoint_range(
+        first_codepoint: u32,
+        last_codepoint: u32,
+        encoder: &impl Encoder,
+        alphabet: &SetU8,
+    ) -> renc::Result<Option<Hir>> {
+        let sequences = encoder.encode_range_to_vec(first_codepoint, last_codepoint)?;
+        let mut alternatives = Vec::with_capacity(sequences.len());
+        'seqs: for seq in sequences {
+            let mut items = Vec::with_capacity(seq.len());
+            for b_range in seq {
+                let mut b_set = SetU8::new();
+                b_set.include(b_range);
+                b_set &= alphabet;
+                if b_set.is_empty() {
+                    continue 'seqs;
+                }
+                items.push(Hir::class(b_set));
+            }
+            if items.len() == 1 {
+                alternatives.push(items.pop().unwrap());
+            } else {
+                alternatives.push(Hir::concat(items));
+            }
+        }
+        if alternatives.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(Hir::disjunct(alternatives)))
+        }
+    }
+
     /// Creates an empty hir instance, i.e. a literal with no bytes.
     #[inline]
     pub fn empty() -> Hir {
@@ -144,6 +273,123 @@ impl Hir {
         matches!(self, Hir::Literal(..))
     }
 
+    #[inline]
+    pub fn is_anchor(&self) -> bool {
+        matches!(self, Hir::Anchor(..))
+    }
+
+    /// Returns the literals if the hir is a literal or a disjunction of
+    /// literals only, i.e. it matches a finite set of strings.
+    pub fn literals(&self) -> Option<Vec<&[u8]>> {
+        match self {
+            Hir::Literal(literal) => Some(vec![literal]),
+            Hir::Disjunct(disjunct) => disjunct
+                .alternatives()
+                .iter()
+                .map(|hir| match hir {
+                    Hir::Literal(literal) => Some(literal.as_slice()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// Simplifies the hir without changing the language it matches:
+    /// - nested concatenations and disjunctions are flattened into their
+    ///   parents, and empty literals are dropped from concatenations;
+    /// - single-byte classes become literals, and consecutive literals of a
+    ///   concatenation are merged into one;
+    /// - consecutive single-byte alternatives are merged into one class.
+    ///
+    /// Groups are kept as is, since they delimit captures. Optimizing the
+    /// result again gives the same hir.
+    pub fn optimize(self) -> Hir {
+        match self {
+            Hir::Disjunct(disjunct) => Self::optimize_disjunct(disjunct.alters),
+            Hir::Concat(concat) => Self::optimize_concat(concat.items),
+            Hir::Repeat(repeat) => Self::repeat_impl(
+                repeat.item.optimize(),
+                repeat.lower,
+                repeat.upper,
+                repeat.greedy,
+            ),
+            Hir::Group(group) => Hir::group(group.label, group.item.optimize()),
+            Hir::Class(set) if set.len() == 1 => Hir::literal([set.bytes().next().unwrap()]),
+            hir @ (Hir::Class(_) | Hir::Literal(_) | Hir::Anchor(_)) => hir,
+        }
+    }
+
+    fn optimize_concat(items: Vec<Hir>) -> Hir {
+        let mut flat = Vec::with_capacity(items.len());
+        for item in items {
+            match item.optimize() {
+                Hir::Concat(concat) => flat.extend(concat.items),
+                Hir::Literal(bytes) if bytes.is_empty() => {}
+                item => flat.push(item),
+            }
+        }
+        let mut merged: Vec<Hir> = Vec::with_capacity(flat.len());
+        for item in flat {
+            if let Hir::Literal(bytes) = &item
+                && let Some(Hir::Literal(last)) = merged.last_mut()
+            {
+                last.extend_from_slice(bytes);
+            } else {
+                merged.push(item);
+            }
+        }
+        Hir::concat(merged)
+    }
+
+    fn optimize_disjunct(alters: Vec<Hir>) -> Hir {
+        let mut flat = Vec::with_capacity(alters.len());
+        for alter in alters {
+            match alter.optimize() {
+                Hir::Disjunct(disjunct) => flat.extend(disjunct.alters),
+                alter => flat.push(alter),
+            }
+        }
+        // deduplicate before merging, so that removing an alternative can't
+        // make two single-byte ones adjacent afterwards
+        let flat = match Hir::disjunct(flat) {
+            Hir::Disjunct(disjunct) => disjunct.alters,
+            hir => return hir,
+        };
+        let mut merged: Vec<Hir> = Vec::with_capacity(flat.len());
+        for alter in flat {
+            if let Some(set) = alter.as_byte_set()
+                && let Some(last) = merged.last_mut()
+                && let Some(mut last_set) = last.as_byte_set()
+            {
+                last_set.include(&set);
+                *last = Hir::class(last_set);
+            } else {
+                merged.push(alter);
+            }
+        }
+        Hir::disjunct(merged)
+    }
+
+    fn has_groups(&self) -> bool {
+        match self {
+            Hir::Disjunct(disjunct) => disjunct.alters.iter().any(Hir::has_groups),
+            Hir::Concat(concat) => concat.items.iter().any(Hir::has_groups),
+            Hir::Repeat(repeat) => repeat.item.has_groups(),
+            Hir::Group(_) => true,
+            Hir::Class(_) | Hir::Literal(_) | Hir::Anchor(_) => false,
+        }
+    }
+
+    /// Returns the set of bytes if the hir matches exactly one byte of it.
+    fn as_byte_set(&self) -> Option<SetU8> {
+        match self {
+            Hir::Class(set) => Some(set.clone()),
+            Hir::Literal(bytes) if bytes.len() == 1 => Some(SetU8::from(bytes[0])),
+            _ => None,
+        }
+    }
+
     /// Returns the bounds of the Hir's length. `None` means infinite.
     pub fn len_hint(&self) -> (usize, Option<usize>) {
         match self {
@@ -153,6 +399,7 @@ impl Hir {
             Hir::Group(hir) => hir.len_hint(),
             Hir::Class(_) => (1, Some(1)),
             Hir::Literal(bytes) => (bytes.len(), Some(bytes.len())),
+            Hir::Anchor(_) => (0, Some(0)),
         }
     }
 
@@ -164,7 +411,7 @@ impl Hir {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DisjunctHir {
     alters: Vec<Hir>,
     min_len: usize,
@@ -177,6 +424,11 @@ impl DisjunctHir {
         &self.alters
     }
 
+    #[inline]
+    pub fn into_alternatives(self) -> Vec<Hir> {
+        self.alters
+    }
+
     #[inline]
     pub fn len_hint(&self) -> (usize, Option<usize>) {
         (self.min_len, self.max_len)
@@ -188,7 +440,7 @@ impl DisjunctHir {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ConcatHir {
     items: Vec<Hir>,
     min_len: usize,
@@ -201,16 +453,22 @@ impl ConcatHir {
         &self.items
     }
 
+    #[inline]
+    pub fn into_items(self) -> Vec<Hir> {
+        self.items
+    }
+
     #[inline]
     pub fn len_hint(&self) -> (usize, Option<usize>) {
         (self.min_len, self.max_len)
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RepeatHir {
     lower: usize,
     upper: Option<usize>,
+    greedy: bool,
     item: Box<Hir>,
 }
 
@@ -220,6 +478,11 @@ impl RepeatHir {
         &self.item
     }
 
+    #[inline]
+    pub fn into_inner(self) -> Hir {
+        *self.item
+    }
+
     pub fn len_hint(&self) -> (usize, Option<usize>) {
         let (min_len, max_len) = self.item.len_hint();
         if let Some(max) = self.upper
@@ -236,11 +499,21 @@ impl RepeatHir {
     pub fn iter_hint(&self) -> (usize, Option<usize>) {
         (self.lower, self.upper)
     }
+
+    /// Returns `false` if the repetition prefers the fewest iterations.
+    ///
+    /// Note that a matcher built from a DFA always returns the leftmost-longest
+    /// match, so the flag only orders the NFA branches. Honoring it requires
+    /// tracking the match length with tags.
+    #[inline]
+    pub fn is_greedy(&self) -> bool {
+        self.greedy
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GroupHir {
-    label: u32,
+    label: GroupLabel,
     item: Box<Hir>,
 }
 
@@ -251,8 +524,13 @@ impl GroupHir {
     }
 
     #[inline]
-    pub fn label(&self) -> u32 {
-        self.label
+    pub fn into_inner(self) -> Hir {
+        *self.item
+    }
+
+    #[inline]
+    pub fn label(&self) -> &GroupLabel {
+        &self.label
     }
 
     #[inline]
@@ -261,6 +539,45 @@ impl GroupHir {
     }
 }
 
+/// Label of a group: either a number, like in `(?<1>...)`, or a name, like
+/// in `(?<year>...)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupLabel {
+    Index(u32),
+    Name(Box<str>),
+}
+
+impl std::convert::From<u32> for GroupLabel {
+    #[inline]
+    fn from(index: u32) -> Self {
+        GroupLabel::Index(index)
+    }
+}
+
+impl std::convert::From<&str> for GroupLabel {
+    #[inline]
+    fn from(name: &str) -> Self {
+        GroupLabel::Name(name.into())
+    }
+}
+
+impl std::convert::From<&GroupLabel> for GroupLabel {
+    #[inline]
+    fn from(label: &GroupLabel) -> Self {
+        label.clone()
+    }
+}
+
+impl std::fmt::Display for GroupLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GroupLabel::Index(index) => std::fmt::Display::fmt(index, f),
+            GroupLabel::Name(name) => f.write_str(name),
+        }
+    }
+}
+
 impl std::fmt::Display for Hir {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -270,12 +587,14 @@ impl std::fmt::Display for Hir {
             Hir::Class(set) => {
                 std::fmt::Display::fmt(&set, f)?;
             }
+            Hir::Anchor(AnchorKind::StartText) => f.write_char('^')?,
+            Hir::Anchor(AnchorKind::EndText) => f.write_char('$')?,
             Hir::Group(group) => {
                 write!(f, "(?<{}> {} )", group.label, group.item)?;
             }
             Hir::Repeat(repeat) => {
                 let item = &repeat.item;
-                let needs_parens = item.is_concat() || item.is_disjunct();
+                let needs_parens = item.is_concat() || item.is_disjunct() || item.is_repeat();
                 if needs_parens {
                     f.write_char('(')?;
                 }
@@ -291,6 +610,9 @@ impl std::fmt::Display for Hir {
                     (lower, Some(upper)) if lower == upper => write!(f, "{{{lower}}}")?,
                     (lower, Some(upper)) => write!(f, "{{{lower},{upper}}}")?,
                 }
+                if !repeat.greedy {
+                    f.write_char('?')?;
+                }
             }
             Hir::Concat(concat) => {
                 let items = &concat.items;
@@ -326,3 +648,109 @@ impl std::fmt::Display for Hir {
         Ok(())
     }
 }
+
+#[cfg(feature = "serde")]
+mod serde_repr {
+    //! Serialized shape of [`Hir`] without the cached length hints. It's
+    //! deserialized into [`HirRepr`] first, and then converted into a [`Hir`]
+    //! with its constructors, so that their invariants hold.
+
+    use super::{AnchorKind, GroupLabel, Hir};
+    use redt::SetU8;
+
+    #[derive(serde::Serialize)]
+    #[serde(rename = "Hir")]
+    enum HirRef<'a> {
+        Disjunct(&'a [Hir]),
+        Concat(&'a [Hir]),
+        Repeat {
+            lower: usize,
+            upper: Option<usize>,
+            greedy: bool,
+            item: &'a Hir,
+        },
+        Group {
+            label: &'a GroupLabel,
+            item: &'a Hir,
+        },
+        Class(&'a SetU8),
+        Literal(&'a [u8]),
+        Anchor(AnchorKind),
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Hir")]
+    pub(super) enum HirRepr {
+        Disjunct(Vec<Hir>),
+        Concat(Vec<Hir>),
+        Repeat {
+            lower: usize,
+            upper: Option<usize>,
+            greedy: bool,
+            item: Box<Hir>,
+        },
+        Group {
+            label: GroupLabel,
+            item: Box<Hir>,
+        },
+        Class(SetU8),
+        Literal(Vec<u8>),
+        Anchor(AnchorKind),
+    }
+
+    impl serde::Serialize for Hir {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let repr = match self {
+                Hir::Disjunct(disjunct) => HirRef::Disjunct(&disjunct.alters),
+                Hir::Concat(concat) => HirRef::Concat(&concat.items),
+                Hir::Repeat(repeat) => HirRef::Repeat {
+                    lower: repeat.lower,
+                    upper: repeat.upper,
+                    greedy: repeat.greedy,
+                    item: &repeat.item,
+                },
+                Hir::Group(group) => HirRef::Group {
+                    label: &group.label,
+                    item: &group.item,
+                },
+                Hir::Class(set) => HirRef::Class(set),
+                Hir::Literal(bytes) => HirRef::Literal(bytes),
+                Hir::Anchor(kind) => HirRef::Anchor(*kind),
+            };
+            repr.serialize(serializer)
+        }
+    }
+
+    impl std::convert::TryFrom<HirRepr> for Hir {
+        type Error = String;
+
+        fn try_from(repr: HirRepr) -> Result<Self, Self::Error> {
+            Ok(match repr {
+                HirRepr::Disjunct(alters) => {
+                    if alters.is_empty() {
+                        return Err("empty disjunction is not allowed".into());
+                    }
+                    Hir::disjunct(alters)
+                }
+                HirRepr::Concat(items) => Hir::concat(items),
+                HirRepr::Repeat {
+                    lower,
+                    upper,
+                    greedy,
+                    item,
+                } => {
+                    if let Some(upper) = upper
+                        && lower > upper
+                    {
+                        return Err(format!("invalid repetition counters: {{{lower},{upper}}}"));
+                    }
+                    Hir::repeat_impl(*item, lower, upper, greedy)
+                }
+                HirRepr::Group { label, item } => Hir::group(label, *item),
+                HirRepr::Class(set) => Hir::class(set),
+                HirRepr::Literal(bytes) => Hir::literal(bytes),
+                HirRepr::Anchor(kind) => Hir::anchor(kind),
+            })
+        }
+    }
+}
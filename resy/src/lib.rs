@@ -1,8 +1,11 @@
+mod case;
+pub use case::fold_case;
+
 mod error;
 pub use error::{Error, Result};
 
 mod hir;
-pub use hir::{ConcatHir, DisjunctHir, GroupHir, Hir, RepeatHir};
+pub use hir::{AnchorKind, ConcatHir, DisjunctHir, GroupHir, GroupLabel, Hir, RepeatHir};
 
 mod lexis;
 pub use lexis::{Lexer, Token, TokenKind, tok};
@@ -10,6 +13,9 @@ pub use lexis::{Lexer, Token, TokenKind, tok};
 mod syntax;
 pub use syntax::Parser;
 
+mod visit;
+pub use visit::{HirFolder, HirVisitor};
+
 /// Re-export of the `renc` crate.
 pub mod enc {
     pub use renc::*;
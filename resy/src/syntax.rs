@@ -1,8 +1,10 @@
+use crate::case::fold_case_ranges;
 use crate::error::{Result, err};
-use crate::hir::Hir;
+use crate::hir::{AnchorKind, GroupLabel, Hir};
 use crate::lexis::{Lexer, tok};
-use redt::{RangeList, SetU8, ops::*};
+use redt::{Range, RangeList, SetU8, ops::*};
 use renc::Encoder;
+use std::collections::HashSet;
 
 /// A regex pattern parser that converts string patterns into high-level
 /// intermediate representation (HIR).
@@ -26,12 +28,75 @@ use renc::Encoder;
 /// ```
 pub struct Parser<C: Encoder> {
     encoder: C,
+    case_insensitive: bool,
+    alphabet: SetU8,
+    max_pattern_len: Option<usize>,
+    max_depth: usize,
+    whitespace: RangeList<u32>,
 }
 
+/// Default maximal nesting depth of groups and repetitions, see [`Parser::with_max_depth`].
+const DEFAULT_MAX_DEPTH: usize = 128;
+
 impl<C: Encoder> Parser<C> {
     /// Creates a new parser with the specified encoder.
     pub fn new(encoder: C) -> Self {
-        Parser { encoder }
+        Parser {
+            encoder,
+            case_insensitive: false,
+            alphabet: !SetU8::empty(),
+            max_pattern_len: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            whitespace: ascii_whitespace(),
+        }
+    }
+
+    /// Makes the parser produce a case-insensitive HIR.
+    ///
+    /// Letters are folded by simple 1:1 case pairs of the Latin, Greek,
+    /// Cyrillic and Armenian scripts. Full case folding, e.g. `ß` to `ss`,
+    /// isn't supported. A class range straddling case boundaries, like
+    /// `[Z-a]`, is extended only by the counterparts of its letters, i.e. `z`
+    /// and `A`. Negated classes exclude both cases, e.g.
+    /// `[^a]` doesn't match `A`.
+    pub fn with_case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// Restricts the bytes the HIR can match to the `alphabet`.
+    ///
+    /// Classes, including `.` and negated ones, are intersected with the
+    /// alphabet. A literal with bytes out of the alphabet, or a class without
+    /// any byte sequence within it, is an error.
+    pub fn with_alphabet(mut self, alphabet: SetU8) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Limits the length of a pattern to `max_len` bytes. A longer pattern is
+    /// rejected before lexing, so it bounds the work spent on untrusted input.
+    pub fn with_max_pattern_len(mut self, max_len: usize) -> Self {
+        self.max_pattern_len = Some(max_len);
+        self
+    }
+
+    /// Limits the nesting depth of groups and repetitions to `max_depth`, 128
+    /// by default. Groups are parsed recursively, and every postfix operator
+    /// wraps its item into one more repetition, e.g. `a**` is a repetition of
+    /// a repetition, so the limit keeps a deeply nested pattern from
+    /// overflowing the stack when the HIR is translated or dropped.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the code points matched by the `\s` class; `\S` matches all the
+    /// other code points of the encoding. By default, it's the ASCII set
+    /// `[ \t\n\r\x0B\x0C]`.
+    pub fn with_whitespace(mut self, whitespace: RangeList<u32>) -> Self {
+        self.whitespace = whitespace;
+        self
     }
 
     /// Parses a regex pattern string into a high-level intermediate
@@ -46,8 +111,17 @@ impl<C: Encoder> Parser<C> {
     /// let hir = parser.parse("hello.*world").unwrap();
     /// ```
     pub fn parse(&self, pattern: &str) -> Result<Hir> {
+        if let Some(max_len) = self.max_pattern_len
+            && pattern.len() > max_len
+        {
+            return err::pattern_too_long(pattern, max_len);
+        }
         let lexer = Lexer::new(pattern);
         let mut parser = ParserImpl::<C>::new(lexer, &self.encoder);
+        parser.case_insensitive = self.case_insensitive;
+        parser.alphabet = self.alphabet.clone();
+        parser.whitespace = self.whitespace.clone();
+        parser.max_depth = self.max_depth;
         parser.parse()
     }
 }
@@ -56,12 +130,31 @@ impl<C: Encoder> Parser<C> {
 struct ParserImpl<'s, 'c, C: Encoder, const UNICODE: bool = true> {
     lexer: Lexer<'s>,
     coder: &'c C,
+    case_insensitive: bool,
+    alphabet: SetU8,
+    whitespace: RangeList<u32>,
+    group_names: HashSet<Box<str>>,
+    /// Nesting depth of the groups at the current position.
+    depth: usize,
+    /// Nesting depth of the groups and repetitions of the current item.
+    height: usize,
+    max_depth: usize,
 }
 
 impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     /// Creates a new parser implementation instance.
     fn new(lexer: Lexer<'s>, coder: &'c C) -> Self {
-        ParserImpl { lexer, coder }
+        ParserImpl {
+            lexer,
+            coder,
+            case_insensitive: false,
+            alphabet: !SetU8::empty(),
+            whitespace: ascii_whitespace(),
+            group_names: HashSet::new(),
+            depth: 0,
+            height: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
     }
 
     /// Parses the entire regex pattern and returns the resulting HIR.
@@ -130,30 +223,73 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     ///     term
     ///     class
     ///     group
+    ///     anchor
     ///     item postfix
+    ///     item postfix '?'
+    ///
+    /// anchor
+    ///     '^'
+    ///     '$'
     /// ```
     fn try_parse_item(&mut self) -> Result<Option<Hir>> {
         let token = self.lexer.peek();
+        let outer_height = std::mem::replace(&mut self.height, self.depth);
         let mut hir = match token.kind() {
             tok::l_paren => self.parse_group(),
             tok::l_paren_question => self.parse_named_group(),
-            tok::dot | tok::l_square | tok::l_square_caret => self.parse_class(),
+            tok::dot
+            | tok::l_square
+            | tok::l_square_caret
+            | tok::escape_char('d' | 'w' | 's' | 'D' | 'W' | 'S') => self.parse_class(),
+            tok::char('^') => {
+                self.lexer.consume_peeked();
+                Ok(Hir::anchor(AnchorKind::StartText))
+            }
+            tok::char('$') => {
+                self.lexer.consume_peeked();
+                Ok(Hir::anchor(AnchorKind::EndText))
+            }
             _ => {
-                if let Some(c) = self.try_parse_term()? {
-                    let mut literal = vec![0, 0, 0, 0, 0, 0, 0, 0];
+                let Some(c) = self.try_parse_term()? else {
+                    self.height = outer_height;
+                    return Ok(None);
+                };
+                let folded = self
+                    .case_insensitive
+                    .then(|| self.fold_case(RangeList::new(c, c)))
+                    .filter(|ranges| *ranges != RangeList::new(c, c));
+                if let Some(ranges) = folded {
+                    let span = token.start()..self.lexer.end_pos();
+                    self.convert_ranges(&ranges, span)
+                } else {
+                    let mut literal = vec![0; C::MAX_BYTES_PER_CODEPOINT];
                     match self.coder.encode_ucp(c, &mut literal[..]) {
                         Ok(len) => literal.resize(len, 0),
                         Err(error) => return err::encoder_error(error, token.span()),
                     }
+                    if !literal.iter().all(|byte| self.alphabet.contains(*byte)) {
+                        let span = token.start()..self.lexer.end_pos();
+                        let spell = self.lexer.slice(span.clone());
+                        return err::out_of_alphabet(spell, span);
+                    }
                     Ok(Hir::literal(literal))
-                } else {
-                    return Ok(None);
                 }
             }
         }?;
-        while let Some((iter_min, iter_max)) = self.try_parse_postfix()? {
-            hir = Hir::repeat(hir, iter_min, iter_max);
+        loop {
+            let start = self.lexer.peek().start();
+            let Some((iter_min, iter_max)) = self.try_parse_postfix()? else {
+                break;
+            };
+            if self.lexer.peek().kind() == tok::question {
+                self.lexer.consume_peeked();
+                hir = Hir::non_greedy_repeat(hir, iter_min, iter_max);
+            } else {
+                hir = Hir::repeat(hir, iter_min, iter_max);
+            }
+            self.enter_repeat(start..self.lexer.end_pos())?;
         }
+        self.height = self.height.max(outer_height);
         Ok(Some(hir))
     }
 
@@ -190,7 +326,9 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
         }
     }
 
-    /// Parses count of iterations within braces..
+    /// Parses count of iterations within braces. The omitted lower bound is
+    /// `0`, i.e. `{,m}` is `{0,m}`, but at least one bound must be present, so
+    /// `{}` and `{,}` are errors. `{,0}` is rejected as `{0,0}` is.
     ///
     /// # Syntax
     ///
@@ -198,20 +336,33 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     ///     '{' decimal '}'
     ///     '{' decimal ',' '}'
     ///     '{' decimal ',' decimal '}'
+    ///     '{' ',' decimal '}'
     /// ```
     fn parse_braces(&mut self) -> Result<(usize, Option<usize>)> {
         let l_brace = self.lexer.expect(tok::l_brace)?;
-        let Some(first_num) = self.try_parse_decimal()? else {
-            let span = l_brace.end()..self.lexer.lex().end();
-            let spell = self.lexer.slice(span.clone());
-            return err::unexpected(spell, span, "a decimal number");
+        let first_num = self.try_parse_decimal()?;
+        let lower_omitted = first_num.is_none();
+        let first_num = match first_num {
+            Some(num) => num,
+            None if self.lexer.peek().kind() == tok::char(',') => 0,
+            None => {
+                let span = l_brace.end()..self.lexer.lex().end();
+                let spell = self.lexer.slice(span.clone());
+                return err::unexpected(spell, span, "a decimal number");
+            }
         };
         let peeked = self.lexer.peek();
         let second_num = match peeked.kind() {
             tok::r_brace => Some(first_num),
             tok::char(',') => {
                 self.lexer.consume_peeked();
-                self.try_parse_decimal()?
+                let second_num = self.try_parse_decimal()?;
+                if lower_omitted && second_num.is_none() {
+                    let peeked = self.lexer.peek();
+                    let spell = self.lexer.slice(peeked.span());
+                    return err::unexpected(spell, peeked.span(), "a decimal number");
+                }
+                second_num
             }
             _ => {
                 let spell = self.lexer.slice(peeked.span());
@@ -236,13 +387,38 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     ///     "(" disjunct ")"
     /// ```
     fn parse_group(&mut self) -> Result<Hir> {
-        self.lexer.expect(tok::l_paren)?;
+        let l_paren = self.lexer.expect(tok::l_paren)?;
+        self.enter_group(l_paren.span())?;
         let hir = self.parse_disjunct()?;
+        self.depth -= 1;
         self.lexer.expect(tok::r_paren)?;
         Ok(hir)
     }
 
-    /// Parses a named group expression.
+    /// Counts the nesting depth of a group opened at the `span`, and returns
+    /// an error if it exceeds the limit.
+    fn enter_group(&mut self, span: std::ops::Range<usize>) -> Result<()> {
+        if self.depth == self.max_depth {
+            return err::too_deep(self.max_depth, span);
+        }
+        self.depth += 1;
+        self.height = self.height.max(self.depth);
+        Ok(())
+    }
+
+    /// Counts a repetition of the current item by the postfix operator at the
+    /// `span` as one more nesting level, and returns an error if the nesting
+    /// depth of the item exceeds the limit.
+    fn enter_repeat(&mut self, span: std::ops::Range<usize>) -> Result<()> {
+        if self.height == self.max_depth {
+            return err::too_deep(self.max_depth, span);
+        }
+        self.height += 1;
+        Ok(())
+    }
+
+    /// Parses a named group expression. A group name must be unique within
+    /// the pattern, while a decimal label may be repeated.
     ///
     /// # Syntax
     ///
@@ -252,26 +428,36 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     ///
     /// label
     ///     '<' decimal '>'
+    ///     '<' identifier '>'
     /// ```
     fn parse_named_group(&mut self) -> Result<Hir> {
-        self.lexer.expect(tok::l_paren_question)?;
+        let l_paren = self.lexer.expect(tok::l_paren_question)?;
+        self.enter_group(l_paren.span())?;
         let l_angle = self.lexer.expect(tok::char('<'))?;
-        if let Some(num) = self.try_parse_decimal()? {
+        let label = if let Some(num) = self.try_parse_decimal()? {
             if let Ok(num) = u32::try_from(num) {
-                self.lexer.expect(tok::char('>'))?;
-                let hir = self.parse_disjunct()?;
-                self.lexer.expect(tok::r_paren)?;
-                Ok(Hir::group(num, hir))
+                GroupLabel::Index(num)
             } else {
                 let span = l_angle.span().end..self.lexer.end_pos();
                 let spell = self.lexer.slice(span.clone());
-                err::out_of_range(spell, span, "`u32` range")
+                return err::out_of_range(spell, span, "`u32` range");
+            }
+        } else if let Some(name) = self.try_parse_identifier() {
+            let span = l_angle.span().end..self.lexer.end_pos();
+            if !self.group_names.insert(name.clone()) {
+                return err::duplicate_group_name(name, span);
             }
+            GroupLabel::Name(name)
         } else {
             let unexpected_token = self.lexer.peek();
             let slice = self.lexer.slice(unexpected_token.span());
-            err::unexpected(slice, unexpected_token.span(), "decimal")
-        }
+            return err::unexpected(slice, unexpected_token.span(), "decimal or identifier");
+        };
+        self.lexer.expect(tok::char('>'))?;
+        let hir = self.parse_disjunct()?;
+        self.depth -= 1;
+        self.lexer.expect(tok::r_paren)?;
+        Ok(Hir::group(label, hir))
     }
 
     /// Parses a class expression.
@@ -283,6 +469,7 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     ///     '.'
     ///     "[" elements "]"
     ///     "[^" elements "]"
+    ///     class_escape
     ///
     /// elements
     ///     element
@@ -299,20 +486,38 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
             tok::dot => self.parse_dot(),
             tok::l_square => self.parse_squares(),
             tok::l_square_caret => self.parse_squares_negated(),
+            tok::escape_char('d' | 'w' | 's' | 'D' | 'W' | 'S') => self.parse_class_escape(),
             _ => {
                 let slice = self.lexer.slice(token.span());
                 return err::unexpected(slice, token.span(), "a dot or square brackets");
             }
         }?;
+        let span = token.start()..self.lexer.end_pos();
+        self.convert_ranges(&range_set, span)
+    }
 
+    /// Converts a set of code point ranges into a Hir. The `span` is used for
+    /// error reporting.
+    fn convert_ranges(
+        &self,
+        range_set: &RangeList<u32>,
+        span: std::ops::Range<usize>,
+    ) -> Result<Hir> {
         if range_set.is_empty() {
             return Ok(Hir::empty());
         }
 
         let mut alternatives = Vec::new();
         for cp_range in range_set.ranges() {
-            let hir = self.convert(cp_range.start(), cp_range.last());
-            alternatives.push(hir);
+            match self.convert(cp_range.start(), cp_range.last()) {
+                Ok(Some(hir)) => alternatives.push(hir),
+                Ok(None) => (),
+                Err(error) => return err::encoder_error(error, span),
+            }
+        }
+        if alternatives.is_empty() {
+            let spell = self.lexer.slice(span.clone());
+            return err::out_of_alphabet(spell, span);
         }
         Ok(Hir::disjunct(alternatives))
     }
@@ -324,6 +529,54 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
         Ok(RangeList::from(encoding.codepoint_ranges()))
     }
 
+    /// Parses a character class escape sequence. Only ASCII characters are
+    /// included into the `\d` and `\w` classes, and `\s` is the configured
+    /// whitespace set; the negated forms include all the other code points of
+    /// the encoding.
+    ///
+    /// # Syntax
+    ///
+    /// ```mkf
+    /// class_escape
+    ///     "\d"
+    ///     "\w"
+    ///     "\s"
+    ///     "\D"
+    ///     "\W"
+    ///     "\S"
+    /// ```
+    fn parse_class_escape(&mut self) -> Result<RangeList<u32>> {
+        let token = self.lexer.lex();
+        let tok::escape_char(c) = token.kind() else {
+            let slice = self.lexer.slice(token.span());
+            return err::unexpected(slice, token.span(), "a class escape sequence");
+        };
+        let ranges = match c.to_ascii_lowercase() {
+            'd' => RangeList::new('0' as u32, '9' as u32),
+            'w' => RangeList::from([
+                Range::new('0' as u32, '9' as u32),
+                Range::new('A' as u32, 'Z' as u32),
+                Range::new('_' as u32, '_' as u32),
+                Range::new('a' as u32, 'z' as u32),
+            ]),
+            's' => self.whitespace.clone(),
+            _ => {
+                let slice = self.lexer.slice(token.span());
+                return err::unexpected(slice, token.span(), "a class escape sequence");
+            }
+        };
+        if c.is_ascii_uppercase() {
+            let encoding = self.coder.encoding();
+            let mut negated = RangeList::from(encoding.codepoint_ranges());
+            for range in ranges.ranges() {
+                negated.exclude(range);
+            }
+            Ok(negated)
+        } else {
+            Ok(ranges)
+        }
+    }
+
     /// Parses a character class with square brackets `[...]`.
     fn parse_squares(&mut self) -> Result<RangeList<u32>> {
         self.lexer.expect(tok::l_square)?;
@@ -334,6 +587,7 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
                 tok::dot => self.parse_dot(),
                 tok::l_square => self.parse_squares(),
                 tok::l_square_caret => self.parse_squares_negated(),
+                tok::escape_char('d' | 'w' | 's' | 'D' | 'W' | 'S') => self.parse_class_escape(),
                 tok::r_square => break,
                 _ => self.parse_range(),
             }?;
@@ -356,6 +610,7 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
                 tok::dot => self.parse_dot(),
                 tok::l_square => self.parse_squares(),
                 tok::l_square_caret => self.parse_squares_negated(),
+                tok::escape_char('d' | 'w' | 's' | 'D' | 'W' | 'S') => self.parse_class_escape(),
                 tok::r_square => break,
                 _ => self.parse_range(),
             }?;
@@ -372,17 +627,56 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     /// This can parse either:
     /// - A single character: `a`
     /// - A character range: `a-z`
+    ///
+    /// Both ends of the range must be valid code points of the encoding.
     fn parse_range(&mut self) -> Result<RangeList<u32>> {
-        let start_codepoint = self.parse_term()?;
-        if let tok::minus = self.lexer.peek().kind() {
+        let start_codepoint = self.parse_class_bound()?;
+        let ranges = if let tok::minus = self.lexer.peek().kind() {
             self.lexer.consume_peeked();
-            let last_codepoint = self.parse_term()?;
-            Ok(RangeList::new(start_codepoint, last_codepoint))
+            let last_codepoint = self.parse_class_bound()?;
+            RangeList::new(start_codepoint, last_codepoint)
         } else {
-            Ok(RangeList::new(start_codepoint, start_codepoint))
+            RangeList::new(start_codepoint, start_codepoint)
+        };
+        if self.case_insensitive {
+            Ok(self.fold_case(ranges))
+        } else {
+            Ok(ranges)
         }
     }
 
+    /// Extends the code point ranges with their case counterparts that are
+    /// valid code points of the encoding, e.g. `ÿ` keeps no counterpart in
+    /// Latin-1, because `Ÿ` is out of it.
+    fn fold_case(&self, ranges: RangeList<u32>) -> RangeList<u32> {
+        let encoding = self.coder.encoding();
+        fold_case_ranges(ranges).intersection(&RangeList::from(encoding.codepoint_ranges()))
+    }
+
+    /// Parses a term like [`ParserImpl::parse_term`] does, but fails if the
+    /// code point can't be encoded, pointing to the term itself rather than to
+    /// the whole class.
+    fn parse_class_bound(&mut self) -> Result<u32> {
+        let start = self.lexer.end_pos();
+        let codepoint = self.parse_term()?;
+        let encoding = self.coder.encoding();
+        if encoding.is_valid_codepoint(codepoint) {
+            return Ok(codepoint);
+        }
+        let cause = if (0xD800..=0xDFFF).contains(&codepoint) && !encoding.allows_surrogates() {
+            renc::Error::SurrogateUnsupported {
+                codepoint,
+                encoding,
+            }
+        } else {
+            renc::Error::InvalidCodePoint {
+                codepoint,
+                encoding,
+            }
+        };
+        err::encoder_error(cause, start..self.lexer.end_pos())
+    }
+
     /// Parses a sequence corresponding to one code point, i.e. either a single
     /// character or an escape sequence. If there is no one, returns `None`.
     ///
@@ -414,6 +708,8 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
     ///     "\]"
     ///     "\{"
     ///     "\}"
+    ///     "\^"
+    ///     "\$"
     ///     "\0"
     ///     "\n"
     ///     "\r"
@@ -442,6 +738,8 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
                     ']' => Some(']' as u32),
                     '{' => Some('{' as u32),
                     '}' => Some('}' as u32),
+                    '^' => Some('^' as u32),
+                    '$' => Some('$' as u32),
                     '0' => Some('\0' as u32),
                     'n' => Some('\n' as u32),
                     'r' => Some('\r' as u32),
@@ -612,27 +910,38 @@ impl<'s, 'c, C: Encoder, const UNICODE: bool> ParserImpl<'s, 'c, C, UNICODE> {
         }
     }
 
-    /// Converts a range of code points to a Hir.
-    fn convert(&self, first_codepoint: u32, last_codepoint: u32) -> Hir {
-        let mut alternatives = Vec::new();
-        self.coder
-            .encode_range(first_codepoint, last_codepoint, |seq| {
-                let mut items = Vec::new();
-                for b_range in seq {
-                    let mut b_set = SetU8::new();
-                    b_set.include(*b_range);
-                    items.push(Hir::class(b_set));
-                }
-                if items.len() == 1 {
-                    alternatives.push(items.pop().unwrap());
-                } else {
-                    alternatives.push(Hir::concat(items));
-                }
-            });
-        Hir::disjunct(alternatives)
+    /// Parses an ASCII identifier, i.e. a letter or `_` followed by letters,
+    /// digits and `_`. If there is no one, returns `None`.
+    fn try_parse_identifier(&mut self) -> Option<Box<str>> {
+        let token = self.lexer.peek();
+        if !matches!(token.kind(), tok::char(sym) if sym.is_ascii_alphabetic() || sym == '_') {
+            return None;
+        }
+
+        while let tok::char(sym) = self.lexer.peek().kind()
+            && (sym.is_ascii_alphanumeric() || sym == '_')
+        {
+            self.lexer.consume_peeked();
+        }
+        let span = token.span().start..self.lexer.end_pos();
+        Some(self.lexer.slice(span).into())
+    }
+
+    /// Converts a range of code points to a Hir. Byte sequences are
+    /// restricted to the alphabet; if there is no one left, returns `None`.
+    fn convert(&self, first_codepoint: u32, last_codepoint: u32) -> renc::Result<Option<Hir>> {
+        Hir::from_codepoint_range(first_codepoint, last_codepoint, self.coder, &self.alphabet)
     }
 }
 
+/// Returns the default whitespace set, i.e. `[ \t\n\r\x0B\x0C]`.
+fn ascii_whitespace() -> RangeList<u32> {
+    RangeList::from([
+        Range::new('\t' as u32, '\r' as u32),
+        Range::new(' ' as u32, ' ' as u32),
+    ])
+}
+
 #[cfg(test)]
 #[path = "syntax.utest.rs"]
 mod utest;
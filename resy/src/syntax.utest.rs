@@ -1,7 +1,7 @@
 use crate::error::err;
-use crate::hir::Hir;
+use crate::hir::{AnchorKind, Hir};
 use crate::lexis::Lexer;
-use crate::syntax::ParserImpl;
+use crate::syntax::{Parser, ParserImpl};
 use pretty_assertions::assert_eq;
 use redt::{Range, RangeList};
 use renc::Utf8Encoder;
@@ -68,6 +68,58 @@ fn parse_item() {
     assert_eq!(parse(), Ok(None));
 }
 
+#[test]
+fn parse_anchor() {
+    let parse = |pattern: &str| {
+        let lexer = Lexer::new(pattern);
+        let mut parser = ParserImpl::<Utf8Encoder, true>::new(lexer, &Utf8Encoder);
+        parser.parse_concat()
+    };
+    assert_eq!(
+        parse("^a$"),
+        Ok(Hir::concat(vec![
+            Hir::anchor(AnchorKind::StartText),
+            Hir::literal("a"),
+            Hir::anchor(AnchorKind::EndText),
+        ]))
+    );
+    assert_eq!(
+        parse("[$^]").map(|hir| hir.to_string()),
+        Ok("['$'] | ['^']".to_string())
+    );
+}
+
+#[test]
+fn parse_non_greedy() {
+    let parse = |pattern: &str| {
+        let lexer = Lexer::new(pattern);
+        let mut parser = ParserImpl::<Utf8Encoder, true>::new(lexer, &Utf8Encoder);
+        parser.parse_concat()
+    };
+    let a = || Hir::literal("a");
+    assert_eq!(parse("a*?"), Ok(Hir::non_greedy_repeat(a(), 0, None)));
+    assert_eq!(parse("a+?"), Ok(Hir::non_greedy_repeat(a(), 1, None)));
+    assert_eq!(parse("a??"), Ok(Hir::non_greedy_repeat(a(), 0, Some(1))));
+    assert_eq!(
+        parse("a{2,5}?"),
+        Ok(Hir::non_greedy_repeat(a(), 2, Some(5)))
+    );
+    assert_eq!(parse("a{2,}?"), Ok(Hir::non_greedy_repeat(a(), 2, None)));
+    assert_eq!(parse("a{2}?"), Ok(Hir::non_greedy_repeat(a(), 2, Some(2))));
+    assert_eq!(
+        parse("a*??"),
+        Ok(Hir::repeat(
+            Hir::non_greedy_repeat(a(), 0, None),
+            0,
+            Some(1)
+        ))
+    );
+    assert_eq!(
+        parse("a*?b").map(|hir| hir.to_string()),
+        Ok(r#""a"*? & "b""#.to_string())
+    );
+}
+
 #[test]
 fn parse_postfix() {
     let parse = |pattern: &str| {
@@ -102,7 +154,13 @@ fn parse_braces() {
         err::out_of_range("1000000000000000000000", 3..25, "allowed range")
     );
     assert_eq!(parse("{}"), err::unexpected("}", 1..2, "a decimal number"));
-    assert_eq!(parse("{,}"), err::unexpected(",", 1..2, "a decimal number"));
+    assert_eq!(parse("{,}"), err::unexpected("}", 2..3, "a decimal number"));
+    assert_eq!(parse("{,3}"), Ok((0, Some(3))));
+    assert_eq!(
+        parse("{,s}"),
+        err::unexpected("s", 2..3, "a decimal number")
+    );
+    assert_eq!(parse("{,0}"), err::zero_repetition(0..4));
     assert_eq!(parse("{0,s}"), err::unexpected("s", 3..4, "`}`"));
     assert_eq!(
         parse("{0s}"),
@@ -142,7 +200,34 @@ fn parse_named_group() {
         parse("(?<123450000000>hello)"),
         err::out_of_range("123450000000", 3..15, "`u32` range")
     );
-    assert_eq!(parse("(?<a>hello)"), err::unexpected("a", 3..4, "decimal"));
+    assert_eq!(
+        parse("(?<year>[0-9]{4})").map(|hir| hir.to_string()),
+        Ok("(?<year> ['0'-'9']{4} )".into())
+    );
+    assert_eq!(
+        parse("(?<_a1>hello)"),
+        Ok(Hir::group("_a1", Hir::literal("hello")))
+    );
+    assert_eq!(parse("(?<1a>hello)"), err::unexpected("a", 4..5, "`>`"));
+    assert_eq!(
+        parse("(?<ў>hello)"),
+        err::unexpected("ў", 3..5, "decimal or identifier")
+    );
+}
+
+#[test]
+fn parse_duplicate_group_names() {
+    let parse = |pattern: &str| Parser::new(Utf8Encoder).parse(pattern);
+    assert_eq!(
+        parse("(?<a>x)(?<a>y)"),
+        err::duplicate_group_name("a", 10..11)
+    );
+    assert_eq!(
+        parse("(?<a>x(?<a>y))"),
+        err::duplicate_group_name("a", 9..10)
+    );
+    assert!(parse("(?<a>x)(?<b>y)").is_ok());
+    assert!(parse("(?<1>x)(?<1>y)").is_ok());
 }
 
 #[test]
@@ -232,6 +317,23 @@ fn parse_squares() {
         parse(r"[a-.]"),
         "expected a character or an escape sequence, but found `.`"
     );
+    assert_eq!(
+        parse(r"[a-\u{110000}]"),
+        "encoder error: invalid unicode code point '\\x110000' for UTF-8 encoding"
+    );
+    assert_eq!(
+        parse(r"[\u{D7FF}-\u{D800}]"),
+        "encoder error: surrogate code point D800h is not supported by UTF-8"
+    );
+    assert_eq!(
+        parse(r"[\u{DFFF}-\u{E000}]"),
+        "encoder error: surrogate code point DFFFh is not supported by UTF-8"
+    );
+    assert_eq!(
+        parse(r"[\u{D7FF}-\u{E000}]"),
+        "([EDh] & [9Fh] & [BFh]) | ([EEh] & [80h] & [80h])"
+    );
+    assert_eq!(parse(r"[\u{10FFFF}]"), "[F4h] & [8Fh] & [BFh] & [BFh]");
 }
 
 #[test]
@@ -275,6 +377,47 @@ fn parse_squares_negated() {
     assert_eq!(parse("a"), err::unexpected("a", 0..1, "`[^`"));
 }
 
+#[test]
+fn parse_class_escape() {
+    let parse = |pattern: &str| {
+        let lexer = Lexer::new(pattern);
+        let mut parser = ParserImpl::<Utf8Encoder, true>::new(lexer, &Utf8Encoder);
+        match parser.parse_class() {
+            Ok(hir) => hir.to_string(),
+            Err(err) => err.to_string(),
+        }
+    };
+    assert_eq!(parse(r"\d"), "['0'-'9']");
+    assert_eq!(parse(r"\w"), "['0'-'9'] | ['A'-'Z'] | ['_'] | ['a'-'z']");
+    assert_eq!(parse(r"\s"), "[09h-0Dh] | [' ']");
+    assert_eq!(parse(r"[\d.]"), parse("."));
+    assert_eq!(parse(r"[\d\-]"), "['-'] | ['0'-'9']");
+    assert_eq!(parse(r"[^\D]"), "['0'-'9']");
+    assert_eq!(parse(r"[\s\S]"), parse("."));
+    assert_eq!(parse(r"[^\W_]"), "['0'-'9'] | ['A'-'Z'] | ['a'-'z']");
+    assert_eq!(
+        parse(r"\D"),
+        concat!(
+            "[00h-'/'] | ",
+            "[':'-7Fh] | ",
+            "([C2h-DFh] & [80h-BFh]) | ",
+            "([E0h] & [A0h-BFh] & [80h-BFh]) | ",
+            "([E1h-ECh] & [80h-BFh] & [80h-BFh]) | ",
+            "([EDh] & [80h-9Fh] & [80h-BFh]) | ",
+            "([EEh-EFh] & [80h-BFh] & [80h-BFh]) | ",
+            "([F0h] & [90h-BFh] & [80h-BFh] & [80h-BFh]) | ",
+            "([F1h-F3h] & [80h-BFh] & [80h-BFh] & [80h-BFh]) | ",
+            "([F4h] & [80h-8Fh] & [80h-BFh] & [80h-BFh])"
+        )
+    );
+    // parsing errors
+    assert_eq!(
+        parse(r"[\d-z]"),
+        "expected a character or an escape sequence, but found `-`"
+    );
+    assert_eq!(parse(r"[a-\d]"), "unsupported escape sequence `\\d`");
+}
+
 #[test]
 fn parse_ascii_escape() {
     let parse = |pattern: &str| {
@@ -294,6 +437,8 @@ fn parse_ascii_escape() {
     assert_eq!(parse(r"\("), Ok('(' as u32));
     assert_eq!(parse(r"\)"), Ok(')' as u32));
     assert_eq!(parse(r"\["), Ok('[' as u32));
+    assert_eq!(parse(r"\^"), Ok('^' as u32));
+    assert_eq!(parse(r"\$"), Ok('$' as u32));
     assert_eq!(parse(r"\]"), Ok(']' as u32));
     assert_eq!(parse(r"\{"), Ok('{' as u32));
     assert_eq!(parse(r"\}"), Ok('}' as u32));
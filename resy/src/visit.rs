@@ -0,0 +1,134 @@
+//! Generic traversals of a [`Hir`] tree, so that a pass over it overrides only
+//! the methods of the variants it is interested in.
+
+use crate::hir::{AnchorKind, ConcatHir, DisjunctHir, GroupHir, Hir, RepeatHir};
+use redt::SetU8;
+
+/// A visitor of the nodes of a [`Hir`] tree, started by [`Hir::visit`].
+///
+/// The default methods of the nested variants visit the children in order, so
+/// an overriding method should call [`HirVisitor::visit_hir`] for the children
+/// itself if it wants to go deeper.
+pub trait HirVisitor {
+    /// Dispatches the `hir` to the method of its variant.
+    fn visit_hir(&mut self, hir: &Hir) {
+        match hir {
+            Hir::Disjunct(disjunct) => self.visit_disjunct(disjunct),
+            Hir::Concat(concat) => self.visit_concat(concat),
+            Hir::Repeat(repeat) => self.visit_repeat(repeat),
+            Hir::Group(group) => self.visit_group(group),
+            Hir::Class(set) => self.visit_class(set),
+            Hir::Literal(bytes) => self.visit_literal(bytes),
+            Hir::Anchor(kind) => self.visit_anchor(*kind),
+        }
+    }
+
+    fn visit_disjunct(&mut self, disjunct: &DisjunctHir) {
+        for alternative in disjunct.alternatives() {
+            self.visit_hir(alternative);
+        }
+    }
+
+    fn visit_concat(&mut self, concat: &ConcatHir) {
+        for item in concat.items() {
+            self.visit_hir(item);
+        }
+    }
+
+    fn visit_repeat(&mut self, repeat: &RepeatHir) {
+        self.visit_hir(repeat.inner());
+    }
+
+    fn visit_group(&mut self, group: &GroupHir) {
+        self.visit_hir(group.inner());
+    }
+
+    fn visit_class(&mut self, _set: &SetU8) {}
+
+    fn visit_literal(&mut self, _bytes: &[u8]) {}
+
+    fn visit_anchor(&mut self, _kind: AnchorKind) {}
+}
+
+/// A transformation of a [`Hir`] tree, started by [`Hir::fold`].
+///
+/// The default methods rebuild the same tree with the [`Hir`] constructors,
+/// folding the children of the nested variants first. So the length hints are
+/// recalculated, and the disjunctions are deduplicated again.
+pub trait HirFolder {
+    /// Dispatches the `hir` to the method of its variant.
+    fn fold_hir(&mut self, hir: Hir) -> Hir {
+        match hir {
+            Hir::Disjunct(disjunct) => self.fold_disjunct(disjunct),
+            Hir::Concat(concat) => self.fold_concat(concat),
+            Hir::Repeat(repeat) => self.fold_repeat(repeat),
+            Hir::Group(group) => self.fold_group(group),
+            Hir::Class(set) => self.fold_class(set),
+            Hir::Literal(bytes) => self.fold_literal(bytes),
+            Hir::Anchor(kind) => self.fold_anchor(kind),
+        }
+    }
+
+    fn fold_disjunct(&mut self, disjunct: DisjunctHir) -> Hir {
+        let alternatives = disjunct.into_alternatives();
+        Hir::disjunct(
+            alternatives
+                .into_iter()
+                .map(|hir| self.fold_hir(hir))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn fold_concat(&mut self, concat: ConcatHir) -> Hir {
+        let items = concat.into_items();
+        Hir::concat(
+            items
+                .into_iter()
+                .map(|hir| self.fold_hir(hir))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn fold_repeat(&mut self, repeat: RepeatHir) -> Hir {
+        let (lower, upper) = repeat.iter_hint();
+        let greedy = repeat.is_greedy();
+        let item = self.fold_hir(repeat.into_inner());
+        if greedy {
+            Hir::repeat(item, lower, upper)
+        } else {
+            Hir::non_greedy_repeat(item, lower, upper)
+        }
+    }
+
+    fn fold_group(&mut self, group: GroupHir) -> Hir {
+        let label = group.label().clone();
+        let item = self.fold_hir(group.into_inner());
+        Hir::group(label, item)
+    }
+
+    fn fold_class(&mut self, set: SetU8) -> Hir {
+        Hir::class(set)
+    }
+
+    fn fold_literal(&mut self, bytes: Vec<u8>) -> Hir {
+        Hir::literal(bytes)
+    }
+
+    fn fold_anchor(&mut self, kind: AnchorKind) -> Hir {
+        Hir::anchor(kind)
+    }
+}
+
+impl Hir {
+    /// Walks the tree with the `visitor`.
+    #[inline]
+    pub fn visit<V: HirVisitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_hir(self);
+    }
+
+    /// Rebuilds the tree with the `folder`.
+    #[inline]
+    pub fn fold<F: HirFolder + ?Sized>(self, folder: &mut F) -> Hir {
+        folder.fold_hir(self)
+    }
+}
@@ -0,0 +1,31 @@
+use pretty_assertions::assert_eq;
+use resy::fold_case;
+
+fn folded(c: char) -> Vec<char> {
+    fold_case(c as u32)
+        .map(|codepoint| char::from_u32(codepoint).unwrap())
+        .collect()
+}
+
+#[test]
+fn case_fold_case() {
+    assert_eq!(folded('a'), ['a', 'A']);
+    assert_eq!(folded('k'), ['k', 'K', '\u{212A}']);
+    assert_eq!(folded('K'), ['K', 'k', '\u{212A}']);
+    assert_eq!(folded('\u{212A}'), ['\u{212A}', 'K', 'k']);
+    assert_eq!(folded('s'), ['s', 'S', 'ſ']);
+    assert_eq!(folded('ſ'), ['ſ', 'S', 's']);
+    assert_eq!(folded('1'), ['1']);
+    assert_eq!(folded('_'), ['_']);
+    assert_eq!(folded('ÿ'), ['ÿ', 'Ÿ']);
+    assert_eq!(folded('Ā'), ['Ā', 'ā']);
+    assert_eq!(folded('ā'), ['ā', 'Ā']);
+    assert_eq!(folded('Σ'), ['Σ', 'σ', 'ς']);
+    assert_eq!(folded('σ'), ['σ', 'Σ', 'ς']);
+    assert_eq!(folded('ς'), ['ς', 'Σ', 'σ']);
+    assert_eq!(folded('ў'), ['ў', 'Ў']);
+    assert_eq!(folded('Ａ'), ['Ａ', 'ａ']);
+    // full case folding isn't supported
+    assert_eq!(folded('ß'), ['ß']);
+    assert_eq!(fold_case(u32::MAX).collect::<Vec<_>>(), [u32::MAX]);
+}
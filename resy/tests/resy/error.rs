@@ -37,4 +37,10 @@ fn error_span() {
 
     let err = Error::InvalidRepetition { span: 0..3 };
     assert_eq!(err.error_span(), 0..3);
+
+    let err = Error::OutOfAlphabet {
+        sequence: "".into(),
+        span: 2..7,
+    };
+    assert_eq!(err.error_span(), 2..7);
 }
@@ -1,7 +1,8 @@
 use ntest::assert_panics;
 use pretty_assertions::{assert_eq, assert_str_eq};
-use redt::{SetU8, ops::*};
-use resy::Hir;
+use redt::{Range, RangeList, SetU8, ops::*};
+use resy::enc::{Encoder, Latin1Encoder, Utf8Encoder};
+use resy::{GroupLabel, Hir, Parser};
 
 #[test]
 fn hir_literal() {
@@ -51,6 +52,13 @@ fn hir_group() {
     assert_str_eq!(group.to_string(), r#"(?<2> "hello" )"#);
     if let Hir::Group(hir) = group {
         assert_eq!(hir.inner(), &Hir::literal("hello"));
+        assert_eq!(hir.label(), &GroupLabel::Index(2));
+    }
+
+    let group = Hir::group("word", Hir::literal(b"hello"));
+    assert_str_eq!(group.to_string(), r#"(?<word> "hello" )"#);
+    if let Hir::Group(hir) = group {
+        assert_eq!(hir.label(), &GroupLabel::Name("word".into()));
     }
 }
 
@@ -66,6 +74,7 @@ fn hir_repeat() {
     if let Hir::Repeat(hir) = repeat {
         assert_eq!(hir.inner(), &Hir::literal("a"));
         assert_eq!(hir.iter_hint(), (0, None));
+        assert!(hir.is_greedy());
     }
 
     let lit = Hir::literal(b"abc");
@@ -109,6 +118,42 @@ fn hir_repeat() {
     });
 }
 
+#[test]
+fn hir_non_greedy_repeat() {
+    let repeat = Hir::non_greedy_repeat(Hir::literal(b"a"), 0, None);
+    assert!(repeat.is_repeat());
+    assert_eq!(repeat.len_hint(), (0, None));
+    assert_str_eq!(repeat.to_string(), r#""a"*?"#);
+    assert_ne!(repeat, Hir::repeat(Hir::literal(b"a"), 0, None));
+    if let Hir::Repeat(hir) = repeat {
+        assert_eq!(hir.iter_hint(), (0, None));
+        assert!(!hir.is_greedy());
+    }
+
+    let repeat = Hir::non_greedy_repeat(Hir::literal(b"a"), 0, Some(1));
+    assert_str_eq!(repeat.to_string(), r#""a"??"#);
+
+    let concat = Hir::concat(vec![Hir::literal(b"ab"), Hir::literal(b"cde")]);
+    let repeat = Hir::non_greedy_repeat(concat, 2, Some(4));
+    assert_eq!(repeat.len_hint(), (10, Some(20)));
+    assert_str_eq!(repeat.to_string(), r#"("ab" & "cde"){2,4}?"#);
+
+    // a repeated repetition is parenthesized, so that a greedy `(a+)?`
+    // differs from a non-greedy `a+?`
+    let a_plus = Hir::repeat(Hir::literal(b"a"), 1, None);
+    let repeat = Hir::repeat(a_plus.clone(), 0, Some(1));
+    assert_str_eq!(repeat.to_string(), r#"("a"+)?"#);
+    let repeat = Hir::non_greedy_repeat(Hir::literal(b"a"), 1, None);
+    assert_str_eq!(repeat.to_string(), r#""a"+?"#);
+    let repeat = Hir::non_greedy_repeat(a_plus, 0, None);
+    assert_str_eq!(repeat.to_string(), r#"("a"+)*?"#);
+
+    assert_panics!({
+        let lit = Hir::literal(b"a");
+        let _ = Hir::non_greedy_repeat(lit, 3, Some(2));
+    });
+}
+
 #[test]
 fn hir_concat() {
     let concat = Hir::concat(vec![Hir::literal(b"ab"), Hir::literal(b"cde")]);
@@ -171,8 +216,209 @@ fn hir_disjunct() {
     }
 }
 
+#[test]
+fn hir_literals() {
+    let parse = |pattern| Parser::new(Utf8Encoder).parse(pattern).unwrap();
+    assert_eq!(parse("foo").literals(), Some(vec![&b"foo"[..]]));
+    assert_eq!(
+        parse("foo|bar|ba").literals(),
+        Some(vec![&b"foo"[..], b"bar", b"ba"])
+    );
+    assert_eq!(parse("ў|").literals(), Some(vec!["ў".as_bytes(), b""]));
+    assert_eq!(parse("foo|ba[rz]").literals(), None);
+    assert_eq!(parse("(foo)").literals(), Some(vec![&b"foo"[..]]));
+    assert_eq!(parse("(?<x>foo)").literals(), None);
+    assert_eq!(parse("^foo").literals(), None);
+    assert_eq!(parse("fo+").literals(), None);
+}
+
 #[test]
 #[should_panic(expected = "empty disjunction is not allowed")]
 fn hir_disjunct_fails() {
     let _ = Hir::disjunct(vec![]);
 }
+
+#[test]
+fn hir_disjunct_dedup() {
+    let disjunct = Hir::disjunct(vec![
+        Hir::literal(b"a"),
+        Hir::literal(b"b"),
+        Hir::literal(b"a"),
+    ]);
+    assert_eq!(
+        disjunct,
+        Hir::disjunct(vec![Hir::literal(b"a"), Hir::literal(b"b")])
+    );
+    assert_str_eq!(disjunct.to_string(), r#""a" | "b""#);
+
+    // the first occurrence keeps its place
+    let plus = Hir::repeat(Hir::literal(b"a"), 1, None);
+    let disjunct = Hir::disjunct(vec![
+        Hir::literal(b"b"),
+        plus.clone(),
+        Hir::literal(b"b"),
+        plus.clone(),
+    ]);
+    assert_str_eq!(disjunct.to_string(), r#""b" | "a"+"#);
+
+    // the only alternative left is returned as is
+    let disjunct = Hir::disjunct(vec![plus.clone(), plus.clone()]);
+    assert_eq!(disjunct, plus);
+
+    assert_eq!(
+        Parser::new(Utf8Encoder).parse("a|b|a").unwrap(),
+        Parser::new(Utf8Encoder).parse("a|b").unwrap()
+    );
+}
+
+#[test]
+fn hir_from_codepoint_ranges() {
+    let dot = RangeList::from(Utf8Encoder.encoding().codepoint_ranges());
+    assert_eq!(
+        Hir::from_codepoint_ranges(&dot, &Utf8Encoder).unwrap(),
+        Parser::new(Utf8Encoder).parse(".").unwrap()
+    );
+
+    let ranges = RangeList::from([
+        Range::new('a' as u32, 'c' as u32),
+        Range::new('ў' as u32, 'ў' as u32),
+    ]);
+    let hir = Hir::from_codepoint_ranges(&ranges, &Utf8Encoder).unwrap();
+    assert_str_eq!(hir.to_string(), "['a'-'c'] | ([D1h] & [9Eh])");
+    assert_eq!(hir, Parser::new(Utf8Encoder).parse("[a-cў]").unwrap());
+
+    let hir = Hir::from_codepoint_ranges(&ranges, &Latin1Encoder);
+    assert!(hir.is_err());
+
+    let hir = Hir::from_codepoint_ranges(&RangeList::default(), &Utf8Encoder).unwrap();
+    assert_eq!(hir, Hir::empty());
+}
+
+fn node_count(hir: &Hir) -> usize {
+    1 + match hir {
+        Hir::Disjunct(disjunct) => disjunct.alternatives().iter().map(node_count).sum(),
+        Hir::Concat(concat) => concat.items().iter().map(node_count).sum(),
+        Hir::Repeat(repeat) => node_count(repeat.inner()),
+        Hir::Group(group) => node_count(group.inner()),
+        Hir::Class(_) | Hir::Literal(_) | Hir::Anchor(_) => 0,
+    }
+}
+
+#[test]
+fn hir_optimize() {
+    let parser = Parser::new(Utf8Encoder::new());
+
+    let parsed = parser.parse("ab[cd]").unwrap();
+    let naive = Hir::concat([
+        Hir::literal(b"a"),
+        Hir::class(SetU8::from(b'b')),
+        Hir::concat([Hir::empty(), Hir::class(SetU8::from(b'c'..=b'd'))]),
+    ]);
+    let optimized = naive.clone().optimize();
+    assert!(node_count(&optimized) < node_count(&naive));
+    assert_eq!(optimized, parsed.clone().optimize());
+    assert_str_eq!(optimized.to_string(), "\"ab\" & ['c'-'d']");
+    assert_eq!(optimized.clone().optimize(), optimized);
+
+    let parsed = parser.parse("a[b]c").unwrap();
+    let optimized = parsed.clone().optimize();
+    assert!(node_count(&optimized) < node_count(&parsed));
+    assert_eq!(optimized, Hir::literal(b"abc"));
+
+    let nested = Hir::disjunct([
+        Hir::disjunct([Hir::literal(b"a"), Hir::literal(b"bc")]),
+        Hir::class(SetU8::from(b'x')),
+        Hir::literal(b"y"),
+        Hir::literal(b"bc"),
+        Hir::literal(b"z"),
+    ]);
+    let optimized = nested.optimize();
+    assert_eq!(
+        optimized,
+        Hir::disjunct([
+            Hir::literal(b"a"),
+            Hir::literal(b"bc"),
+            Hir::class(SetU8::from(b'x'..=b'z')),
+        ])
+    );
+    assert_eq!(optimized.clone().optimize(), optimized);
+
+    let group = parser.parse("(?<g>a[b])+|c").unwrap();
+    let optimized = group.optimize();
+    assert_str_eq!(optimized.to_string(), "(?<g> \"ab\" )+ | \"c\"");
+    assert_eq!(optimized.clone().optimize(), optimized);
+}
+
+#[test]
+fn hir_disjunct_normalization() {
+    let a_plus = Hir::repeat(Hir::literal(b"a"), 1, None);
+    assert_eq!(Hir::disjunct([a_plus.clone(), a_plus.clone()]), a_plus);
+    assert_eq!(
+        Hir::disjunct([a_plus.clone(), Hir::empty()]),
+        Hir::repeat(a_plus.clone(), 0, Some(1))
+    );
+    assert_eq!(
+        Hir::disjunct([a_plus.clone(), Hir::empty(), a_plus.clone(), Hir::empty()]),
+        Hir::repeat(a_plus.clone(), 0, Some(1))
+    );
+    assert!(Hir::disjunct([Hir::empty(), a_plus.clone()]).is_disjunct());
+    assert!(Hir::disjunct([Hir::literal(b"a"), Hir::empty()]).is_disjunct());
+    assert!(Hir::disjunct([Hir::group(1, a_plus), Hir::empty()]).is_disjunct());
+
+    let parser = Parser::new(Utf8Encoder::new());
+    assert_str_eq!(parser.parse("(a|)").unwrap().to_string(), "\"a\" | \"\"");
+    assert_str_eq!(parser.parse("(a+|)").unwrap().to_string(), r#"("a"+)?"#);
+    assert_str_eq!(
+        parser.parse("([ab]c|)").unwrap().to_string(),
+        "(['a'-'b'] & \"c\")?"
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn hir_serde() {
+    let parser = Parser::new(Utf8Encoder);
+    for pattern in [
+        "",
+        "abc",
+        "a|b[0-9]",
+        "^(?<1>x+?|y{2,3})(?<name>z)*$",
+        "[^a]я",
+    ] {
+        let hir = parser.parse(pattern).unwrap();
+        let json = serde_json::to_string(&hir).unwrap();
+        assert_eq!(serde_json::from_str::<Hir>(&json).unwrap(), hir);
+    }
+
+    let hir = Hir::concat([
+        Hir::literal(b"ab"),
+        Hir::repeat(Hir::class(SetU8::from(b'c')), 1, None),
+    ]);
+    assert_eq!(
+        serde_json::to_string(&hir).unwrap(),
+        r#"{"Concat":[{"Literal":[97,98]},{"Repeat":{"lower":1,"upper":null,"greedy":true,"item":{"Class":[[99,99]]}}}]}"#
+    );
+
+    // the length hints are recalculated rather than deserialized
+    let hir: Hir =
+        serde_json::from_str(r#"{"Disjunct":[{"Literal":[97]},{"Literal":[98,99]}]}"#).unwrap();
+    assert_eq!(hir.len_hint(), (1, Some(2)));
+    assert_eq!(
+        hir,
+        Hir::disjunct([Hir::literal(b"a"), Hir::literal(b"bc")])
+    );
+
+    let err = serde_json::from_str::<Hir>(
+        r#"{"Repeat":{"lower":3,"upper":1,"greedy":true,"item":{"Literal":[97]}}}"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("invalid repetition counters: {3,1}")
+    );
+    let err = serde_json::from_str::<Hir>(r#"{"Disjunct":[]}"#).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("empty disjunction is not allowed")
+    );
+}
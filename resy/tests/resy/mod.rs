@@ -1,4 +1,6 @@
+mod case;
 mod error;
 mod hir;
 mod lexis;
 mod syntax;
+mod visit;
@@ -1,5 +1,6 @@
 use pretty_assertions::assert_eq;
-use renc::Utf8Encoder;
+use redt::{Range, RangeList, SetU8};
+use renc::{Latin1Encoder, Utf8Encoder};
 use resy::Parser;
 
 #[test]
@@ -12,6 +13,11 @@ fn parser_parse() {
         }
     };
 
+    assert_eq!(parse(""), r#""""#);
+    assert_eq!(
+        Parser::new(Utf8Encoder::new()).parse(""),
+        Ok(resy::Hir::empty())
+    );
     assert_eq!(parse("asdf|dfgh"), r#""asdf" | "dfgh""#);
     assert_eq!(parse("(asdf)|(?<1>dfgh)"), r#""asdf" | (?<1> "dfgh" )"#);
     assert_eq!(parse("[sdf]"), r#"['d'] | ['f'] | ['s']"#);
@@ -24,3 +30,229 @@ fn parser_parse() {
         "encoder error: surrogate code point D800h is not supported by UTF-8"
     );
 }
+
+#[test]
+fn parser_parse_case_insensitive() {
+    let parse = |pattern: &str| {
+        let parser = Parser::new(Utf8Encoder::new()).with_case_insensitive(true);
+        match parser.parse(pattern) {
+            Ok(hir) => hir.to_string(),
+            Err(err) => err.to_string(),
+        }
+    };
+
+    assert_eq!(parse("a1"), r#"(['A'] | ['a']) & "1""#);
+    assert_eq!(parse("a+1?"), r#"(['A'] | ['a'])+ & "1"?"#);
+    assert_eq!(parse("[a-c]"), "['A'-'C'] | ['a'-'c']");
+    assert_eq!(parse("[Z-a]"), "['A'] | ['Z'-'a'] | ['z']");
+    assert_eq!(parse("[Y-b]"), "['A'-'B'] | ['Y'-'b'] | ['y'-'z']");
+    assert!(parse("[^a]").starts_with("[00h-'@'] | ['B'-'`'] | ['b'-7Fh] | "));
+    assert_eq!(parse("ў"), "([D0h] & [8Eh]) | ([D1h] & [9Eh])");
+    assert_eq!(parse("Ñ"), "([C3h] & [91h]) | ([C3h] & [B1h])");
+    assert_eq!(parse("ā"), "[C4h] & [80h-81h]");
+    assert_eq!(parse("Ÿ"), "([C3h] & [BFh]) | ([C5h] & [B8h])");
+    assert_eq!(parse("Σ"), "([CEh] & [A3h]) | ([CFh] & [82h-83h])");
+    assert_eq!(parse("[А-Б]"), "([D0h] & [90h-91h]) | ([D0h] & [B0h-B1h])");
+    assert_eq!(parse("k"), "['K'] | ['k'] | ([E2h] & [84h] & [AAh])");
+    assert_eq!(parse("ſ"), "['S'] | ['s'] | ([C5h] & [BFh])");
+    assert_eq!(parse("ς"), "([CEh] & [A3h]) | ([CFh] & [82h-83h])");
+    // full case folding isn't supported
+    assert_eq!(parse("ß"), r#""\xC3\x9F""#);
+}
+
+#[test]
+fn parser_parse_latin1() {
+    let parse = |pattern: &str| {
+        let parser = Parser::new(Latin1Encoder::new());
+        match parser.parse(pattern) {
+            Ok(hir) => hir.to_string(),
+            Err(err) => err.to_string(),
+        }
+    };
+
+    assert_eq!(parse("café"), r#""caf\xE9""#);
+    assert_eq!(
+        Parser::new(Latin1Encoder::new()).parse("café").unwrap(),
+        resy::Hir::literal(b"caf\xE9")
+    );
+    assert_eq!(
+        Parser::new(Utf8Encoder::new()).parse("café").unwrap(),
+        resy::Hir::literal("café")
+    );
+    assert_eq!(parse("."), "[00h-FFh]");
+    assert_eq!(parse("[^a]"), "[00h-'`'] | ['b'-FFh]");
+    assert_eq!(parse("[à-ÿ]"), "[E0h-FFh]");
+    assert_eq!(
+        parse("ў"),
+        "encoder error: invalid unicode code point '\\x45E' for ISO-8859-1 encoding"
+    );
+    assert_eq!(
+        parse("[à-ў]"),
+        "encoder error: invalid unicode code point '\\x45E' for ISO-8859-1 encoding"
+    );
+    // the error points to the invalid end of the range
+    let error = Parser::new(Latin1Encoder::new())
+        .parse(r"x[a-\u{100}]")
+        .unwrap_err();
+    assert_eq!(error.error_span(), 4..11);
+
+    // the case counterparts out of the encoding are dropped
+    let parse_ci = |pattern: &str| {
+        let parser = Parser::new(Latin1Encoder::new()).with_case_insensitive(true);
+        match parser.parse(pattern) {
+            Ok(hir) => hir.to_string(),
+            Err(err) => err.to_string(),
+        }
+    };
+    assert_eq!(parse_ci("ÿ"), r#""\xFF""#);
+    assert_eq!(parse_ci("[þ-ÿ]"), "[DEh] | [FEh-FFh]");
+    assert_eq!(parse_ci("k"), "['K'] | ['k']");
+}
+
+#[test]
+fn parser_parse_with_alphabet() {
+    let parse = |pattern: &str| {
+        let parser = Parser::new(Utf8Encoder::new()).with_alphabet(SetU8::from(b' '..=b'~'));
+        match parser.parse(pattern) {
+            Ok(hir) => hir.to_string(),
+            Err(err) => err.to_string(),
+        }
+    };
+
+    assert_eq!(parse("."), "[' '-'~']");
+    assert_eq!(parse("[^a]"), "[' '-'`'] | ['b'-'~']");
+    assert_eq!(parse("\\D"), "[' '-'/'] | [':'-'~']");
+    assert_eq!(parse("[\\x00-a]"), "[' '-'a']");
+
+    assert_eq!(parse("a\\tb"), "`\\t` is out of the allowed byte alphabet");
+    assert_eq!(
+        parse("ab\\x00"),
+        "`\\x00` is out of the allowed byte alphabet"
+    );
+    assert_eq!(parse("ў"), "`ў` is out of the allowed byte alphabet");
+    assert_eq!(
+        parse("a[\\x00-\\x1F]"),
+        "`[\\x00-\\x1F]` is out of the allowed byte alphabet"
+    );
+}
+
+#[test]
+fn parser_parse_with_max_depth() {
+    let parser = Parser::new(Utf8Encoder::new()).with_max_depth(2);
+    assert_eq!(parser.parse("((a)(b))").unwrap().to_string(), r#""ab""#);
+
+    let error = parser.parse("((a)((b)))").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "nesting depth of groups and repetitions exceeds the maximum of 2"
+    );
+    assert_eq!(error.error_span(), 5..6);
+    let error = parser.parse("(?<x>(?<y>(?<z>a)))").unwrap_err();
+    assert_eq!(error.error_span(), 10..12);
+
+    // the default limit stops the recursion long before the stack overflows
+    let parser = Parser::new(Utf8Encoder::new());
+    let pattern = format!("{}a{}", "(".repeat(128), ")".repeat(128));
+    assert!(parser.parse(&pattern).is_ok());
+    let pattern = format!("{}a{}", "(".repeat(10_000), ")".repeat(10_000));
+    let error = parser.parse(&pattern).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "nesting depth of groups and repetitions exceeds the maximum of 128"
+    );
+    assert_eq!(error.error_span(), 128..129);
+
+    // repetitions are nested too
+    let parser = Parser::new(Utf8Encoder::new()).with_max_depth(2);
+    assert!(parser.parse("a**b*(c)+").is_ok());
+    assert!(parser.parse("a*+?").is_ok());
+    assert_eq!(parser.parse("a*+*?").unwrap_err().error_span(), 3..5);
+    assert_eq!(parser.parse("(a*)*").unwrap_err().error_span(), 4..5);
+    assert_eq!(parser.parse("((a)*)").unwrap_err().error_span(), 4..5);
+    let parser = Parser::new(Utf8Encoder::new());
+    let pattern = format!("a{}", "*".repeat(128));
+    assert!(parser.parse(&pattern).is_ok());
+    let pattern = format!("a{}", "*".repeat(200_000));
+    let error = parser.parse(&pattern).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "nesting depth of groups and repetitions exceeds the maximum of 128"
+    );
+    assert_eq!(error.error_span(), 129..130);
+}
+
+#[test]
+fn parser_parse_unexpected_close_span() {
+    let parser = Parser::new(Utf8Encoder::new());
+    let error = parser.parse("a)b").unwrap_err();
+    assert_eq!(error.to_string(), "expected `EOF`, but found `)`");
+    assert_eq!(error.error_span(), 1..2);
+    let error = parser.parse(r"a\mb").unwrap_err();
+    assert_eq!(error.to_string(), r"unsupported escape sequence `\m`");
+    assert_eq!(error.error_span(), 1..3);
+}
+
+#[test]
+fn parser_parse_with_max_pattern_len() {
+    let parser = Parser::new(Utf8Encoder::new()).with_max_pattern_len(4);
+    assert_eq!(parser.parse("a|bc").unwrap().to_string(), r#""a" | "bc""#);
+
+    let error = parser.parse("a|bcd").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "pattern length 5 exceeds the maximum of 4 bytes"
+    );
+    assert_eq!(error.error_span(), 4..5);
+
+    // the length is measured in bytes, and nothing is lexed before the check
+    let error = parser.parse("ўў(").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "pattern length 5 exceeds the maximum of 4 bytes"
+    );
+    assert_eq!(error.error_span(), 4..5);
+
+    // the span starts at a char boundary
+    let pattern = "aўў";
+    let error = parser.parse(pattern).unwrap_err();
+    assert_eq!(error.error_span(), 3..5);
+    assert_eq!(&pattern[error.error_span()], "ў");
+}
+
+#[test]
+fn parser_parse_with_whitespace() {
+    let parse = |parser: &Parser<Utf8Encoder>, pattern: &str| match parser.parse(pattern) {
+        Ok(hir) => hir.to_string(),
+        Err(err) => err.to_string(),
+    };
+
+    let parser = Parser::new(Utf8Encoder::new());
+    assert_eq!(parse(&parser, "\\s"), "[09h-0Dh] | [' ']");
+
+    let whitespace = RangeList::from([Range::new(0x0A, 0x0D), Range::new(0x20, 0x20)]);
+    let parser = Parser::new(Utf8Encoder::new()).with_whitespace(whitespace);
+    assert_eq!(parse(&parser, "\\s"), "[0Ah-0Dh] | [' ']");
+    assert!(parse(&parser, "\\S").starts_with("[00h-09h] | [0Eh-1Fh] | ['!'-7Fh] | "));
+
+    let parser = Parser::new(Latin1Encoder::new()).with_whitespace(RangeList::from([
+        Range::new(0x20, 0x20),
+        Range::new(0xA0, 0xA0),
+    ]));
+    assert_eq!(parser.parse("\\s").unwrap().to_string(), "[' '] | [A0h]");
+    assert_eq!(
+        parser.parse("\\S").unwrap().to_string(),
+        "[00h-1Fh] | ['!'-9Fh] | [A1h-FFh]"
+    );
+}
+
+#[test]
+fn parser_parse_omitted_lower_bound() {
+    let parser = Parser::new(Utf8Encoder::new());
+    assert_eq!(parser.parse("a{,3}"), parser.parse("a{0,3}"));
+    assert_eq!(parser.parse("(ab){,1}c"), parser.parse("(ab)?c"));
+    assert_eq!(
+        parser.parse("a{,}").unwrap_err().to_string(),
+        "expected a decimal number, but found `}`"
+    );
+    assert!(parser.parse("a{,0}").is_err());
+}
@@ -0,0 +1,69 @@
+use pretty_assertions::assert_eq;
+use redt::SetU8;
+use resy::{GroupHir, GroupLabel, Hir, HirFolder, HirVisitor, Parser, enc::Utf8Encoder};
+
+fn parse(pattern: &str) -> Hir {
+    Parser::new(Utf8Encoder).parse(pattern).unwrap()
+}
+
+#[test]
+fn hir_visit() {
+    #[derive(Default)]
+    struct Counter {
+        literals: Vec<Vec<u8>>,
+        classes: usize,
+        groups: Vec<GroupLabel>,
+    }
+
+    impl HirVisitor for Counter {
+        fn visit_group(&mut self, group: &GroupHir) {
+            self.groups.push(group.label().clone());
+            self.visit_hir(group.inner());
+        }
+
+        fn visit_class(&mut self, _set: &SetU8) {
+            self.classes += 1;
+        }
+
+        fn visit_literal(&mut self, bytes: &[u8]) {
+            self.literals.push(bytes.to_vec());
+        }
+    }
+
+    let mut counter = Counter::default();
+    parse("ab(?<x>c[0-9]+|d(?<1>e))*f").visit(&mut counter);
+    assert_eq!(counter.literals, [&b"ab"[..], b"c", b"d", b"e", b"f"]);
+    assert_eq!(counter.classes, 1);
+    assert_eq!(counter.groups, [GroupLabel::from("x"), GroupLabel::from(1)]);
+}
+
+#[test]
+fn hir_fold() {
+    struct Uppercase;
+
+    impl HirFolder for Uppercase {
+        fn fold_literal(&mut self, bytes: Vec<u8>) -> Hir {
+            Hir::literal(bytes.to_ascii_uppercase())
+        }
+    }
+
+    let hir = parse("ab(?<x>c[0-9]+|dé)*f|g$").fold(&mut Uppercase);
+    assert_eq!(hir, parse("AB(?<x>C[0-9]+|Dé)*F|G$"));
+
+    // the default folder rebuilds the same tree
+    struct Identity;
+    impl HirFolder for Identity {}
+    let hir = parse("a(?<1>b|c*?)+d{2,3}");
+    assert_eq!(hir.clone().fold(&mut Identity), hir);
+
+    // the length hints are recalculated
+    struct Double;
+    impl HirFolder for Double {
+        fn fold_literal(&mut self, bytes: Vec<u8>) -> Hir {
+            Hir::literal(bytes.repeat(2))
+        }
+    }
+    let hir = parse("ab|c").fold(&mut Double);
+    assert_eq!(hir.len_hint(), (2, Some(4)));
+    assert_eq!(hir, parse("abab|cc"));
+}
@@ -0,0 +1,57 @@
+//! A compile test: the code generated by `re!` with the `no_std` flag must
+//! build in a `#![no_std]` crate that has only `core` and `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use remc::re;
+
+pub fn match_len(haystack: &str) -> Option<usize> {
+    re!("[a-z]+[0-9]*", no_std)
+        .match_at(haystack, 0)
+        .map(|m| m.len())
+}
+
+pub fn match_error_offset(haystack: &str) -> Option<usize> {
+    re!("ab$", strict, sink, no_std)
+        .match_at(haystack, 0)
+        .err()
+        .map(|error| error.offset())
+}
+
+pub fn find_all(haystack: &str) -> Vec<(usize, usize)> {
+    re!("[0-9]+", sparse, classes, no_std)
+        .find_all(haystack)
+        .iter()
+        .map(|m| (m.start(), m.end()))
+        .collect()
+}
+
+pub fn replace_all(haystack: &str) -> String {
+    re!("x+", flat, anchored_start, no_std).replacen(haystack, "y", 0)
+}
+
+pub fn group(haystack: &str) -> Option<&str> {
+    re!("(?<key>[a-z]+)=[0-9]+", no_std)
+        .match_at(haystack, 0)
+        .and_then(|m| m.group("key"))
+}
+
+pub fn resolved_group(haystack: &str) -> Option<&str> {
+    re!("(?<1>a|ab)(?<2>c|bcd)", no_std)
+        .match_at(haystack, 0)
+        .and_then(|m| m.group(2))
+}
+
+pub fn token(haystack: &str) -> Option<u8> {
+    re! { "if" => 0, "[a-z]+" => 1, first, no_std }
+        .match_at(haystack, 0)
+        .map(|(_, value)| *value)
+}
+
+pub fn find_in_segments(segments: &[&[u8]]) -> Option<(usize, usize)> {
+    re!("ab", no_std).match_segments(segments, 0)
+}